@@ -1,32 +1,121 @@
+use crate::checks::Check;
 use crate::dumpsys;
 use crate::errors::*;
-use crate::ioc::{Suspicion, SuspicionLevel};
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
 use crate::parsers::accessibility::Accessibility;
-use forensic_adb::Device;
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::settings;
+use crate::shell::Shell;
+use async_trait::async_trait;
 
-pub async fn dump(device: &Device) -> Result<Accessibility> {
+pub async fn dump(device: &dyn Shell) -> Result<Accessibility> {
     info!("Reading accessibility settings");
     let out = dumpsys::dump_service(device, "accessibility").await?;
     out.parse::<Accessibility>()
         .context("Failed to parse accessibility service output")
 }
 
+pub struct AccessibilityCheck;
+
+#[async_trait]
+impl Check for AccessibilityCheck {
+    fn id(&self) -> &'static str {
+        "accessibility"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look for enabled accessibility services that can read the screen and input"
+    }
+
+    fn enabled(&self, settings: &scan::Settings) -> bool {
+        !settings.skip_accessibility
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        _rules: &Rules,
+        _settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        info!("Enumerating service list");
+        let services = dumpsys::list_services(device).await?;
+
+        if services.contains("accessibility") {
+            let accessibility = dump(device).await?;
+            for sus in accessibility.audit() {
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.sus(sus).await?;
+            }
+        }
+
+        info!("Enumerating apps with enabled accessibility services");
+        let services = enabled_services(device).await?;
+        for (package, sus) in audit_enabled_services(&services) {
+            warn!("Suspicious {:?}: {}", sus.level, sus.description);
+            report.app(package, sus).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// List the components configured in `enabled_accessibility_services`
+pub async fn enabled_services(device: &dyn Shell) -> Result<Vec<String>> {
+    let value = settings::get(device, "secure", "enabled_accessibility_services").await?;
+    Ok(value
+        .as_deref()
+        .map(parse_enabled_services)
+        .unwrap_or_default())
+}
+
+fn parse_enabled_services(s: &str) -> Vec<String> {
+    s.split(':')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Attribute each enabled accessibility service to the package providing it
+pub fn audit_enabled_services(services: &[String]) -> Vec<(String, Suspicion)> {
+    let mut sus = Vec::new();
+    for component in services {
+        let package = component
+            .split_once('/')
+            .map(|(package, _)| package)
+            .unwrap_or(component);
+        sus.push((
+            package.to_string(),
+            Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Setting,
+                format!("Package {package:?} has an enabled accessibility service: {component:?}"),
+            ),
+        ));
+    }
+    sus
+}
+
 impl Accessibility {
     pub fn audit(&self) -> Vec<Suspicion> {
         let mut sus = Vec::new();
         if let Some(services) = &self.bound_services {
             warn!("Found bound accessibility services: {:?}", services);
-            sus.push(Suspicion {
-                level: SuspicionLevel::High,
-                description: "An accessibility service is bound".to_string(),
-            });
+            sus.push(Suspicion::new(
+                SuspicionLevel::High,
+                Kind::Setting,
+                "An accessibility service is bound",
+            ));
         }
         if let Some(services) = &self.enabled_services {
             warn!("Found enabled accessibility services: {:?}", services);
-            sus.push(Suspicion {
-                level: SuspicionLevel::High,
-                description: format!("An accessibility service is enabled: {:?}", services),
-            });
+            sus.push(Suspicion::new(
+                SuspicionLevel::High,
+                Kind::Setting,
+                format!("An accessibility service is enabled: {:?}", services),
+            ));
         }
         sus
     }
@@ -36,6 +125,38 @@ impl Accessibility {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_enabled_services() {
+        let services = parse_enabled_services(
+            "com.wifi0/com.wifi0.AccessibilityReceiver4:org.example/.ScreenReader\n",
+        );
+        assert_eq!(
+            services,
+            &[
+                "com.wifi0/com.wifi0.AccessibilityReceiver4",
+                "org.example/.ScreenReader",
+            ]
+        );
+        assert_eq!(parse_enabled_services(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_audit_enabled_services() {
+        let sus =
+            audit_enabled_services(&["com.wifi0/com.wifi0.AccessibilityReceiver4".to_string()]);
+        assert_eq!(
+            sus,
+            &[(
+                "com.wifi0".to_string(),
+                Suspicion::new(
+                    SuspicionLevel::Info,
+                    Kind::Setting,
+                    "Package \"com.wifi0\" has an enabled accessibility service: \"com.wifi0/com.wifi0.AccessibilityReceiver4\"",
+                )
+            )]
+        );
+    }
+
     #[test]
     fn test_audit_accessibility_plain() {
         let data = include_str!("../test_data/dumpsys/accessibility/plain.txt");
@@ -58,14 +179,8 @@ mod tests {
         let a = data.parse::<Accessibility>().unwrap();
         let sus = a.audit();
         assert_eq!(&sus, &[
-            Suspicion {
-                level: SuspicionLevel::High,
-                description: "An accessibility service is bound".to_string(),
-            },
-            Suspicion {
-                level: SuspicionLevel::High,
-                description: "An accessibility service is enabled: \"{com.wifi0/com.wifi0.AccessibilityReceiver4}\"".to_string(),
-            },
+            Suspicion::new(SuspicionLevel::High, Kind::Setting, "An accessibility service is bound"),
+            Suspicion::new(SuspicionLevel::High, Kind::Setting, "An accessibility service is enabled: \"{com.wifi0/com.wifi0.AccessibilityReceiver4}\""),
         ]);
     }
 }
@@ -0,0 +1,62 @@
+//! Details about the adb server the scan is talking to
+use crate::errors::*;
+use forensic_adb::Host;
+
+/// Older servers lack features the scan relies on, commands fail or return partial output
+pub const MIN_SERVER_VERSION: u32 = 39;
+
+/// The internal version of the adb server, `41` is reported by adb 1.0.41
+pub async fn server_version(host: &Host) -> Result<u32> {
+    let version = host
+        .execute_host_command("version", true, true)
+        .await
+        .context("Failed to query adb server version")?;
+    parse_version(&version)
+}
+
+fn parse_version(version: &str) -> Result<u32> {
+    u32::from_str_radix(version.trim(), 16)
+        .with_context(|| anyhow!("Invalid adb server version: {version:?}"))
+}
+
+/// A warning for servers that are known to cause problems
+pub fn check_version(version: u32) -> Option<String> {
+    (version < MIN_SERVER_VERSION).then(|| {
+        format!(
+            "adb server version 1.0.{version} is older than 1.0.{MIN_SERVER_VERSION}, update the platform-tools if scans fail or are incomplete"
+        )
+    })
+}
+
+/// Log the version of the adb server, returns a warning if it's unknown or incompatible
+pub async fn check_server(host: &Host) -> Option<String> {
+    let warning = match server_version(host).await {
+        Ok(version) => {
+            info!("Connected to adb server version 1.0.{version}");
+            check_version(version)
+        }
+        Err(err) => Some(format!(
+            "Could not determine the adb server version, scans may fail in unexpected ways: {err:#}"
+        )),
+    };
+    if let Some(warning) = &warning {
+        warn!("{warning}");
+    }
+    warning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version() {
+        assert_eq!(parse_version("0029").unwrap(), 41);
+        assert!(parse_version("OKAY").is_err());
+        assert_eq!(check_version(41), None);
+        assert_eq!(
+            check_version(32),
+            Some("adb server version 1.0.32 is older than 1.0.39, update the platform-tools if scans fail or are incomplete".to_string())
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Computers that are allowed to connect to the device with adb
+use crate::checks::Check;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::network;
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use async_trait::async_trait;
+use std::env;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Only readable with root on most devices
+const ADB_KEYS: &str = "/data/misc/adb/adb_keys";
+/// More authorized computers than this, besides the one running the scan, are unusual for a personal phone
+const MAX_EXPECTED_KEYS: usize = 2;
+
+/// An entry of `adb_keys`, a public key followed by the user and host it was created on
+#[derive(Debug, PartialEq)]
+pub struct AuthorizedKey<'a> {
+    pub key: &'a str,
+    pub comment: Option<&'a str>,
+}
+
+pub fn parse(keys: &str) -> Vec<AuthorizedKey<'_>> {
+    keys.lines()
+        .filter_map(|line| {
+            let mut words = line.trim().splitn(2, char::is_whitespace);
+            let key = words.next().filter(|key| !key.is_empty())?;
+            let comment = words.next().map(str::trim).filter(|c| !c.is_empty());
+            Some(AuthorizedKey { key, comment })
+        })
+        .collect()
+}
+
+/// The public key of this computer, it's authorized on the device for the scan itself
+async fn local_key() -> Option<String> {
+    let path = match env::var_os("ANDROID_USER_HOME") {
+        Some(home) => PathBuf::from(home).join("adbkey.pub"),
+        None => dirs::home_dir()?.join(".android/adbkey.pub"),
+    };
+    let buf = fs::read_to_string(&path).await.ok()?;
+    buf.split_whitespace().next().map(String::from)
+}
+
+pub fn audit(keys: &[AuthorizedKey<'_>], local_key: Option<&str>) -> Vec<Suspicion> {
+    let others = keys
+        .iter()
+        .filter(|entry| Some(entry.key) != local_key)
+        .collect::<Vec<_>>();
+    let mut out = others
+        .iter()
+        .map(|entry| {
+            Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Setting,
+                format!(
+                    "Another computer is authorized to use adb on this device: {:?}",
+                    entry.comment.unwrap_or("unnamed key")
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    if others.len() > MAX_EXPECTED_KEYS {
+        out.push(Suspicion::new(SuspicionLevel::Medium, Kind::Setting, format!(
+                "{} other computers are authorized to use adb on this device",
+                others.len()
+            )).with_remediation(Some("Revoke USB debugging authorizations in the developer options, then authorize only this computer again".to_string())));
+    }
+    out
+}
+
+pub struct AdbKeysCheck;
+
+#[async_trait]
+impl Check for AdbKeysCheck {
+    fn id(&self) -> &'static str {
+        "adb-keys"
+    }
+
+    fn description(&self) -> &'static str {
+        "List the computers that are authorized to use adb, if the device allows reading them"
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        _rules: &Rules,
+        _settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        info!("Reading authorized adb keys");
+        // most devices only allow this with root, it's not a failure of the scan
+        let Some(keys) = network::cat(device, ADB_KEYS).await? else {
+            info!("Authorized adb keys {ADB_KEYS:?} are not readable, other computers with adb access can't be listed");
+            return Ok(());
+        };
+        let keys = parse(&keys);
+        debug!("Device has {} authorized adb keys", keys.len());
+        let local_key = local_key().await;
+        for sus in audit(&keys, local_key.as_deref()) {
+            info!("Advisory {:?}: {}", sus.level, sus.description);
+            report.sus(sus).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit() {
+        let keys = parse(
+            "QAAAAOwn1== user@laptop\nQAAAAB5x== analyst@station\n\nQAAAAC9y==\nQAAAAD1z== root@kali\n",
+        );
+        assert_eq!(
+            keys[0],
+            AuthorizedKey {
+                key: "QAAAAOwn1==",
+                comment: Some("user@laptop"),
+            }
+        );
+        assert_eq!(keys.len(), 4);
+
+        let sus = audit(&keys[..2], Some("QAAAAB5x=="));
+        assert_eq!(
+            sus,
+            &[Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Setting,
+                "Another computer is authorized to use adb on this device: \"user@laptop\""
+            )]
+        );
+
+        let sus = audit(&keys, Some("QAAAAB5x=="));
+        assert_eq!(sus.len(), 4);
+        assert_eq!(
+            sus[3].description,
+            "3 other computers are authorized to use adb on this device"
+        );
+        assert_eq!(sus[3].level, SuspicionLevel::Medium);
+    }
+}
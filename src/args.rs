@@ -1,4 +1,9 @@
 use crate::errors::*;
+use crate::i18n::Lang;
+use crate::ioc::SuspicionLevel;
+use crate::scan;
+use crate::utils;
+use chrono::NaiveDateTime;
 use clap::{ArgAction, CommandFactory, Parser};
 use clap_complete::Shell;
 use std::io::stdout;
@@ -19,6 +24,73 @@ pub struct Args {
         default_value = "auto"
     )]
     pub start_adb_server: AdbServerChoice,
+    /// Prefix findings with symbols that convey severity without relying on color
+    #[arg(long, global = true, env = "SPYTRAP_SEVERITY_SYMBOLS")]
+    pub severity_symbols: bool,
+    /// Prefix findings with a symbol for what they are about, eg. an app, a file or a setting
+    #[arg(long, global = true, env = "SPYTRAP_KIND_SYMBOLS")]
+    pub kind_symbols: bool,
+    /// Ring the terminal bell for findings of this severity or higher (high if no level is given)
+    #[arg(
+        long,
+        global = true,
+        value_name = "level",
+        env = "SPYTRAP_BELL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "high"
+    )]
+    pub bell: Option<SuspicionLevel>,
+    /// Show informational advisories in the report view, they are hidden until the filter is changed otherwise
+    #[arg(long, global = true, env = "SPYTRAP_SHOW_ADVISORIES")]
+    pub show_advisories: bool,
+    /// Show findings in the order they are reported instead of sorting them by severity
+    #[arg(long, global = true, env = "SPYTRAP_ARRIVAL_ORDER")]
+    pub arrival_order: bool,
+    /// Replace the serial with a hash and leave out device details and notes in exported reports, and bugreports in evidence bundles
+    #[arg(long, global = true, env = "SPYTRAP_ANONYMIZE")]
+    pub anonymize: bool,
+    /// Hide serials and account names on screen, exports still contain them (toggle with m)
+    #[arg(long, global = true, env = "SPYTRAP_MASK")]
+    pub mask: bool,
+    /// Allow uninstalling and disabling apps from the report view (after confirmation)
+    #[arg(long, global = true, env = "SPYTRAP_ALLOW_REMEDIATION")]
+    pub allow_remediation: bool,
+    /// Never modify the device, even if --allow-remediation is set
+    #[arg(long, global = true, env = "SPYTRAP_READ_ONLY")]
+    pub read_only: bool,
+    /// Record the findings of scans to search them with the history subcommand, and the device fingerprints to compare later scans with
+    #[arg(long, global = true, env = "SPYTRAP_KEEP_HISTORY")]
+    pub keep_history: bool,
+    /// Ask the operator to confirm they are authorized to scan before adb is used
+    #[arg(long, global = true, env = "SPYTRAP_REQUIRE_CONSENT")]
+    pub require_consent: bool,
+    /// Close the report and return to the device list after this many seconds without input, for shared stations
+    #[arg(
+        long,
+        global = true,
+        value_name = "seconds",
+        env = "SPYTRAP_IDLE_TIMEOUT"
+    )]
+    pub idle_timeout: Option<u64>,
+    /// Disconnect network devices from adb when the idle timeout clears the session
+    #[arg(long, global = true, env = "SPYTRAP_IDLE_DISCONNECT")]
+    pub idle_disconnect: bool,
+    /// Language of the interface and the guided scan, defaults to the language of the locale
+    #[arg(long, global = true, value_name = "lang", env = "SPYTRAP_LANG")]
+    pub lang: Option<Lang>,
+    /// Read defaults for the interactive interface from this file
+    #[arg(long, global = true, value_name = "path", env = "SPYTRAP_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Open a session that has been saved from the report view with s, to continue its triage
+    #[arg(long, value_name = "path")]
+    pub session: Option<PathBuf>,
+    /// Scan a built-in mock device and verify the expected findings are reported
+    #[arg(long)]
+    pub self_test: bool,
+    /// Print every check the scanner can run as json and exit
+    #[arg(long)]
+    pub list_checks: bool,
     #[command(subcommand)]
     pub subcommand: Option<SubCommand>,
 }
@@ -33,16 +105,20 @@ pub enum AdbServerChoice {
 #[derive(Debug, Parser)]
 pub enum SubCommand {
     Scan(Scan),
+    Compare(Compare),
     List(List),
     DownloadIoc(DownloadIoc),
     Completions(Completions),
+    History(History),
+    Guide(Guide),
+    Profile(Profile),
 }
 
-/// Run a scan on a given device
+/// Run a scan on a given device and print a readable report of the findings
 #[derive(Debug, Parser)]
 pub struct Scan {
     pub serial: Option<String>,
-    /// Use specific rule files instead of latest downloaded
+    /// Use specific rule files instead of latest downloaded, directories are loaded in full
     #[arg(long)]
     pub rules: Vec<PathBuf>,
     #[arg(long)]
@@ -50,6 +126,90 @@ pub struct Scan {
     /// Do not scan apps for suspicious permissions
     #[arg(long)]
     pub skip_apps: bool,
+    /// Do not inspect accessibility services
+    #[arg(long)]
+    pub skip_accessibility: bool,
+    /// Additional directories to search for files named after known stalkerware (eg. /sdcard/Documents)
+    #[arg(long = "scan-path", value_name = "path")]
+    pub scan_paths: Vec<String>,
+    /// Number of apps to inspect in parallel, use 1 with --throttle for slow devices
+    #[arg(long, value_name = "n", default_value_t = scan::DEFAULT_CONCURRENCY)]
+    pub concurrency: usize,
+    /// Run a failed adb command a second time before giving up on a check
+    #[arg(long)]
+    pub retry: bool,
+    /// Wait at least this many milliseconds between adb commands, for slow or overloaded devices
+    #[arg(long, value_name = "ms")]
+    pub throttle: Option<u64>,
+    /// Give up on an adb command after this many seconds, eg. if the device stopped responding (0 waits forever)
+    #[arg(long, value_name = "seconds", default_value_t = scan::DEFAULT_TIMEOUT)]
+    pub timeout: u64,
+    /// Only audit apps installed or updated after this date, eg. `2024-06-01` or `2024-06-01 18:30`, known stalkerware is always reported
+    #[arg(long, value_name = "date", value_parser = utils::parse_datetime)]
+    pub since: Option<NaiveDateTime>,
+    /// Report matches of rules of a category with at least this level, eg. `watchware=high`, matches below low are left as they are
+    #[arg(long, value_name = "category=level", value_parser = parse_escalation)]
+    pub escalate: Vec<(String, SuspicionLevel)>,
+    /// Stop the scan at the first finding of this severity or higher (high if no level is given)
+    #[arg(
+        long,
+        value_name = "level",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "high",
+        conflicts_with = "watch"
+    )]
+    pub fail_fast: Option<SuspicionLevel>,
+    /// Don't compare the device with the previous scan or record its fingerprint, even with --keep-history
+    #[arg(long)]
+    pub no_fingerprint: bool,
+    /// Record the progress of the scan, so it can be continued with --resume if it's interrupted
+    #[arg(long, conflicts_with = "watch")]
+    pub checkpoint: bool,
+    /// Continue an interrupted scan of the same device from its checkpoint, this implies --checkpoint
+    #[arg(long, conflicts_with = "watch")]
+    pub resume: bool,
+    /// Scan an extracted backup or directory of pulled files instead of a device
+    #[arg(long, value_name = "path")]
+    pub offline: Option<PathBuf>,
+    /// Write scan events as json lines to this file while the scan is running (`-` for stdout)
+    #[arg(long, value_name = "path")]
+    pub json_progress: Option<PathBuf>,
+    /// Listen on a unix domain socket and stream scan events as json lines to every client, only the current user can connect
+    #[arg(long, value_name = "path", conflicts_with_all = ["json_progress", "syslog", "watch"])]
+    pub json_socket: Option<PathBuf>,
+    /// Also allow clients whose primary group is this gid to connect to --json-socket, eg. an unprivileged interface
+    #[arg(long, value_name = "gid", requires = "json_socket")]
+    pub json_socket_group: Option<u32>,
+    /// Only check the network settings, hosts file and active connections for c2 servers
+    #[arg(long, conflicts_with = "offline")]
+    pub network_only: bool,
+    /// Send findings to the local syslog daemon
+    #[arg(long, conflicts_with = "json_progress")]
+    pub syslog: bool,
+    /// Keep running and scan every device once it's connected, writing a report for each
+    #[arg(long, conflicts_with_all = ["serial", "offline", "json_progress"])]
+    pub watch: bool,
+    /// Directory to write reports to in --watch mode
+    #[arg(long, value_name = "path", default_value = ".", requires = "watch")]
+    pub report_dir: PathBuf,
+}
+
+fn parse_escalation(s: &str) -> Result<(String, SuspicionLevel)> {
+    let (category, level) = s
+        .split_once('=')
+        .with_context(|| anyhow!("Expected category=level, eg. watchware=high: {s:?}"))?;
+    Ok((category.to_string(), level.parse()?))
+}
+
+/// Scan two devices and show which indicators they have in common
+#[derive(Debug, Parser)]
+pub struct Compare {
+    pub first: String,
+    pub second: String,
+    /// Use specific rule files instead of latest downloaded, directories are loaded in full
+    #[arg(long)]
+    pub rules: Vec<PathBuf>,
 }
 
 /// List all available devices
@@ -60,6 +220,30 @@ pub struct List {}
 #[derive(Debug, Parser)]
 pub struct DownloadIoc {}
 
+/// Walk through connecting, scanning and reviewing a device step by step, for non-experts
+#[derive(Debug, Parser)]
+pub struct Guide {
+    /// Use specific rule files instead of latest downloaded, directories are loaded in full
+    #[arg(long)]
+    pub rules: Vec<PathBuf>,
+}
+
+/// Write the overview of a device (os version, patch level, app count) without scanning it
+#[derive(Debug, Parser)]
+pub struct Profile {
+    pub serial: Option<String>,
+    /// Write the profile to this file instead of stdout
+    #[arg(short, long, value_name = "path", default_value = "-")]
+    pub output: PathBuf,
+}
+
+/// Search the findings of past scans recorded with --keep-history
+#[derive(Debug, Parser)]
+pub struct History {
+    /// A package name, domain or rule name to look for
+    pub term: String,
+}
+
 /// Generate shell completions
 #[derive(Debug, Parser)]
 pub struct Completions {
@@ -0,0 +1,94 @@
+//! Capture an android bugreport at the time of the scan, for offline analysis
+use crate::errors::*;
+use crate::export;
+use crate::shell::Shell;
+use crate::utils;
+use forensic_adb::{Device, UnixPath};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+const CMD: &str = "bugreportz";
+
+/// Find the location of the zip file on the device
+fn parse_output(output: &str) -> Result<&str> {
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("OK:") {
+            return Ok(path.trim());
+        } else if let Some(reason) = line.strip_prefix("FAIL:") {
+            bail!("Device failed to create bugreport: {}", reason.trim());
+        }
+    }
+    bail!("Unexpected output from {CMD:?}: {output:?}")
+}
+
+pub fn filename(serial: &str, timestamp: i64) -> String {
+    let serial = export::safe_serial(serial);
+    format!("spytrap-bugreport-{serial}-{timestamp}.zip")
+}
+
+/// Create a bugreport on the device and download it into `dir`, this takes a few minutes
+///
+/// In read-only mode the bugreport is left on the device, it's not deleted after the download.
+pub async fn capture(device: &Device, dir: &Path, read_only: bool) -> Result<PathBuf> {
+    info!("Creating bugreport on device {:?}", device.serial);
+    let output = device
+        .exec(CMD)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", CMD))?;
+    let output = String::from_utf8_lossy(&output);
+    let remote = UnixPath::new(parse_output(&output)?);
+
+    let name = filename(&device.serial, utils::now());
+    let path = dir.join(&name);
+    // the download is only moved into place once it's complete
+    let partial = dir.join(format!(".{name}.part"));
+    info!("Downloading bugreport from {remote:?} to {path:?}");
+    if let Err(err) = download(device, remote, &partial).await {
+        fs::remove_file(&partial).await.ok();
+        return Err(err);
+    }
+    fs::rename(&partial, &path)
+        .await
+        .with_context(|| anyhow!("Failed to move bugreport to {path:?}"))?;
+
+    if read_only {
+        info!("Leaving bugreport on device in read-only mode: {remote:?}");
+    } else if let Err(err) = device.remove(remote).await {
+        warn!("Failed to delete bugreport from device: {err:#}");
+    }
+
+    Ok(path)
+}
+
+async fn download(device: &Device, remote: &UnixPath, path: &Path) -> Result<()> {
+    let mut file = fs::File::create(path)
+        .await
+        .with_context(|| anyhow!("Failed to create file at {path:?}"))?;
+    device
+        .pull(remote, &mut file)
+        .await
+        .with_context(|| anyhow!("Failed to download bugreport from {remote:?}"))?;
+    file.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let path = parse_output("BEGIN:/bugreports/x.zip\nOK:/data/user_de/0/com.android.shell/files/bugreports/bugreport-panther-AP2A.2024-07-02-23-34-14.zip\n").unwrap();
+        assert_eq!(
+            path,
+            "/data/user_de/0/com.android.shell/files/bugreports/bugreport-panther-AP2A.2024-07-02-23-34-14.zip"
+        );
+        let err = parse_output("FAIL:Could not open socket\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Device failed to create bugreport: Could not open socket"
+        );
+        assert!(parse_output("/system/bin/sh: bugreportz: inaccessible or not found\n").is_err());
+    }
+}
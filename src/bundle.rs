@@ -0,0 +1,131 @@
+//! Archive a report together with the files collected from the device, for case archival
+use crate::errors::*;
+use crate::export::{self, Report};
+use chrono::{Datelike, TimeZone, Timelike, Utc};
+use std::fs::{self, File};
+use std::io::{self, Seek, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+pub fn filename(serial: &str, timestamp: i64) -> String {
+    let serial = export::safe_serial(serial);
+    format!("spytrap-evidence-{serial}-{timestamp}.zip")
+}
+
+/// The modification time of all entries, zip can't represent times before 1980
+fn zip_datetime(timestamp: i64) -> DateTime {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .and_then(|time| {
+            DateTime::from_date_and_time(
+                u16::try_from(time.year()).ok()?,
+                u8::try_from(time.month()).ok()?,
+                u8::try_from(time.day()).ok()?,
+                u8::try_from(time.hour()).ok()?,
+                u8::try_from(time.minute()).ok()?,
+                u8::try_from(time.second()).ok()?,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Write the json report and copy the artifacts into the zip file, without reading them into memory
+fn write_zip<W: Write + Seek>(
+    out: W,
+    json: &str,
+    artifacts: &[PathBuf],
+    timestamp: i64,
+) -> Result<W> {
+    // the collected files are usually compressed already, bugreports can be larger than 4GB
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .last_modified_time(zip_datetime(timestamp))
+        .large_file(true);
+
+    let mut zip = ZipWriter::new(out);
+    zip.start_file("report.json", options)?;
+    zip.write_all(json.as_bytes())?;
+    for artifact in artifacts {
+        let name = artifact
+            .file_name()
+            .with_context(|| anyhow!("Artifact has no file name: {artifact:?}"))?
+            .to_string_lossy();
+        debug!("Adding {artifact:?} to evidence bundle");
+        let mut file = File::open(artifact)
+            .with_context(|| anyhow!("Failed to open artifact {artifact:?}"))?;
+        zip.start_file(format!("artifacts/{name}"), options)?;
+        io::copy(&mut file, &mut zip)
+            .with_context(|| anyhow!("Failed to add artifact {artifact:?}"))?;
+    }
+    let out = zip.finish()?;
+    Ok(out)
+}
+
+/// Write the json report and the artifacts into a zip file inside of `dir`
+pub async fn write(
+    dir: &Path,
+    report: &Report<'_>,
+    artifacts: &[PathBuf],
+    timestamp: i64,
+) -> Result<PathBuf> {
+    let path = dir.join(filename(&report.serial, timestamp));
+    let json = export::Format::Json.render(report)?;
+    let artifacts = artifacts.to_vec();
+    let dest = path.clone();
+    tokio::task::spawn_blocking(move || {
+        let file =
+            File::create(&dest).with_context(|| anyhow!("Failed to create file at {dest:?}"))?;
+        let ret = write_zip(file, &json, &artifacts, timestamp)
+            .map(drop)
+            .with_context(|| anyhow!("Failed to write evidence bundle to {dest:?}"));
+        if ret.is_err() {
+            // don't leave a truncated bundle behind that looks like a complete one
+            fs::remove_file(&dest).ok();
+        }
+        ret
+    })
+    .await??;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    #[test]
+    fn test_zip_datetime() {
+        // 2024-07-02 23:34:14 UTC
+        let time = zip_datetime(1719963254);
+        assert_eq!((time.year(), time.month(), time.day()), (2024, 7, 2));
+        assert_eq!((time.hour(), time.minute(), time.second()), (23, 34, 14));
+        assert_eq!(zip_datetime(0), DateTime::default());
+    }
+
+    #[test]
+    fn test_write_zip() {
+        let dir = std::env::temp_dir().join(format!("spytrap-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let artifact = dir.join("bugreport.zip");
+        std::fs::write(&artifact, b"PK").unwrap();
+
+        let buf = write_zip(Cursor::new(Vec::new()), "{}\n", &[artifact], 1719963254).unwrap();
+        let mut zip = ZipArchive::new(buf).unwrap();
+        assert_eq!(
+            zip.file_names().collect::<Vec<_>>(),
+            &["report.json", "artifacts/bugreport.zip"]
+        );
+        let mut content = String::new();
+        zip.by_name("report.json")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "{}\n");
+
+        assert!(write_zip(Cursor::new(Vec::new()), "{}\n", &[dir.join("missing")], 0).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
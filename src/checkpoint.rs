@@ -0,0 +1,196 @@
+//! Record the progress of a scan, so an interrupted scan can continue where it stopped
+use crate::errors::*;
+use crate::ioc::Suspicion;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Something that has been reported during the scan and is replayed when resuming
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Recorded {
+    Suspicion { sus: Suspicion },
+    App { name: String, sus: Suspicion },
+    Warning { message: String },
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    pub serial: String,
+    /// checks that have run to completion
+    pub checks: BTreeSet<String>,
+    /// apps that have been inspected by the apps check
+    pub apps: BTreeSet<String>,
+    /// the events of completed checks and apps
+    pub events: Vec<Recorded>,
+}
+
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    state: State,
+    /// events of the check or app that is still running, dropped if the scan is interrupted
+    pending: Vec<Recorded>,
+}
+
+impl Checkpoint {
+    /// Start recording, with `resume` the previous state of the same device is picked up again
+    pub async fn open(path: PathBuf, serial: &str, resume: bool) -> Result<Self> {
+        let mut state = State {
+            serial: serial.to_string(),
+            ..Default::default()
+        };
+        if resume {
+            match fs::read(&path).await {
+                Ok(buf) => {
+                    let previous = serde_json::from_slice::<State>(&buf)
+                        .with_context(|| anyhow!("Failed to parse checkpoint from {path:?}"))?;
+                    if previous.serial == serial {
+                        info!(
+                            "Resuming scan, {} checks and {} apps are already done",
+                            previous.checks.len(),
+                            previous.apps.len()
+                        );
+                        state = previous;
+                    } else {
+                        warn!(
+                            "Checkpoint is for a different device ({:?}), starting a new scan",
+                            previous.serial
+                        );
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    info!("No checkpoint found, starting a new scan");
+                }
+                Err(err) => {
+                    return Err(err)
+                        .with_context(|| anyhow!("Failed to read checkpoint from {path:?}"))
+                }
+            }
+        }
+        Ok(Checkpoint {
+            path,
+            state,
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn events(&self) -> &[Recorded] {
+        &self.state.events
+    }
+
+    pub fn is_check_done(&self, id: &str) -> bool {
+        self.state.checks.contains(id)
+    }
+
+    pub fn is_app_done(&self, id: &str) -> bool {
+        self.state.apps.contains(id)
+    }
+
+    pub fn record(&mut self, event: Recorded) {
+        self.pending.push(event);
+    }
+
+    pub async fn check_done(&mut self, id: &str) -> Result<()> {
+        self.state.checks.insert(id.to_string());
+        self.commit().await
+    }
+
+    pub async fn app_done(&mut self, id: &str) -> Result<()> {
+        self.state.apps.insert(id.to_string());
+        self.commit().await
+    }
+
+    /// The check has failed and runs again when resuming, so its events are not kept
+    pub fn check_failed(&mut self) {
+        self.pending.clear();
+    }
+
+    async fn commit(&mut self) -> Result<()> {
+        self.state.events.append(&mut self.pending);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| anyhow!("Failed to create directory at {parent:?}"))?;
+        }
+        let buf = serde_json::to_vec(&self.state)?;
+        fs::write(&self.path, &buf)
+            .await
+            .with_context(|| anyhow!("Failed to write checkpoint to {:?}", self.path))?;
+        Ok(())
+    }
+
+    /// The scan has completed, there's nothing left to resume
+    pub async fn remove(&self) -> Result<()> {
+        match fs::remove_file(&self.path).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => {
+                Err(err).with_context(|| anyhow!("Failed to remove checkpoint at {:?}", self.path))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Kind, SuspicionLevel};
+
+    #[tokio::test]
+    async fn test_resume() {
+        let path =
+            std::env::temp_dir().join(format!("spytrap-checkpoint-{}.json", std::process::id()));
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware",
+        );
+
+        let mut checkpoint = Checkpoint::open(path.clone(), "ABC123", false)
+            .await
+            .unwrap();
+        checkpoint.record(Recorded::App {
+            name: "com.wifi0".to_string(),
+            sus: sus.clone(),
+        });
+        checkpoint.app_done("com.wifi0").await.unwrap();
+        // interrupted before the next app is done
+        checkpoint.record(Recorded::Warning {
+            message: "Failed to inspect package".to_string(),
+        });
+        drop(checkpoint);
+
+        let mut checkpoint = Checkpoint::open(path.clone(), "ABC123", true)
+            .await
+            .unwrap();
+        checkpoint.record(Recorded::Warning {
+            message: "Check \"network\" has failed".to_string(),
+        });
+        checkpoint.check_failed();
+        checkpoint.check_done("files").await.unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::open(path.clone(), "ABC123", true)
+            .await
+            .unwrap();
+        assert!(checkpoint.is_app_done("com.wifi0"));
+        assert!(!checkpoint.is_check_done("apps"));
+        assert!(checkpoint.is_check_done("files"));
+        assert!(!checkpoint.is_check_done("network"));
+        assert_eq!(
+            checkpoint.events(),
+            &[Recorded::App {
+                name: "com.wifi0".to_string(),
+                sus,
+            }]
+        );
+
+        let other = Checkpoint::open(path.clone(), "XYZ", true).await.unwrap();
+        assert!(!other.is_app_done("com.wifi0"));
+
+        checkpoint.remove().await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::accessibility::AccessibilityCheck;
+use crate::adb_keys::AdbKeysCheck;
+use crate::device_policy::DeviceAdminCheck;
+use crate::errors::*;
+use crate::files::FilesCheck;
+use crate::fingerprint::FingerprintCheck;
+use crate::network::NetworkCheck;
+use crate::package::AppsCheck;
+use crate::probes::ProbesCheck;
+use crate::rules::Rules;
+use crate::scan::{ScanNotifier, Settings};
+use crate::settings::SettingsCheck;
+use crate::shell::Shell;
+use async_trait::async_trait;
+use serde::Serialize;
+
+/// A self-contained inspection of a device that reports its findings through the notifier
+#[async_trait]
+pub trait Check: Send + Sync {
+    /// A short, stable identifier for this check
+    fn id(&self) -> &'static str;
+
+    /// What the check inspects, in a single sentence
+    fn description(&self) -> &'static str;
+
+    /// Whether the check should run with the given scan settings
+    fn enabled(&self, _settings: &Settings) -> bool {
+        true
+    }
+
+    /// Whether the check can run against an offline snapshot instead of a live device
+    fn offline(&self) -> bool {
+        false
+    }
+
+    /// Whether the check looks for network indicators, the only ones run with `--network-only`
+    fn network(&self) -> bool {
+        false
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        rules: &Rules,
+        settings: &Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()>;
+}
+
+/// All checks known to the scanner, in the order they are executed
+pub fn registry() -> Vec<Box<dyn Check>> {
+    vec![
+        Box::new(SettingsCheck),
+        Box::new(AccessibilityCheck),
+        Box::new(DeviceAdminCheck),
+        Box::new(AppsCheck),
+        Box::new(FilesCheck),
+        Box::new(NetworkCheck),
+        Box::new(AdbKeysCheck),
+        Box::new(FingerprintCheck),
+        Box::new(ProbesCheck),
+    ]
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckInfo {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub offline: bool,
+    pub network: bool,
+}
+
+/// Describe every check of the registry, for `--list-checks`
+pub fn manifest() -> Vec<CheckInfo> {
+    registry()
+        .iter()
+        .map(|check| CheckInfo {
+            id: check.id(),
+            description: check.description(),
+            offline: check.offline(),
+            network: check.network(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_registry_ids_unique() {
+        let checks = registry();
+        let ids = checks.iter().map(|c| c.id()).collect::<HashSet<_>>();
+        assert_eq!(ids.len(), checks.len());
+    }
+
+    #[test]
+    fn test_manifest() {
+        let manifest = manifest();
+        assert!(manifest.iter().all(|check| !check.description.is_empty()));
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(json[0]["id"], "settings");
+        assert_eq!(json[3]["id"], "apps");
+        assert_eq!(json[3]["offline"], true);
+    }
+}
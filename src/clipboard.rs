@@ -0,0 +1,29 @@
+//! Copy text to the clipboard through the terminal, with an OSC 52 escape sequence
+//!
+//! This also works over ssh, but some terminals need to be configured to allow it.
+use crate::errors::*;
+use base64::prelude::*;
+use std::io::{self, Write};
+
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))
+}
+
+pub fn copy(text: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(osc52(text).as_bytes())
+        .and_then(|_| stdout.flush())
+        .context("Failed to write to terminal")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        assert_eq!(osc52("spytrap"), "\x1b]52;c;c3B5dHJhcA==\x07");
+    }
+}
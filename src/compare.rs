@@ -0,0 +1,208 @@
+//! Scan two devices and highlight the indicators they have in common
+use crate::errors::*;
+use crate::ioc::Suspicion;
+use crate::rules::Rules;
+use crate::scan::{self, Findings};
+use crate::utils;
+use forensic_adb::{DeviceInfo, Host};
+use std::fmt::Write;
+
+/// A finding, either about the device or about one of its apps
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Entry<'a> {
+    pub app: Option<&'a str>,
+    pub sus: &'a Suspicion,
+}
+
+impl Entry<'_> {
+    /// Only rule matches are shared indicators, advisories are common on stock phones
+    fn matches(&self, other: &Entry<'_>) -> bool {
+        self.sus.is_ioc() && self.sus.rule == other.sus.rule
+    }
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Comparison<'a> {
+    pub shared: Vec<Entry<'a>>,
+    pub only_first: Vec<Entry<'a>>,
+    pub only_second: Vec<Entry<'a>>,
+}
+
+fn entries(findings: &Findings) -> Vec<Entry<'_>> {
+    let device = findings.findings.iter().map(|sus| Entry { app: None, sus });
+    let apps = findings.apps.iter().flat_map(|(name, findings)| {
+        findings.iter().map(|sus| Entry {
+            app: Some(name.as_str()),
+            sus,
+        })
+    });
+    device.chain(apps).collect()
+}
+
+pub fn compare<'a>(first: &'a Findings, second: &'a Findings) -> Comparison<'a> {
+    let first = entries(first);
+    let second = entries(second);
+
+    let mut comparison = Comparison::default();
+    for entry in &first {
+        if second.iter().any(|other| entry.matches(other)) {
+            comparison.shared.push(*entry);
+        } else {
+            comparison.only_first.push(*entry);
+        }
+    }
+    comparison.only_second = second
+        .into_iter()
+        .filter(|entry| !first.iter().any(|other| entry.matches(other)))
+        .collect();
+    comparison
+}
+
+fn render_section(out: &mut String, title: &str, entries: &[Entry<'_>]) {
+    writeln!(out, "{title} ({}):", entries.len()).ok();
+    if entries.is_empty() {
+        writeln!(out, "  -").ok();
+    }
+    for entry in entries {
+        let level = entry.sus.level.as_str();
+        match entry.app {
+            Some(app) => writeln!(out, "  [{level}] {app}: {}", entry.sus.description),
+            None => writeln!(out, "  [{level}] {}", entry.sus.description),
+        }
+        .ok();
+    }
+    writeln!(out).ok();
+}
+
+pub fn render(
+    first: (&str, &Findings),
+    second: (&str, &Findings),
+    comparison: &Comparison<'_>,
+) -> String {
+    let mut out = String::new();
+    render_section(&mut out, "Shared indicators", &comparison.shared);
+    render_section(
+        &mut out,
+        &format!("Only on {:?}", first.0),
+        &comparison.only_first,
+    );
+    render_section(
+        &mut out,
+        &format!("Only on {:?}", second.0),
+        &comparison.only_second,
+    );
+    for (serial, findings) in [first, second] {
+        if !findings.warnings.is_empty() {
+            writeln!(
+                out,
+                "Scan of {serial:?} may be incomplete, {} warnings:",
+                findings.warnings.len()
+            )
+            .ok();
+            for warning in &findings.warnings {
+                writeln!(out, "  {warning}").ok();
+            }
+            writeln!(out).ok();
+        }
+    }
+    out
+}
+
+/// Scan both devices one after another and print the comparison
+pub async fn run(
+    adb_host: Host,
+    rules: &Rules,
+    settings: &scan::Settings,
+    first: &str,
+    second: &str,
+) -> Result<()> {
+    let devices = adb_host
+        .devices::<Vec<_>>()
+        .await
+        .map_err(ScanError::AdbUnavailable)?;
+    let find = |serial: &str| -> Result<&DeviceInfo> {
+        devices
+            .iter()
+            .find(|device| device.serial == serial)
+            .with_context(|| anyhow!("Device {serial:?} is not connected"))
+    };
+    let first = find(first)?;
+    let second = find(second)?;
+
+    info!("Scanning first device: {}", utils::format_device(first));
+    let first_findings = scan::scan_device(&adb_host, first, rules, settings).await;
+    info!("Scanning second device: {}", utils::format_device(second));
+    let second_findings = scan::scan_device(&adb_host, second, rules, settings).await;
+
+    let comparison = compare(&first_findings, &second_findings);
+    print!(
+        "{}",
+        render(
+            (&first.serial, &first_findings),
+            (&second.serial, &second_findings),
+            &comparison
+        )
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Kind, SuspicionLevel};
+    use indexmap::IndexMap;
+
+    fn sus(level: SuspicionLevel, description: &str) -> Suspicion {
+        Suspicion::new(level, Kind::Other, description.to_string())
+    }
+
+    #[test]
+    fn test_compare() {
+        let stalkerware = sus(
+            SuspicionLevel::High,
+            "Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")",
+        )
+        .with_rule("SpyLive360");
+        let first = Findings {
+            findings: vec![sus(SuspicionLevel::Medium, "Package verifier is disabled")],
+            apps: IndexMap::from([("com.wifi0".to_string(), vec![stalkerware.clone()])]),
+            warnings: vec![],
+        };
+        let second = Findings {
+            findings: vec![sus(SuspicionLevel::Medium, "Package verifier is disabled")],
+            apps: IndexMap::from([
+                ("com.wifi0".to_string(), vec![stalkerware.clone()]),
+                (
+                    "org.example".to_string(),
+                    vec![sus(SuspicionLevel::Low, "Package has no launcher icon")],
+                ),
+            ]),
+            warnings: vec!["Check \"files\" has failed".to_string()],
+        };
+
+        let comparison = compare(&first, &second);
+        assert_eq!(comparison.shared.len(), 1);
+        assert_eq!(comparison.shared[0].app, Some("com.wifi0"));
+        assert_eq!(comparison.only_first.len(), 1);
+        assert_eq!(comparison.only_second.len(), 2);
+
+        let out = render(("A", &first), ("B", &second), &comparison);
+        assert_eq!(
+            out,
+            "Shared indicators (1):
+  [high] com.wifi0: Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")
+
+Only on \"A\" (1):
+  [medium] Package verifier is disabled
+
+Only on \"B\" (2):
+  [medium] Package verifier is disabled
+  [low] org.example: Package has no launcher icon
+
+Scan of \"B\" may be incomplete, 1 warnings:
+  Check \"files\" has failed
+
+"
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::errors::*;
+use crate::ioc::SuspicionLevel;
+use crate::probes::Probe;
+use crate::scan;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+
+/// Persistent defaults for the interactive interface, command line flags take precedence
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub severity_symbols: bool,
+    pub kind_symbols: bool,
+    pub arrival_order: bool,
+    pub show_advisories: bool,
+    /// Ring the terminal bell for findings of this severity or higher
+    pub bell: Option<SuspicionLevel>,
+    /// Directory that exported reports are written to
+    pub export_dir: Option<PathBuf>,
+    /// Hide findings below this level in the report view
+    pub min_level: Option<SuspicionLevel>,
+    /// Ask the operator to confirm they are authorized to scan before adb is used
+    pub require_consent: bool,
+    /// Record the findings and the fingerprint of every scan, so they can be searched and compared later
+    pub keep_history: bool,
+    /// Return to the device list after this many seconds without input
+    pub idle_timeout: Option<u64>,
+    pub idle_disconnect: bool,
+    /// Rules file that is downloaded when updating the rules from within the tui
+    pub rules_url: Option<String>,
+    pub scan: ScanProfile,
+}
+
+/// The scan settings used when a scan is started from the device list
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ScanProfile {
+    pub skip_apps: bool,
+    pub skip_accessibility: bool,
+    pub scan_paths: Vec<String>,
+    pub concurrency: usize,
+    pub retry: bool,
+    /// Wait at least this many milliseconds between adb commands
+    pub throttle: Option<u64>,
+    /// Give up on an adb command after this many seconds, `0` waits forever
+    pub timeout: u64,
+    /// Report matches of rules of a category with at least this level, matches below low are left as they are
+    pub escalate: BTreeMap<String, SuspicionLevel>,
+    /// Additional read-only commands to run, their output is matched against a pattern
+    pub probes: Vec<Probe>,
+}
+
+impl Default for ScanProfile {
+    fn default() -> Self {
+        ScanProfile {
+            skip_apps: false,
+            skip_accessibility: false,
+            scan_paths: Vec::new(),
+            concurrency: scan::DEFAULT_CONCURRENCY,
+            retry: false,
+            throttle: None,
+            timeout: scan::DEFAULT_TIMEOUT,
+            escalate: BTreeMap::new(),
+            probes: Vec::new(),
+        }
+    }
+}
+
+impl From<&ScanProfile> for scan::Settings {
+    fn from(profile: &ScanProfile) -> scan::Settings {
+        scan::Settings {
+            skip_apps: profile.skip_apps,
+            skip_accessibility: profile.skip_accessibility,
+            scan_paths: profile.scan_paths.clone(),
+            concurrency: profile.concurrency,
+            retry: profile.retry,
+            throttle: profile.throttle.map(Duration::from_millis),
+            timeout: scan::timeout(profile.timeout),
+            escalate: profile.escalate.clone(),
+            probes: profile.probes.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn default_path() -> Result<PathBuf> {
+    let dir = dirs::config_dir().context("Failed to find config dir")?;
+    Ok(dir.join("spytrap-adb").join("config.toml"))
+}
+
+pub fn parse(buf: &str) -> Result<Config> {
+    let config = toml::from_str::<Config>(buf)?;
+    for probe in &config.scan.probes {
+        probe.validate()?;
+    }
+    if let Some(url) = &config.rules_url {
+        if !url.starts_with("https://") {
+            bail!("Rules can only be downloaded over https, rules-url is: {url:?}");
+        }
+    }
+    Ok(config)
+}
+
+/// Read the config file, a missing file is the same as an empty one
+pub async fn load(path: &Path) -> Result<Config> {
+    match fs::read_to_string(path).await {
+        Ok(buf) => parse(&buf).with_context(|| anyhow!("Failed to parse config file {path:?}")),
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            debug!("No config file at {path:?}, using defaults");
+            Ok(Config::default())
+        }
+        Err(err) => Err(err).with_context(|| anyhow!("Failed to read config file {path:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::Kind;
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse(
+            r#"
+arrival-order = true
+require-consent = true
+idle-timeout = 300
+export-dir = "/home/user/reports"
+rules-url = "https://example.com/rules.yaml"
+min-level = "medium"
+
+[scan]
+skip-accessibility = true
+throttle = 250
+timeout = 30
+scan-paths = ["/sdcard/Documents"]
+
+[scan.escalate]
+watchware = "high"
+
+[[scan.probes]]
+name = "debuggable"
+command = "getprop ro.debuggable"
+pattern = "^1$"
+level = "medium"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                severity_symbols: false,
+                kind_symbols: false,
+                arrival_order: true,
+                show_advisories: false,
+                bell: None,
+                export_dir: Some(PathBuf::from("/home/user/reports")),
+                min_level: Some(SuspicionLevel::Medium),
+                require_consent: true,
+                keep_history: false,
+                idle_timeout: Some(300),
+                idle_disconnect: false,
+                rules_url: Some("https://example.com/rules.yaml".to_string()),
+                scan: ScanProfile {
+                    skip_accessibility: true,
+                    scan_paths: vec!["/sdcard/Documents".to_string()],
+                    throttle: Some(250),
+                    timeout: 30,
+                    escalate: BTreeMap::from([("watchware".to_string(), SuspicionLevel::High)]),
+                    probes: vec![Probe {
+                        name: "debuggable".to_string(),
+                        command: "getprop ro.debuggable".to_string(),
+                        pattern: "^1$".to_string(),
+                        level: SuspicionLevel::Medium,
+                        kind: Kind::Other,
+                        description: None,
+                    }],
+                    ..Default::default()
+                },
+            }
+        );
+
+        assert_eq!(parse("").unwrap(), Config::default());
+        assert!(parse("min-level = \"critical\"").is_err());
+        assert!(parse("colour = true").is_err());
+        assert!(
+            parse("[[scan.probes]]\nname = \"x\"\ncommand = \"reboot\"\npattern = \"\"\n").is_err()
+        );
+        assert!(parse("rules-url = \"http://example.com/rules.yaml\"").is_err());
+    }
+}
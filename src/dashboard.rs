@@ -0,0 +1,182 @@
+use crate::device_policy;
+use crate::errors::*;
+use crate::export;
+use crate::pm;
+use crate::settings;
+use crate::shell::Shell;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+/// A quick overview of a device, assembled before committing to a full scan
+#[derive(Debug, PartialEq, Clone, Default, Serialize)]
+pub struct Dashboard {
+    pub android_version: Option<String>,
+    pub security_patch: Option<String>,
+    pub installed_apps: usize,
+    pub system_apps: usize,
+    pub user_apps: usize,
+    pub device_admins: Vec<String>,
+    pub wireless_debugging: Option<bool>,
+}
+
+pub async fn getprop(device: &dyn Shell, key: &str) -> Result<Option<String>> {
+    let cmd = format!("getprop {key}");
+    debug!("Executing {:?}", cmd);
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    let value = String::from_utf8_lossy(&output).trim().to_string();
+    Ok(Some(value).filter(|value| !value.is_empty()))
+}
+
+pub async fn collect(device: &dyn Shell) -> Result<Dashboard> {
+    let android_version = getprop(device, "ro.build.version.release").await?;
+    let security_patch = getprop(device, "ro.build.version.security_patch").await?;
+    let installed_apps = pm::list_packages(device).await?.len();
+    let (system_apps, user_apps) = pm::count_packages(device).await?;
+
+    let policy = device_policy::dump(device).await?;
+    let mut device_admins = Vec::new();
+    if let Some(owner) = &policy.device_owner {
+        device_admins.push(owner.package.clone());
+    }
+    device_admins.extend(policy.profile_owners.iter().map(|o| o.package.clone()));
+    for admin in &policy.admins {
+        let package = admin.package().to_string();
+        if !device_admins.contains(&package) {
+            device_admins.push(package);
+        }
+    }
+
+    let wireless_debugging = settings::get(device, "global", "adb_wifi_enabled")
+        .await?
+        .map(|value| value == "1");
+
+    Ok(Dashboard {
+        android_version,
+        security_patch,
+        installed_apps,
+        system_apps,
+        user_apps,
+        device_admins,
+        wireless_debugging,
+    })
+}
+
+/// The overview of a device on its own, for intake logging without a scan
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Profile<'a> {
+    pub serial: Cow<'a, str>,
+    pub device: &'a BTreeMap<String, String>,
+    pub time: i64,
+    #[serde(flatten)]
+    pub dashboard: &'a Dashboard,
+}
+
+/// Device details are dropped entirely from anonymized profiles
+static NO_DEVICE_INFO: BTreeMap<String, String> = BTreeMap::new();
+
+impl Profile<'_> {
+    /// Replace the serial with a hash and remove device details
+    pub fn anonymize(mut self) -> Self {
+        self.serial = Cow::Owned(export::anonymize_serial(&self.serial));
+        self.device = &NO_DEVICE_INFO;
+        self
+    }
+}
+
+/// The default filename for a profile, based on the device serial and the time of export
+pub fn filename(serial: &str, timestamp: i64) -> String {
+    let serial = export::safe_serial(serial);
+    format!("spytrap-profile-{serial}-{timestamp}.json")
+}
+
+/// Write the profile as json, `-` writes to stdout
+pub async fn write(path: &Path, profile: &Profile<'_>) -> Result<()> {
+    let mut buf = serde_json::to_string_pretty(profile)?;
+    buf.push('\n');
+    if path == Path::new("-") {
+        print!("{buf}");
+        return Ok(());
+    }
+    debug!("Writing device profile to {path:?}...");
+    fs::write(path, buf.as_bytes())
+        .await
+        .with_context(|| anyhow!("Failed to write device profile to {path:?}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::MockShell;
+
+    #[test]
+    fn test_serialize_profile() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let dashboard = Dashboard {
+            android_version: Some("14".to_string()),
+            security_patch: Some("2024-06-05".to_string()),
+            installed_apps: 2,
+            system_apps: 1,
+            user_apps: 1,
+            device_admins: vec!["com.wifi0".to_string()],
+            wireless_debugging: None,
+        };
+        let profile = Profile {
+            serial: Cow::Borrowed("ABC123"),
+            device: &device,
+            time: 1719963254,
+            dashboard: &dashboard,
+        };
+        assert_eq!(
+            serde_json::to_string(&profile).unwrap(),
+            r#"{"serial":"ABC123","device":{"model":"Pixel_7"},"time":1719963254,"android_version":"14","security_patch":"2024-06-05","installed_apps":2,"system_apps":1,"user_apps":1,"device_admins":["com.wifi0"],"wireless_debugging":null}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&profile.anonymize()).unwrap(),
+            r#"{"serial":"sha256-e0bebd2281999342","device":{},"time":1719963254,"android_version":"14","security_patch":"2024-06-05","installed_apps":2,"system_apps":1,"user_apps":1,"device_admins":["com.wifi0"],"wireless_debugging":null}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collect() {
+        let device = MockShell::default()
+            .with("getprop ro.build.version.release", "14\n")
+            .with("getprop ro.build.version.security_patch", "2024-06-05\n")
+            .with(
+                "pm list packages -f -i",
+                "package:/data/app/base.apk=com.wifi0  installer=null
+package:/system/app/Settings.apk=com.android.settings  installer=null
+",
+            )
+            .with("pm list packages -s", "package:com.android.settings\n")
+            .with("pm list packages -3", "package:com.wifi0\n")
+            .with(
+                "dumpsys device_policy",
+                &include_bytes!("../test_data/dumpsys/device_policy/spyware.txt")[..],
+            )
+            .with("settings get global adb_wifi_enabled", "0\n");
+        let dashboard = collect(&device).await.unwrap();
+        assert_eq!(
+            dashboard,
+            Dashboard {
+                android_version: Some("14".to_string()),
+                security_patch: Some("2024-06-05".to_string()),
+                installed_apps: 2,
+                system_apps: 1,
+                user_apps: 1,
+                device_admins: vec![
+                    "com.wifi0".to_string(),
+                    "com.example.mdm".to_string(),
+                    "com.google.android.gms".to_string(),
+                ],
+                wireless_debugging: Some(false),
+            }
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::checks::Check;
+use crate::dumpsys;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::parsers::device_policy::DevicePolicy;
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use async_trait::async_trait;
+
+/// Device admins that ship with regular Android installations
+const KNOWN_ADMINS: &[&str] =
+    &["com.google.android.gms/com.google.android.gms.mdm.receivers.MdmDeviceAdminReceiver"];
+
+pub async fn dump(device: &dyn Shell) -> Result<DevicePolicy> {
+    info!("Reading device policy");
+    let out = dumpsys::dump_service(device, "device_policy").await?;
+    out.parse::<DevicePolicy>()
+        .context("Failed to parse device_policy service output")
+}
+
+pub struct DeviceAdminCheck;
+
+#[async_trait]
+impl Check for DeviceAdminCheck {
+    fn id(&self) -> &'static str {
+        "device-admin"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look for device admins that can lock, wipe or monitor the device"
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        rules: &Rules,
+        _settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        let policy = dump(device).await?;
+        for (package, sus) in policy.audit(rules) {
+            warn!("Suspicious {:?}: {}", sus.level, sus.description);
+            report.app(package, sus).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Owners are also set up by legitimate device management (MDM), only known stalkerware is reported as such
+fn audit_owner(
+    rules: &Rules,
+    package: &str,
+    level: SuspicionLevel,
+    description: String,
+) -> Suspicion {
+    if let Some(rule) = rules.get(package) {
+        Suspicion::new(rules.get_level(rule), Kind::Package, description)
+            .with_remediation(rules.get_remediation(rule).cloned())
+            .with_rule(rule.clone())
+            .with_confidence(rules.get_confidence(rule))
+    } else {
+        Suspicion::new(level, Kind::Package, description)
+    }
+}
+
+impl DevicePolicy {
+    pub fn audit(&self, rules: &Rules) -> Vec<(String, Suspicion)> {
+        let mut sus = Vec::new();
+
+        if let Some(owner) = &self.device_owner {
+            sus.push((
+                owner.package.clone(),
+                audit_owner(
+                    rules,
+                    &owner.package,
+                    SuspicionLevel::Medium,
+                    format!("Package {:?} is the device owner", owner.package),
+                ),
+            ));
+        }
+
+        for owner in &self.profile_owners {
+            sus.push((
+                owner.package.clone(),
+                audit_owner(
+                    rules,
+                    &owner.package,
+                    SuspicionLevel::Info,
+                    format!(
+                        "Package {:?} is the profile owner of user {}",
+                        owner.package, owner.user
+                    ),
+                ),
+            ));
+        }
+
+        for admin in &self.admins {
+            let level = if KNOWN_ADMINS.contains(&admin.component.as_str()) {
+                SuspicionLevel::Info
+            } else {
+                SuspicionLevel::Medium
+            };
+            sus.push((
+                admin.package().to_string(),
+                Suspicion::new(
+                    level,
+                    Kind::Package,
+                    format!(
+                        "Package {:?} is an active device admin for user {}: {:?}",
+                        admin.package(),
+                        admin.user,
+                        admin.component
+                    ),
+                ),
+            ));
+        }
+
+        sus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Rules {
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "unit-test",
+                b"
+- name: SpyLive360
+  type: stalkerware
+  packages:
+  - com.wifi0
+",
+            )
+            .unwrap();
+        rules
+    }
+
+    #[test]
+    fn test_audit_device_policy_plain() {
+        let data = include_str!("../test_data/dumpsys/device_policy/plain.txt");
+        let policy = data.parse::<DevicePolicy>().unwrap();
+        let sus = policy.audit(&rules());
+        assert_eq!(&sus, &[
+            ("com.google.android.gms".to_string(), Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"com.google.android.gms\" is an active device admin for user 0: \"com.google.android.gms/com.google.android.gms.mdm.receivers.MdmDeviceAdminReceiver\"")),
+        ]);
+    }
+
+    #[test]
+    fn test_audit_device_policy_spyware() {
+        let data = include_str!("../test_data/dumpsys/device_policy/spyware.txt");
+        let policy = data.parse::<DevicePolicy>().unwrap();
+        let sus = policy.audit(&rules());
+        assert_eq!(&sus, &[
+            ("com.wifi0".to_string(), Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.wifi0\" is the device owner").with_rule("SpyLive360")),
+            ("com.example.mdm".to_string(), Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"com.example.mdm\" is the profile owner of user 10")),
+            ("com.wifi0".to_string(), Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" is an active device admin for user 0: \"com.wifi0/com.wifi0.AdminReceiver\"")),
+            ("com.google.android.gms".to_string(), Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"com.google.android.gms\" is an active device admin for user 0: \"com.google.android.gms/com.google.android.gms.mdm.receivers.MdmDeviceAdminReceiver\"")),
+            ("com.example.mdm".to_string(), Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.example.mdm\" is an active device admin for user 10: \"com.example.mdm/com.example.mdm.ProfileReceiver\"")),
+        ]);
+    }
+
+    #[test]
+    fn test_audit_unknown_device_owner() {
+        let data = include_str!("../test_data/dumpsys/device_policy/spyware.txt");
+        let policy = data.parse::<DevicePolicy>().unwrap();
+        let sus = policy.audit(&Rules::default());
+        assert_eq!(
+            sus[0],
+            (
+                "com.wifi0".to_string(),
+                Suspicion::new(
+                    SuspicionLevel::Medium,
+                    Kind::Package,
+                    "Package \"com.wifi0\" is the device owner"
+                )
+            )
+        );
+    }
+}
@@ -1,16 +1,16 @@
 use crate::errors::*;
+use crate::shell::Shell;
 use bstr::ByteSlice;
-use forensic_adb::Device;
 use std::collections::HashSet;
 use std::str;
 
 const CMD_LIST_SERVICES: &str = "dumpsys -l";
 
-pub async fn list_services(device: &Device) -> Result<HashSet<String>> {
+pub async fn list_services(device: &dyn Shell) -> Result<HashSet<String>> {
     let cmd = CMD_LIST_SERVICES;
     debug!("Executing {:?}", cmd);
     let output = device
-        .execute_host_exec_out_command(cmd)
+        .exec(cmd)
         .await
         .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
 
@@ -30,11 +30,11 @@ pub async fn list_services(device: &Device) -> Result<HashSet<String>> {
     Ok(services)
 }
 
-pub async fn dump_service(device: &Device, service: &str) -> Result<String> {
+pub async fn dump_service(device: &dyn Shell, service: &str) -> Result<String> {
     let cmd = format!("dumpsys {}", service);
     debug!("Executing {:?}", cmd);
     let output = device
-        .execute_host_exec_out_command(&cmd)
+        .exec(&cmd)
         .await
         .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
     let output = String::from_utf8_lossy(&output);
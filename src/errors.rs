@@ -1,2 +1,64 @@
 pub use anyhow::{anyhow, bail, Context as _, Error, Result};
 pub use log::{debug, error, info, trace, warn};
+
+use crate::ioc::SuspicionLevel;
+
+/// Failure modes of a scan that callers may want to handle differently
+#[derive(Debug, thiserror::Error)]
+pub enum ScanError {
+    #[error("Failed to list devices from adb: {0}")]
+    AdbUnavailable(#[source] forensic_adb::DeviceError),
+    #[error("Failed to access device: {serial:?}")]
+    DeviceUnavailable {
+        serial: Option<String>,
+        #[source]
+        source: forensic_adb::DeviceError,
+    },
+    #[error("Failed to load rules")]
+    Rules(#[source] Error),
+    #[error("Check {id:?} has failed")]
+    Check {
+        id: &'static str,
+        #[source]
+        source: Error,
+    },
+    #[error("Scan was stopped at the first {} finding: {description}", level.as_str())]
+    FailFast {
+        level: SuspicionLevel,
+        description: String,
+    },
+}
+
+impl ScanError {
+    /// A hint for the user on how to resolve the error
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            ScanError::AdbUnavailable(_) => {
+                "Make sure adb is installed and the adb server is running"
+            }
+            ScanError::DeviceUnavailable { .. } => {
+                "Make sure the device is connected, unlocked and usb debugging has been authorized"
+            }
+            ScanError::Rules(_) => {
+                "Download the latest stalkerware-indicators database or check the rule files"
+            }
+            ScanError::Check { .. } => {
+                "The device may have been disconnected or locked during the scan, try to scan again"
+            }
+            ScanError::FailFast { .. } => {
+                "The scan has not completed because of --fail-fast, scan again without it for a full report"
+            }
+        }
+    }
+
+    /// The exit code used when the error ends a headless scan
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ScanError::AdbUnavailable(_) => 3,
+            ScanError::DeviceUnavailable { .. } => 4,
+            ScanError::Rules(_) => 5,
+            ScanError::Check { .. } => 6,
+            ScanError::FailFast { .. } => 7,
+        }
+    }
+}
@@ -0,0 +1,618 @@
+use crate::errors::*;
+use crate::ioc::{Suspicion, SuspicionLevel};
+use crate::stix;
+use crate::utils;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::fs;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Report<'a> {
+    pub serial: Cow<'a, str>,
+    pub device: &'a BTreeMap<String, String>,
+    /// If set, findings below this level have been left out
+    pub min_level: Option<SuspicionLevel>,
+    /// Notes taken by the analyst during the scan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<&'a str>,
+    /// Problems that prevented parts of the device from being scanned
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<&'a str>,
+    pub findings: Vec<&'a Suspicion>,
+    pub apps: IndexMap<&'a str, Vec<&'a Suspicion>>,
+    /// Findings the analyst has marked as reviewed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reviewed: Vec<FindingId<'a>>,
+}
+
+/// Identifies a finding within a report
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+pub struct FindingId<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<&'a str>,
+    pub description: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Json,
+    Csv,
+    Markdown,
+    Html,
+    Stix,
+}
+
+impl Format {
+    pub const ALL: &'static [Format] = &[
+        Format::Json,
+        Format::Csv,
+        Format::Markdown,
+        Format::Html,
+        Format::Stix,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Json => "JSON",
+            Format::Csv => "CSV",
+            Format::Markdown => "Markdown",
+            Format::Html => "HTML",
+            Format::Stix => "STIX 2.1",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Markdown => "md",
+            Format::Html => "html",
+            Format::Stix => "stix.json",
+        }
+    }
+
+    pub fn render(&self, report: &Report<'_>) -> Result<String> {
+        let mut out = match self {
+            Format::Json => serde_json::to_string_pretty(report)?,
+            Format::Csv => render_csv(report),
+            Format::Markdown => render_markdown(report),
+            Format::Html => render_html(report),
+            Format::Stix => stix::render(report, utils::now())?,
+        };
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// Device details are dropped entirely from anonymized reports
+static NO_DEVICE_INFO: BTreeMap<String, String> = BTreeMap::new();
+
+impl Report<'_> {
+    pub fn is_reviewed(&self, app: Option<&str>, sus: &Suspicion) -> bool {
+        self.reviewed
+            .iter()
+            .any(|id| id.app == app && id.description == sus.description)
+    }
+
+    /// Replace the serial with a hash and remove device details and notes
+    pub fn anonymize(mut self) -> Self {
+        self.serial = Cow::Owned(anonymize_serial(&self.serial));
+        self.device = &NO_DEVICE_INFO;
+        self.notes = None;
+        self
+    }
+}
+
+pub fn anonymize_serial(serial: &str) -> String {
+    let hash = utils::sha256(serial.as_bytes());
+    format!("sha256-{}", &hash[..16])
+}
+
+/// All findings of the report with the app they belong to, if any
+fn rows<'a>(report: &'a Report<'a>) -> impl Iterator<Item = (Option<&'a str>, &'a Suspicion)> {
+    report.findings.iter().map(|sus| (None, *sus)).chain(
+        report
+            .apps
+            .iter()
+            .flat_map(|(name, findings)| findings.iter().map(move |sus| (Some(*name), *sus))),
+    )
+}
+
+fn csv_field(s: &str) -> Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", s.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+fn render_csv(report: &Report<'_>) -> String {
+    let mut out = String::from("serial,app,level,description,reviewed,confidence\n");
+    for (app, sus) in rows(report) {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            csv_field(&report.serial),
+            csv_field(app.unwrap_or("")),
+            sus.level.as_str(),
+            csv_field(&sus.description),
+            report.is_reviewed(app, sus),
+            sus.confidence.map(|c| c.as_str()).unwrap_or("")
+        ));
+    }
+    for warning in &report.warnings {
+        out.push_str(&format!(
+            "{},,warning,{},false,\n",
+            csv_field(&report.serial),
+            csv_field(warning)
+        ));
+    }
+    out
+}
+
+fn render_markdown(report: &Report<'_>) -> String {
+    let mut out = format!("# spytrap-adb report for `{}`\n\n", report.serial);
+    for (key, value) in report.device {
+        out.push_str(&format!("- **{key}**: `{value}`\n"));
+    }
+    if let Some(min_level) = report.min_level {
+        out.push_str(&format!(
+            "\nOnly findings of level {} and above are included.\n",
+            min_level.as_str()
+        ));
+    }
+    if let Some(notes) = report.notes {
+        out.push_str("\n## Notes\n\n");
+        out.push_str(notes);
+        out.push('\n');
+    }
+    out.push_str("\n| App | Level | Description |\n|---|---|---|\n");
+    for (app, sus) in rows(report) {
+        out.push_str(&format!(
+            "| {} | {} | {}{}{}{} |\n",
+            app.unwrap_or("-"),
+            sus.level.as_str(),
+            if report.is_reviewed(app, sus) {
+                "✓ "
+            } else {
+                ""
+            },
+            sus.description.replace('|', "\\|"),
+            sus.confidence_note().unwrap_or_default(),
+            sus.remediation
+                .as_ref()
+                .map(|remediation| format!(
+                    "<br>**What to do:** {}",
+                    remediation.trim().replace('|', "\\|").replace('\n', " ")
+                ))
+                .unwrap_or_default()
+        ));
+    }
+    if !report.warnings.is_empty() {
+        out.push_str("\n## Warnings\n\nThe scan may be incomplete:\n\n");
+        for warning in &report.warnings {
+            out.push_str(&format!("- {warning}\n"));
+        }
+    }
+    out
+}
+
+/// Plain text for pasting into a chat or ticket
+pub fn render_text(report: &Report<'_>) -> String {
+    let mut out = format!("spytrap-adb report for {:?}\n", report.serial);
+    for (key, value) in report.device {
+        out.push_str(&format!("{key}: {value}\n"));
+    }
+    if let Some(min_level) = report.min_level {
+        out.push_str(&format!(
+            "Only findings of level {} and above are included.\n",
+            min_level.as_str()
+        ));
+    }
+    if let Some(notes) = report.notes {
+        out.push_str(&format!("\nNotes:\n{}\n", notes.trim_end()));
+    }
+    out.push_str("\nFindings:\n");
+    let mut empty = true;
+    for (app, sus) in rows(report) {
+        empty = false;
+        let level = sus.level.as_str();
+        let confidence = sus.confidence_note().unwrap_or_default();
+        let reviewed = if report.is_reviewed(app, sus) {
+            " (reviewed)"
+        } else {
+            ""
+        };
+        match app {
+            Some(app) => out.push_str(&format!(
+                "  [{level}] {app}: {}{confidence}{reviewed}\n",
+                sus.description
+            )),
+            None => out.push_str(&format!(
+                "  [{level}] {}{confidence}{reviewed}\n",
+                sus.description
+            )),
+        }
+        if let Some(remediation) = &sus.remediation {
+            out.push_str(&format!(
+                "    What to do: {}\n",
+                remediation.trim().replace('\n', " ")
+            ));
+        }
+    }
+    if empty {
+        out.push_str("  -\n");
+    }
+    if !report.warnings.is_empty() {
+        out.push_str("\nThe scan may be incomplete:\n");
+        for warning in &report.warnings {
+            out.push_str(&format!("  {warning}\n"));
+        }
+    }
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn render_html(report: &Report<'_>) -> String {
+    let serial = html_escape(&report.serial);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>spytrap-adb report for {serial}</title>\n</head>\n<body>\n<h1>spytrap-adb report for {serial}</h1>\n<ul>\n"
+    );
+    for (key, value) in report.device {
+        out.push_str(&format!(
+            "<li><b>{}</b>: {}</li>\n",
+            html_escape(key),
+            html_escape(value)
+        ));
+    }
+    out.push_str("</ul>\n");
+    if let Some(min_level) = report.min_level {
+        out.push_str(&format!(
+            "<p>Only findings of level {} and above are included.</p>\n",
+            min_level.as_str()
+        ));
+    }
+    if let Some(notes) = report.notes {
+        out.push_str(&format!("<h2>Notes</h2>\n<p>{}</p>\n", html_escape(notes)));
+    }
+    out.push_str("<table>\n<tr><th>App</th><th>Level</th><th>Description</th></tr>\n");
+    for (app, sus) in rows(report) {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}{}{}{}</td></tr>\n",
+            html_escape(app.unwrap_or("-")),
+            sus.level.as_str(),
+            if report.is_reviewed(app, sus) {
+                "&#10003; "
+            } else {
+                ""
+            },
+            html_escape(&sus.description),
+            html_escape(&sus.confidence_note().unwrap_or_default()),
+            sus.remediation
+                .as_ref()
+                .map(|remediation| format!(
+                    "<br><i>What to do:</i> {}",
+                    html_escape(remediation.trim())
+                ))
+                .unwrap_or_default()
+        ));
+    }
+    out.push_str("</table>\n");
+    if !report.warnings.is_empty() {
+        out.push_str("<h2>Warnings</h2>\n<p>The scan may be incomplete:</p>\n<ul>\n");
+        for warning in &report.warnings {
+            out.push_str(&format!("<li>{}</li>\n", html_escape(warning)));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Replace characters of a serial that shouldn't be used in filenames
+pub fn safe_serial(serial: &str) -> String {
+    serial
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The default filename for a report, based on the device serial and the time of export
+pub fn filename(serial: &str, timestamp: i64, format: Format) -> String {
+    let serial = safe_serial(serial);
+    format!("spytrap-report-{serial}-{timestamp}.{}", format.extension())
+}
+
+pub async fn write(path: &Path, format: Format, report: &Report<'_>) -> Result<()> {
+    let buf = format.render(report)?;
+    debug!("Writing {} report to {path:?}...", format.name());
+    fs::write(path, buf.as_bytes())
+        .await
+        .with_context(|| anyhow!("Failed to write report to {path:?}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Confidence, Kind};
+
+    #[test]
+    fn test_filename() {
+        assert_eq!(
+            filename("192.168.1.5:5555", 1719963254, Format::Json),
+            "spytrap-report-192.168.1.5_5555-1719963254.json"
+        );
+        assert_eq!(
+            filename("ABC123", 1719963254, Format::Markdown),
+            "spytrap-report-ABC123-1719963254.md"
+        );
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let device = BTreeMap::new();
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Setting,
+            "Google Play Protect is turned off",
+        );
+        let app_sus = Suspicion::new(
+            SuspicionLevel::Medium,
+            Kind::Package,
+            "Package \"com.wifi0\" has requested permission, twice",
+        )
+        .with_confidence(Some(Confidence::Low));
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: None,
+            warnings: vec![],
+            findings: vec![&sus],
+            apps: IndexMap::from([("com.wifi0", vec![&app_sus])]),
+            reviewed: vec![FindingId {
+                app: None,
+                description: "Google Play Protect is turned off",
+            }],
+        };
+        assert_eq!(
+            Format::Csv.render(&report).unwrap(),
+            "serial,app,level,description,reviewed,confidence
+ABC123,,high,Google Play Protect is turned off,true,
+ABC123,com.wifi0,medium,\"Package \"\"com.wifi0\"\" has requested permission, twice\",false,low
+"
+        );
+    }
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(
+            html_escape("<b>\"Tom & Jerry's\"</b>"),
+            "&lt;b&gt;&quot;Tom &amp; Jerry&#39;s&quot;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_serialize_report() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Setting,
+            "Google Play Protect is turned off",
+        );
+        let app_sus = Suspicion::new(
+            SuspicionLevel::Medium,
+            Kind::Package,
+            "Package \"com.wifi0\" has requested permission",
+        );
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: Some(SuspicionLevel::Medium),
+            notes: None,
+            warnings: vec![],
+            findings: vec![&sus],
+            apps: IndexMap::from([("com.wifi0", vec![&app_sus])]),
+            reviewed: vec![],
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            json,
+            r#"{"serial":"ABC123","device":{"model":"Pixel_7"},"min_level":"medium","findings":[{"level":"high","description":"Google Play Protect is turned off","kind":"setting"}],"apps":{"com.wifi0":[{"level":"medium","description":"Package \"com.wifi0\" has requested permission","kind":"package"}]}}"#
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_notes() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: Some("owner reports battery drain"),
+            warnings: vec![],
+            findings: vec![],
+            apps: IndexMap::new(),
+            reviewed: vec![],
+        };
+        assert_eq!(
+            Format::Markdown.render(&report).unwrap(),
+            "# spytrap-adb report for `ABC123`
+
+- **model**: `Pixel_7`
+
+## Notes
+
+owner reports battery drain
+
+| App | Level | Description |
+|---|---|---|
+"
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["notes"], "owner reports battery drain");
+    }
+
+    #[test]
+    fn test_render_markdown_warnings() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: None,
+            warnings: vec!["find: '/sdcard/Download/secret': Permission denied"],
+            findings: vec![],
+            apps: IndexMap::new(),
+            reviewed: vec![],
+        };
+        assert_eq!(
+            Format::Markdown.render(&report).unwrap(),
+            "# spytrap-adb report for `ABC123`
+
+- **model**: `Pixel_7`
+
+| App | Level | Description |
+|---|---|---|
+
+## Warnings
+
+The scan may be incomplete:
+
+- find: '/sdcard/Download/secret': Permission denied
+"
+        );
+    }
+
+    #[test]
+    fn test_render_remediation() {
+        let device = BTreeMap::new();
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")",
+        )
+        .with_remediation(Some(
+            "Back up evidence, then uninstall <com.wifi0>".to_string(),
+        ))
+        .with_rule("SpyLive360");
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: None,
+            warnings: vec![],
+            findings: vec![],
+            apps: IndexMap::from([("com.wifi0", vec![&sus])]),
+            reviewed: vec![],
+        };
+        assert!(Format::Markdown.render(&report).unwrap().contains(
+            "| com.wifi0 | high | Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")<br>**What to do:** Back up evidence, then uninstall <com.wifi0> |\n"
+        ));
+        assert!(Format::Html.render(&report).unwrap().contains(
+            "<br><i>What to do:</i> Back up evidence, then uninstall &lt;com.wifi0&gt;</td>"
+        ));
+    }
+
+    #[test]
+    fn test_render_text() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Setting,
+            "Google Play Protect is turned off",
+        );
+        let app_sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")",
+        )
+        .with_remediation(Some(
+            "Back up evidence,\nthen uninstall the app\n".to_string(),
+        ))
+        .with_rule("SpyLive360")
+        .with_confidence(Some(Confidence::Medium));
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: Some(SuspicionLevel::High),
+            notes: Some("owner reports battery drain"),
+            warnings: vec!["Check \"files\" has failed"],
+            findings: vec![&sus],
+            apps: IndexMap::from([("com.wifi0", vec![&app_sus])]),
+            reviewed: vec![FindingId {
+                app: None,
+                description: "Google Play Protect is turned off",
+            }],
+        };
+        assert_eq!(
+            render_text(&report),
+            "spytrap-adb report for \"ABC123\"
+model: Pixel_7
+Only findings of level high and above are included.
+
+Notes:
+owner reports battery drain
+
+Findings:
+  [high] Google Play Protect is turned off (advisory) (reviewed)
+  [high] com.wifi0: Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\") (medium confidence)
+    What to do: Back up evidence, then uninstall the app
+
+The scan may be incomplete:
+  Check \"files\" has failed
+"
+        );
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let device = BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]);
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Setting,
+            "Google Play Protect is turned off",
+        );
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: Some("owner is Alice"),
+            warnings: vec![],
+            findings: vec![&sus],
+            apps: IndexMap::new(),
+            reviewed: vec![],
+        }
+        .anonymize();
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            json,
+            r#"{"serial":"sha256-e0bebd2281999342","device":{},"min_level":null,"findings":[{"level":"high","description":"Google Play Protect is turned off","kind":"setting"}],"apps":{}}"#
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::checks::Check;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use crate::utils;
+use async_trait::async_trait;
+use bstr::ByteSlice;
+use std::borrow::Cow;
+
+/// Locations that are always searched for leftover stalkerware files
+pub const DEFAULT_SCAN_PATHS: &[&str] = &["/sdcard/Download"];
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Listing {
+    pub files: Vec<String>,
+    /// directories that are not accessible without root, this is expected on most devices
+    pub denied: Vec<String>,
+    /// any other errors `find` ran into
+    pub errors: Vec<String>,
+}
+
+/// List all files in a directory
+pub async fn list_files(device: &dyn Shell, root: &str) -> Result<Listing> {
+    let cmd = format!(
+        "find {} -type f 2>&1",
+        shell_escape::escape(Cow::Borrowed(root))
+    );
+    debug!("Executing {:?}", cmd);
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    Ok(parse_find_output(&output))
+}
+
+fn parse_find_output(output: &[u8]) -> Listing {
+    let mut listing = Listing::default();
+    for line in output.lines() {
+        if line.is_empty() {
+            continue;
+        }
+        let line = String::from_utf8_lossy(line);
+        if let Some(error) = line.strip_prefix("find: ") {
+            if utils::is_permission_denied(error) {
+                info!("Directory is not accessible: {line:?}");
+                let path = error.rsplit_once(": ").map_or(error, |(path, _)| path);
+                listing.denied.push(path.trim_matches('\'').to_string());
+            } else {
+                warn!("Failed to list files: {line:?}");
+                listing.errors.push(line.into_owned());
+            }
+        } else {
+            listing.files.push(line.into_owned());
+        }
+    }
+    listing
+}
+
+/// Match a file path against the package ids of known stalkerware
+///
+/// Only names are matched, the rules don't contain hashes of files so their content isn't read
+pub fn audit_file(rules: &Rules, path: &str) -> Option<Suspicion> {
+    for component in path.split('/') {
+        let name = component.strip_suffix(".apk").unwrap_or(component);
+        if let Some(rule) = rules.get(name) {
+            return Some(
+                Suspicion::new(
+                    rules.get_level(rule),
+                    Kind::File,
+                    format!("Found file related to known stalkerware {rule:?}: {path:?}"),
+                )
+                .with_remediation(rules.get_remediation(rule).cloned())
+                .with_rule(rule.clone())
+                .with_confidence(rules.get_confidence(rule)),
+            );
+        }
+    }
+
+    if path.ends_with(".apk") {
+        return Some(Suspicion::new(
+            SuspicionLevel::Info,
+            Kind::File,
+            format!("Found app installer file: {path:?}"),
+        ));
+    }
+
+    None
+}
+
+pub struct FilesCheck;
+
+#[async_trait]
+impl Check for FilesCheck {
+    fn id(&self) -> &'static str {
+        "files"
+    }
+
+    fn description(&self) -> &'static str {
+        "Search shared storage for files named after known stalkerware packages"
+    }
+
+    fn offline(&self) -> bool {
+        true
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        rules: &Rules,
+        settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        let roots = DEFAULT_SCAN_PATHS
+            .iter()
+            .copied()
+            .chain(settings.scan_paths.iter().map(String::as_str));
+
+        for root in roots {
+            info!("Searching for suspicious files in {root:?}");
+            let listing = list_files(device, root).await?;
+            if !listing.denied.is_empty() {
+                report
+                    .warning(format!(
+                        "{} directories in {root:?} are not accessible without root and have not been searched: {:?}",
+                        listing.denied.len(),
+                        listing.denied
+                    ))
+                    .await?;
+            }
+            for error in listing.errors {
+                report.warning(error).await?;
+            }
+            for path in listing.files {
+                trace!("Found file: {path:?}");
+                if let Some(sus) = audit_file(rules, &path) {
+                    warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                    report.sus(sus).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Rules {
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "unit-test",
+                b"
+- name: SpyLive360
+  type: stalkerware
+  packages:
+  - com.wifi0
+",
+            )
+            .unwrap();
+        rules
+    }
+
+    #[test]
+    fn test_parse_find_output() {
+        let listing = parse_find_output(
+            b"/sdcard/Download/com.wifi0.apk
+find: '/sdcard/Download/secret': Permission denied
+/sdcard/Download/cat.jpg
+find: '/sdcard/Download/gone': No such file or directory
+",
+        );
+        assert_eq!(
+            listing,
+            Listing {
+                files: vec![
+                    "/sdcard/Download/com.wifi0.apk".to_string(),
+                    "/sdcard/Download/cat.jpg".to_string(),
+                ],
+                denied: vec!["/sdcard/Download/secret".to_string()],
+                errors: vec!["find: '/sdcard/Download/gone': No such file or directory".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_audit_known_apk() {
+        let sus = audit_file(&rules(), "/sdcard/Download/com.wifi0.apk");
+        assert_eq!(
+            sus,
+            Some(
+                Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::File,
+                    "Found file related to known stalkerware \"SpyLive360\": \"/sdcard/Download/com.wifi0.apk\"",
+                )
+                .with_rule("SpyLive360")
+            )
+        );
+    }
+
+    #[test]
+    fn test_audit_known_directory() {
+        let sus = audit_file(&rules(), "/sdcard/Android/data/com.wifi0/files/log.txt");
+        assert_eq!(
+            sus,
+            Some(
+                Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::File,
+                    "Found file related to known stalkerware \"SpyLive360\": \"/sdcard/Android/data/com.wifi0/files/log.txt\"",
+                )
+                .with_rule("SpyLive360")
+            )
+        );
+    }
+
+    #[test]
+    fn test_audit_unknown_files() {
+        let rules = rules();
+        assert_eq!(
+            audit_file(&rules, "/sdcard/Download/fdroid.apk"),
+            Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::File,
+                "Found app installer file: \"/sdcard/Download/fdroid.apk\""
+            ))
+        );
+        assert_eq!(audit_file(&rules, "/sdcard/Download/cat.jpg"), None);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::checks::Check;
+use crate::dashboard::getprop;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::pm;
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use crate::utils;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::ErrorKind;
+use std::path::Path;
+use tokio::fs;
+
+/// Properties of a device that are not expected to change between scans
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub build_fingerprint: Option<String>,
+    pub bootloader: Option<String>,
+    pub system_apps: BTreeSet<String>,
+    /// Changes with every system update, along with the build fingerprint
+    #[serde(default)]
+    pub security_patch: Option<String>,
+    #[serde(default)]
+    pub incremental: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub time: i64,
+    pub fingerprint: Fingerprint,
+}
+
+/// The most recent fingerprint of every device that has been scanned, by hardware serial
+pub type Store = BTreeMap<String, Record>;
+
+pub async fn collect(device: &dyn Shell) -> Result<Fingerprint> {
+    let build_fingerprint = getprop(device, "ro.build.fingerprint").await?;
+    let bootloader = getprop(device, "ro.bootloader").await?;
+    let security_patch = getprop(device, "ro.build.version.security_patch").await?;
+    let incremental = getprop(device, "ro.build.version.incremental").await?;
+    let system_apps = pm::list_packages(device)
+        .await?
+        .into_iter()
+        .filter(|apk| apk.is_system())
+        .map(|apk| apk.id)
+        .collect();
+    Ok(Fingerprint {
+        build_fingerprint,
+        bootloader,
+        system_apps,
+        security_patch,
+        incremental,
+    })
+}
+
+pub async fn load(path: &Path) -> Result<Store> {
+    match fs::read(path).await {
+        Ok(buf) => serde_json::from_slice(&buf)
+            .with_context(|| anyhow!("Failed to parse fingerprints from {path:?}")),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Store::new()),
+        Err(err) => Err(err).with_context(|| anyhow!("Failed to read fingerprints from {path:?}")),
+    }
+}
+
+pub async fn save(path: &Path, store: &Store) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| anyhow!("Failed to create directory at {parent:?}"))?;
+    }
+    let mut buf = serde_json::to_vec_pretty(store)?;
+    buf.push(b'\n');
+    fs::write(path, &buf)
+        .await
+        .with_context(|| anyhow!("Failed to write fingerprints to {path:?}"))?;
+    Ok(())
+}
+
+/// Describe what changed since the previous scan, a changed firmware is only suspicious without a system update
+pub fn compare(previous: &Record, current: &Fingerprint) -> Vec<Suspicion> {
+    let since = utils::format_datetime(previous.time);
+    let old = &previous.fingerprint;
+    let mut out = Vec::new();
+
+    let updated =
+        old.security_patch != current.security_patch || old.incremental != current.incremental;
+    let level = if old.build_fingerprint != current.build_fingerprint && !updated {
+        SuspicionLevel::Low
+    } else {
+        SuspicionLevel::Info
+    };
+
+    for (name, old, new) in [
+        (
+            "Build fingerprint",
+            &old.build_fingerprint,
+            &current.build_fingerprint,
+        ),
+        ("Bootloader", &old.bootloader, &current.bootloader),
+    ] {
+        if old != new {
+            out.push(Suspicion::new(
+                level,
+                Kind::Other,
+                format!(
+                    "{name} has changed since the scan on {since}: {:?} -> {:?}",
+                    utils::human_option_str(old.as_ref()),
+                    utils::human_option_str(new.as_ref())
+                ),
+            ));
+        }
+    }
+
+    for app in current.system_apps.difference(&old.system_apps) {
+        out.push(Suspicion::new(
+            level,
+            Kind::Other,
+            format!("System app {app:?} has been added since the scan on {since}"),
+        ));
+    }
+    for app in old.system_apps.difference(&current.system_apps) {
+        out.push(Suspicion::new(
+            level,
+            Kind::Other,
+            format!("System app {app:?} has been removed since the scan on {since}"),
+        ));
+    }
+
+    out
+}
+
+pub struct FingerprintCheck;
+
+#[async_trait]
+impl Check for FingerprintCheck {
+    fn id(&self) -> &'static str {
+        "fingerprint"
+    }
+
+    fn description(&self) -> &'static str {
+        "Compare the firmware and system apps with the previous scan of the device"
+    }
+
+    fn enabled(&self, settings: &scan::Settings) -> bool {
+        settings.fingerprints.is_some()
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        _rules: &Rules,
+        settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        let Some(path) = &settings.fingerprints else {
+            return Ok(());
+        };
+        let Some(serial) = getprop(device, "ro.serialno").await? else {
+            report
+                .warning("Device has no serial number, can't compare with previous scans".into())
+                .await?;
+            return Ok(());
+        };
+
+        info!("Comparing device fingerprint with previous scans");
+        let fingerprint = collect(device).await?;
+        let mut store = load(path).await?;
+        if let Some(previous) = store.get(&serial) {
+            for sus in compare(previous, &fingerprint) {
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.sus(sus).await?;
+            }
+        }
+
+        store.insert(
+            serial,
+            Record {
+                time: utils::now(),
+                fingerprint,
+            },
+        );
+        save(path, &store).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let previous = Record {
+            time: 1719963254,
+            fingerprint: Fingerprint {
+                build_fingerprint: Some(
+                    "google/panther/panther:14/AP2A/1:user/release-keys".into(),
+                ),
+                bootloader: Some("cloudripper-14.4".into()),
+                system_apps: BTreeSet::from([
+                    "com.android.settings".into(),
+                    "com.android.vending".into(),
+                ]),
+                security_patch: Some("2024-06-05".into()),
+                incremental: Some("11677807".into()),
+            },
+        };
+        assert_eq!(compare(&previous, &previous.fingerprint), &[]);
+
+        // the firmware has been replaced without a system update
+        let mut current = Fingerprint {
+            build_fingerprint: Some("google/panther/panther:14/AP2A/1:userdebug/test-keys".into()),
+            bootloader: Some("cloudripper-14.4".into()),
+            system_apps: BTreeSet::from(["com.android.settings".into(), "com.wifi0".into()]),
+            security_patch: Some("2024-06-05".into()),
+            incremental: Some("11677807".into()),
+        };
+        assert_eq!(
+            compare(&previous, &current),
+            &[
+                Suspicion::new(SuspicionLevel::Low, Kind::Other, "Build fingerprint has changed since the scan on 2024-07-02 23:34 UTC: \"google/panther/panther:14/AP2A/1:user/release-keys\" -> \"google/panther/panther:14/AP2A/1:userdebug/test-keys\""),
+                Suspicion::new(SuspicionLevel::Low, Kind::Other, "System app \"com.wifi0\" has been added since the scan on 2024-07-02 23:34 UTC"),
+                Suspicion::new(SuspicionLevel::Low, Kind::Other, "System app \"com.android.vending\" has been removed since the scan on 2024-07-02 23:34 UTC"),
+            ]
+        );
+
+        // an OTA update changes all of them
+        current.security_patch = Some("2024-07-05".into());
+        current.incremental = Some("11942050".into());
+        assert!(compare(&previous, &current)
+            .iter()
+            .all(|sus| sus.level == SuspicionLevel::Info));
+    }
+}
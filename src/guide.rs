@@ -0,0 +1,246 @@
+//! A step-by-step scan for volunteers without forensic training
+use crate::checks;
+use crate::errors::*;
+use crate::i18n::{Lang, Msg};
+use crate::ioc::{Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, Findings};
+use crate::utils;
+use forensic_adb::{DeviceInfo, Host};
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::time::Duration;
+use tokio::io::{self, AsyncBufReadExt, BufReader, Lines, Stdin};
+use tokio::time;
+
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+type Input = Lines<BufReader<Stdin>>;
+
+async fn prompt(input: &mut Input, msg: &str) -> Result<String> {
+    print!("{msg}");
+    std::io::stdout().flush()?;
+    let line = input
+        .next_line()
+        .await
+        .context("Failed to read from stdin")?
+        .context("Input has ended, the guided scan was not completed")?;
+    Ok(line.trim().to_string())
+}
+
+/// Ask on the command line to confirm the operator is authorized to scan, like `tui::ask_consent` does in the terminal ui
+pub async fn ask_consent(lang: Lang) -> Result<bool> {
+    let mut input = BufReader::new(io::stdin()).lines();
+    confirm_consent(&mut input, lang).await
+}
+
+async fn confirm_consent(input: &mut Input, lang: Lang) -> Result<bool> {
+    println!(
+        "{}\n\n{}\n",
+        lang.get(Msg::ConsentTitle),
+        lang.get(Msg::Consent)
+    );
+    let answer = prompt(input, lang.get(Msg::ConsentLine)).await?;
+    Ok(answer.eq_ignore_ascii_case("y"))
+}
+
+async fn wait_for_device(adb_host: &Host, input: &mut Input, lang: Lang) -> Result<DeviceInfo> {
+    println!(
+        "{}\n\n{}",
+        lang.get(Msg::StepConnect),
+        lang.get(Msg::ConnectHelp)
+    );
+    let mut waiting = false;
+    loop {
+        let mut devices = adb_host
+            .devices::<Vec<_>>()
+            .await
+            .map_err(ScanError::AdbUnavailable)?;
+        match devices.len() {
+            0 => {
+                if !waiting {
+                    println!("{}", lang.get(Msg::WaitingForDevice));
+                    waiting = true;
+                }
+                time::sleep(DEVICE_POLL_INTERVAL).await;
+            }
+            1 => return Ok(devices.remove(0)),
+            _ => {
+                println!("{}", lang.get(Msg::MultipleDevices));
+                for (i, device) in devices.iter().enumerate() {
+                    println!("  {}) {}", i + 1, utils::format_device(device));
+                }
+                let answer = prompt(input, lang.get(Msg::PickDevice)).await?;
+                match answer.parse::<usize>() {
+                    Ok(n) if (1..=devices.len()).contains(&n) => return Ok(devices.remove(n - 1)),
+                    _ => println!(
+                        "{}",
+                        lang.format(Msg::NoSuchDevice, &[("answer", &format!("{answer:?}"))])
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// What a finding means, in plain language
+fn meaning(lang: Lang, sus: &Suspicion) -> String {
+    if let Some(rule) = &sus.rule {
+        return lang.format(Msg::MeaningRule, &[("rule", &format!("{rule:?}"))]);
+    }
+    lang.get(match sus.level {
+        SuspicionLevel::High => Msg::MeaningHigh,
+        SuspicionLevel::Medium => Msg::MeaningMedium,
+        SuspicionLevel::Low => Msg::MeaningLow,
+        SuspicionLevel::Info | SuspicionLevel::Good => Msg::MeaningNone,
+    })
+    .to_string()
+}
+
+/// What to do about a finding, the remediation of the rule if it has one
+fn next_step(lang: Lang, sus: &Suspicion) -> &str {
+    if let Some(remediation) = &sus.remediation {
+        return remediation.trim();
+    }
+    lang.get(match sus.level {
+        SuspicionLevel::High | SuspicionLevel::Medium => Msg::NextStepSevere,
+        _ => Msg::NextStepOther,
+    })
+}
+
+/// Describe a single finding, `n` counts from 1
+pub fn render_finding(
+    lang: Lang,
+    n: usize,
+    total: usize,
+    app: Option<&str>,
+    sus: &Suspicion,
+) -> String {
+    let mut out = String::new();
+    let header = lang.format(
+        Msg::FindingOf,
+        &[
+            ("n", &n.to_string()),
+            ("total", &total.to_string()),
+            ("level", lang.level(sus.level)),
+        ],
+    );
+    writeln!(out, "{header}").ok();
+    if let Some(app) = app {
+        writeln!(out, "{}", lang.format(Msg::FindingApp, &[("app", app)])).ok();
+    }
+    writeln!(out, "{}\n", sus.description).ok();
+    writeln!(
+        out,
+        "{}{}",
+        lang.get(Msg::WhatThisMeans),
+        meaning(lang, sus)
+    )
+    .ok();
+    writeln!(out, "{}{}", lang.get(Msg::WhatToDo), next_step(lang, sus)).ok();
+    out
+}
+
+/// The findings worth walking through, most severe first
+fn relevant(findings: &Findings) -> Vec<(Option<&str>, &Suspicion)> {
+    let device = findings.findings.iter().map(|sus| (None, sus));
+    let apps = findings
+        .apps
+        .iter()
+        .flat_map(|(name, list)| list.iter().map(move |sus| (Some(name.as_str()), sus)));
+    let mut out = device
+        .chain(apps)
+        .filter(|(_, sus)| sus.level >= SuspicionLevel::Low)
+        .collect::<Vec<_>>();
+    out.sort_by_key(|(_, sus)| Reverse(sus.level));
+    out
+}
+
+/// The guided scan leaves out the apps and files, they take the longest to inspect
+fn quick_settings() -> scan::Settings {
+    let checks = checks::registry()
+        .iter()
+        .map(|check| check.id())
+        .filter(|id| !["apps", "files"].contains(id))
+        .map(String::from)
+        .collect();
+    scan::Settings {
+        skip_apps: true,
+        checks: Some(checks),
+        ..Default::default()
+    }
+}
+
+pub async fn run(adb_host: &Host, rules: &Rules, lang: Lang) -> Result<()> {
+    let mut input = BufReader::new(io::stdin()).lines();
+
+    let device = wait_for_device(adb_host, &mut input, lang).await?;
+    let found = utils::format_device(&device);
+    println!("{}\n", lang.format(Msg::FoundDevice, &[("device", &found)]));
+
+    println!("{}\n", lang.get(Msg::StepConsent));
+    if !confirm_consent(&mut input, lang).await? {
+        bail!("Authorization to scan has not been confirmed");
+    }
+    let time = utils::format_datetime(utils::now());
+    println!(
+        "{}\n",
+        lang.format(Msg::ConsentConfirmed, &[("time", &time)])
+    );
+
+    println!("{}\n", lang.get(Msg::StepScan));
+    println!("{}", lang.get(Msg::ScanTakesMinutes));
+    let findings = scan::collect(adb_host, Some(&device.serial), rules, &quick_settings()).await?;
+    println!("{}\n", lang.get(Msg::ScanComplete));
+
+    println!("{}\n", lang.get(Msg::StepReview));
+    let relevant = relevant(&findings);
+    if relevant.is_empty() {
+        println!("{}\n", lang.get(Msg::NothingFound));
+    }
+    for (i, (app, sus)) in relevant.iter().enumerate() {
+        println!("{}", render_finding(lang, i + 1, relevant.len(), *app, sus));
+        prompt(&mut input, lang.get(Msg::PressEnter)).await?;
+        println!();
+    }
+    if !findings.warnings.is_empty() {
+        let n = findings.warnings.len().to_string();
+        println!("{}\n", lang.format(Msg::GuideWarnings, &[("n", &n)]));
+    }
+    println!("{}", lang.get(Msg::ClosingAdvice));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::Kind;
+
+    #[test]
+    fn test_quick_settings() {
+        let checks = quick_settings().checks.unwrap();
+        assert!(checks.contains("settings"));
+        assert!(!checks.contains("apps"));
+        assert!(!checks.contains("files"));
+    }
+
+    #[test]
+    fn test_render_finding() {
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")",
+        )
+        .with_rule("SpyLive360");
+        assert_eq!(
+            render_finding(Lang::En, 1, 2, Some("com.wifi0"), &sus),
+            "Finding 1 of 2 (high)
+App: com.wifi0
+Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")
+
+What this means: This matches \"SpyLive360\", a known stalkerware or monitoring app.
+What to do: Write it down and talk to the owner about it before removing or changing anything.
+"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! Keep the findings of past scans, so indicators can be looked up again later
+use crate::errors::*;
+use crate::export::{self, Report};
+use crate::ioc::Suspicion;
+use crate::utils;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Everything that has been reported during a past scan
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub time: i64,
+    pub serial: String,
+    #[serde(default)]
+    pub device: BTreeMap<String, String>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub findings: Vec<Suspicion>,
+    #[serde(default)]
+    pub apps: IndexMap<String, Vec<Suspicion>>,
+}
+
+impl Record {
+    pub fn from_report(report: &Report<'_>, time: i64) -> Self {
+        Record {
+            time,
+            serial: report.serial.to_string(),
+            device: report.device.clone(),
+            warnings: report.warnings.iter().map(|w| w.to_string()).collect(),
+            findings: report.findings.iter().map(|sus| (*sus).clone()).collect(),
+            apps: report
+                .apps
+                .iter()
+                .map(|(name, findings)| {
+                    let findings = findings.iter().map(|sus| (*sus).clone()).collect();
+                    (name.to_string(), findings)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A finding of a past scan that contains the search term
+#[derive(Debug, PartialEq)]
+pub struct Match<'a> {
+    pub record: &'a Record,
+    pub app: Option<&'a str>,
+    pub sus: &'a Suspicion,
+}
+
+pub fn filename(serial: &str, time: i64) -> String {
+    let serial = export::safe_serial(serial);
+    format!("{serial}-{time}.json")
+}
+
+pub async fn save(dir: &Path, record: &Record) -> Result<PathBuf> {
+    fs::create_dir_all(dir)
+        .await
+        .with_context(|| anyhow!("Failed to create directory at {dir:?}"))?;
+    let path = dir.join(filename(&record.serial, record.time));
+    let buf = serde_json::to_vec(record)?;
+    fs::write(&path, &buf)
+        .await
+        .with_context(|| anyhow!("Failed to write scan history to {path:?}"))?;
+    Ok(path)
+}
+
+/// Every recorded scan, the oldest first
+pub async fn load(dir: &Path) -> Result<Vec<Record>> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err).with_context(|| anyhow!("Failed to read scan history from {dir:?}"))
+        }
+    };
+    let mut records = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let buf = fs::read(&path)
+            .await
+            .with_context(|| anyhow!("Failed to read scan history from {path:?}"))?;
+        match serde_json::from_slice::<Record>(&buf) {
+            Ok(record) => records.push(record),
+            Err(err) => warn!("Skipping invalid scan history file {path:?}: {err:#}"),
+        }
+    }
+    records.sort_by_key(|record| record.time);
+    Ok(records)
+}
+
+/// Findings of past scans that mention the term in the app name, description or rule, eg. a package name, domain or rule
+pub fn search<'a>(records: &'a [Record], term: &str) -> Vec<Match<'a>> {
+    let term = term.to_lowercase();
+    let matches = |s: &str| s.to_lowercase().contains(&term);
+    let matches_sus =
+        |sus: &Suspicion| matches(&sus.description) || sus.rule.as_deref().is_some_and(matches);
+    let mut out = Vec::new();
+    for record in records {
+        for sus in &record.findings {
+            if matches_sus(sus) {
+                out.push(Match {
+                    record,
+                    app: None,
+                    sus,
+                });
+            }
+        }
+        for (app, findings) in &record.apps {
+            for sus in findings {
+                if matches(app) || matches_sus(sus) {
+                    out.push(Match {
+                        record,
+                        app: Some(app),
+                        sus,
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+pub fn render(records: &[Record], term: &str, matches: &[Match<'_>]) -> String {
+    let mut out = String::new();
+    let mut scans = 0;
+    let mut previous: Option<&Record> = None;
+    for m in matches {
+        // matches of the same scan are next to each other
+        if !previous.is_some_and(|previous| std::ptr::eq(previous, m.record)) {
+            previous = Some(m.record);
+            scans += 1;
+            writeln!(
+                out,
+                "Scan of {:?} on {}:",
+                m.record.serial,
+                utils::format_datetime(m.record.time)
+            )
+            .ok();
+        }
+        let level = m.sus.level.as_str();
+        match m.app {
+            Some(app) => writeln!(out, "  [{level}] {app}: {}", m.sus.description),
+            None => writeln!(out, "  [{level}] {}", m.sus.description),
+        }
+        .ok();
+    }
+    if matches.is_empty() {
+        writeln!(
+            out,
+            "No past scan has a finding about {term:?} ({} scans searched)",
+            records.len()
+        )
+        .ok();
+    } else {
+        writeln!(
+            out,
+            "Found {} findings about {term:?} in {scans} of {} scans",
+            matches.len(),
+            records.len()
+        )
+        .ok();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Kind, SuspicionLevel};
+
+    fn sus(level: SuspicionLevel, description: &str) -> Suspicion {
+        Suspicion::new(level, Kind::Other, description.to_string())
+    }
+
+    #[test]
+    fn test_search() {
+        let records = [
+            Record {
+                time: 1719963254,
+                serial: "ABC123".to_string(),
+                device: BTreeMap::new(),
+                warnings: vec![],
+                findings: vec![sus(
+                    SuspicionLevel::High,
+                    "Private DNS setting contains c2 domain of known stalkerware \"SpyLive360\": \"spylive360.com\"",
+                )],
+                apps: IndexMap::from([
+                    (
+                        "com.wifi0".to_string(),
+                        vec![sus(SuspicionLevel::Low, "Package has no launcher icon")],
+                    ),
+                    (
+                        "com.example".to_string(),
+                        vec![sus(SuspicionLevel::High, "Found known stalkerware")
+                            .with_rule("TheTruthSpy")],
+                    ),
+                ]),
+            },
+            Record {
+                time: 1720000000,
+                serial: "XYZ".to_string(),
+                device: BTreeMap::new(),
+                warnings: vec![],
+                findings: vec![],
+                apps: IndexMap::new(),
+            },
+        ];
+
+        let matches = search(&records, "SPYLIVE360");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].app, None);
+        let matches = search(&records, "thetruthspy");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].app, Some("com.example"));
+        let matches = search(&records, "com.wifi0");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].app, Some("com.wifi0"));
+
+        assert_eq!(
+            render(&records, "com.wifi0", &matches),
+            "Scan of \"ABC123\" on 2024-07-02 23:34 UTC:
+  [low] com.wifi0: Package has no launcher icon
+Found 1 findings about \"com.wifi0\" in 1 of 2 scans
+"
+        );
+        assert_eq!(
+            render(&records, "org.example", &search(&records, "org.example")),
+            "No past scan has a finding about \"org.example\" (2 scans searched)\n"
+        );
+    }
+}
@@ -0,0 +1,826 @@
+//! Translations of the strings shown to volunteers, anything that isn't translated is shown in English
+use crate::ioc::{Kind, SuspicionLevel};
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Lang {
+    /// The language of the locale, eg. `fr_FR.UTF-8`, the first of these variables that is set wins
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+            .and_then(|value| Self::from_locale(&value))
+            .unwrap_or_default()
+    }
+
+    fn from_locale(locale: &str) -> Option<Lang> {
+        let code = locale.split(['_', '.', '@', '-']).next()?;
+        Lang::value_variants()
+            .iter()
+            .find(|lang| lang.code() == code.to_ascii_lowercase())
+            .copied()
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Fr => "fr",
+        }
+    }
+
+    pub fn get(&self, msg: Msg) -> &'static str {
+        match self {
+            Lang::En => None,
+            Lang::Fr => fr(msg),
+        }
+        .unwrap_or_else(|| en(msg))
+    }
+
+    /// The name of a severity as it's shown on screen, exports always use the english name
+    pub fn level(&self, level: SuspicionLevel) -> &'static str {
+        self.get(match level {
+            SuspicionLevel::High => Msg::LevelHigh,
+            SuspicionLevel::Medium => Msg::LevelMedium,
+            SuspicionLevel::Low => Msg::LevelLow,
+            SuspicionLevel::Info => Msg::LevelInfo,
+            SuspicionLevel::Good => Msg::LevelGood,
+        })
+    }
+
+    /// The name of a kind of finding as it's shown on screen
+    pub fn kind(&self, kind: Kind) -> &'static str {
+        self.get(match kind {
+            Kind::Package => Msg::KindPackage,
+            Kind::File => Msg::KindFile,
+            Kind::Network => Msg::KindNetwork,
+            Kind::Setting => Msg::KindSetting,
+            Kind::Other => Msg::KindOther,
+        })
+    }
+
+    /// Fill in the `{name}` placeholders of a message
+    pub fn format(&self, msg: Msg, args: &[(&str, &str)]) -> String {
+        let mut out = self.get(msg).to_string();
+        for (name, value) in args {
+            out = out.replace(&format!("{{{name}}}"), value);
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    ConsentTitle,
+    Consent,
+    ConsentKey,
+    ConsentLine,
+    Scanning,
+    Downloading,
+    Idle,
+    Press,
+    ToToggle,
+    ToContinue,
+    ToRerunChecks,
+    ToHideLegend,
+    ForLegend,
+    ToGoBack,
+    ToReturnToReport,
+    ForRuleStatistics,
+    ToEditRules,
+    ToExit,
+    ToSelect,
+    ToSave,
+    ToConfirm,
+    ToCancel,
+    NoChecksToRerun,
+    NoReportToSwitchTo,
+    SelectRuleFile,
+    ToSkipApps,
+    ToScanApps,
+    ToUpdateRules,
+    NoRulesUrl,
+    AppsSkipped,
+    AppsScanned,
+    ScanRunning,
+    ConnectHelp,
+    ClosingAdvice,
+    FindingOf,
+    FindingApp,
+    WhatThisMeans,
+    WhatToDo,
+    MeaningRule,
+    MeaningHigh,
+    MeaningMedium,
+    MeaningLow,
+    MeaningNone,
+    NextStepSevere,
+    NextStepOther,
+    ConsentConfirmed,
+    Exiting,
+    MaskingSerials,
+    ShowingSerials,
+    GroupingFindings,
+    ListingFindings,
+    FollowingFindings,
+    StoppedFollowing,
+    NoHighBelow,
+    NoHighAbove,
+    ReportWritten,
+    ExportFailed,
+    SaveFailed,
+    ReportCopied,
+    CopyFailed,
+    HistoryFailed,
+    BundleWritten,
+    BundleFailed,
+    ProfileWritten,
+    ProfileFailed,
+    SessionWritten,
+    SessionFailed,
+    SessionOpened,
+    RulesLoaded,
+    DownloadingRules,
+    RulesUpdated,
+    RulesUpdateFailed,
+    RulesUpdateRunning,
+    RerunningChecks,
+    CapturingBugreport,
+    NoBugreport,
+    BugreportWritten,
+    BugreportFailed,
+    ConfirmBugreport,
+    ConfirmUninstall,
+    ConfirmDisable,
+    NotUninstalling,
+    NotDisabling,
+    ReadOnlyUninstall,
+    ReadOnlyDisable,
+    Uninstalled,
+    Disabled,
+    Uninstalling,
+    Disabling,
+    ExportFormat,
+    SaveReportTo,
+    NotesPrompt,
+    ScanFailed,
+    Findings,
+    VisibleRange,
+    HiddenByFilter,
+    FollowingTitle,
+    NotesTitle,
+    WarningsTitle,
+    ScanCancelled,
+    Took,
+    AppRow,
+    WarningTag,
+    Scanned,
+    NoFindings,
+    SummaryPartial,
+    SummaryIncomplete,
+    Saved,
+    LegendSeverity,
+    LegendMarkers,
+    LevelHigh,
+    LevelMedium,
+    LevelLow,
+    LevelInfo,
+    LevelGood,
+    KindPackage,
+    KindFile,
+    KindNetwork,
+    KindSetting,
+    KindOther,
+    DeviceOverview,
+    Device,
+    QueryingDevice,
+    QueryDeviceFailed,
+    AndroidVersion,
+    SecurityPatch,
+    InstalledApps,
+    InstalledAppsCount,
+    DeviceAdmins,
+    WirelessDebugging,
+    Enabled,
+    DisabledSetting,
+    ToStartScan,
+    ToExportProfile,
+    SelectRuleFiles,
+    RuleStatistics,
+    LoadRulesFailed,
+    Rules,
+    PackageNames,
+    Certificates,
+    Websites,
+    C2Domains,
+    C2Ips,
+    ConnectedDevices,
+    EnumeratingDevices,
+    Released,
+    UpdateAvailable,
+    StepConnect,
+    WaitingForDevice,
+    MultipleDevices,
+    PickDevice,
+    NoSuchDevice,
+    FoundDevice,
+    StepConsent,
+    StepScan,
+    ScanTakesMinutes,
+    ScanComplete,
+    StepReview,
+    NothingFound,
+    PressEnter,
+    GuideWarnings,
+}
+
+impl Msg {
+    pub const ALL: &'static [Msg] = &[
+        Msg::ConsentTitle,
+        Msg::Consent,
+        Msg::ConsentKey,
+        Msg::ConsentLine,
+        Msg::Scanning,
+        Msg::Downloading,
+        Msg::Idle,
+        Msg::Press,
+        Msg::ToToggle,
+        Msg::ToContinue,
+        Msg::ToRerunChecks,
+        Msg::ToHideLegend,
+        Msg::ForLegend,
+        Msg::ToGoBack,
+        Msg::ToReturnToReport,
+        Msg::ForRuleStatistics,
+        Msg::ToEditRules,
+        Msg::ToExit,
+        Msg::ToSelect,
+        Msg::ToSave,
+        Msg::ToConfirm,
+        Msg::ToCancel,
+        Msg::NoChecksToRerun,
+        Msg::NoReportToSwitchTo,
+        Msg::SelectRuleFile,
+        Msg::ToSkipApps,
+        Msg::ToScanApps,
+        Msg::ToUpdateRules,
+        Msg::NoRulesUrl,
+        Msg::AppsSkipped,
+        Msg::AppsScanned,
+        Msg::ScanRunning,
+        Msg::ConnectHelp,
+        Msg::ClosingAdvice,
+        Msg::FindingOf,
+        Msg::FindingApp,
+        Msg::WhatThisMeans,
+        Msg::WhatToDo,
+        Msg::MeaningRule,
+        Msg::MeaningHigh,
+        Msg::MeaningMedium,
+        Msg::MeaningLow,
+        Msg::MeaningNone,
+        Msg::NextStepSevere,
+        Msg::NextStepOther,
+        Msg::ConsentConfirmed,
+        Msg::Exiting,
+        Msg::MaskingSerials,
+        Msg::ShowingSerials,
+        Msg::GroupingFindings,
+        Msg::ListingFindings,
+        Msg::FollowingFindings,
+        Msg::StoppedFollowing,
+        Msg::NoHighBelow,
+        Msg::NoHighAbove,
+        Msg::ReportWritten,
+        Msg::ExportFailed,
+        Msg::SaveFailed,
+        Msg::ReportCopied,
+        Msg::CopyFailed,
+        Msg::HistoryFailed,
+        Msg::BundleWritten,
+        Msg::BundleFailed,
+        Msg::ProfileWritten,
+        Msg::ProfileFailed,
+        Msg::SessionWritten,
+        Msg::SessionFailed,
+        Msg::SessionOpened,
+        Msg::RulesLoaded,
+        Msg::DownloadingRules,
+        Msg::RulesUpdated,
+        Msg::RulesUpdateFailed,
+        Msg::RulesUpdateRunning,
+        Msg::RerunningChecks,
+        Msg::CapturingBugreport,
+        Msg::NoBugreport,
+        Msg::BugreportWritten,
+        Msg::BugreportFailed,
+        Msg::ConfirmBugreport,
+        Msg::ConfirmUninstall,
+        Msg::ConfirmDisable,
+        Msg::NotUninstalling,
+        Msg::NotDisabling,
+        Msg::ReadOnlyUninstall,
+        Msg::ReadOnlyDisable,
+        Msg::Uninstalled,
+        Msg::Disabled,
+        Msg::Uninstalling,
+        Msg::Disabling,
+        Msg::ExportFormat,
+        Msg::SaveReportTo,
+        Msg::NotesPrompt,
+        Msg::ScanFailed,
+        Msg::Findings,
+        Msg::VisibleRange,
+        Msg::HiddenByFilter,
+        Msg::FollowingTitle,
+        Msg::NotesTitle,
+        Msg::WarningsTitle,
+        Msg::ScanCancelled,
+        Msg::Took,
+        Msg::AppRow,
+        Msg::WarningTag,
+        Msg::Scanned,
+        Msg::NoFindings,
+        Msg::SummaryPartial,
+        Msg::SummaryIncomplete,
+        Msg::Saved,
+        Msg::LegendSeverity,
+        Msg::LegendMarkers,
+        Msg::LevelHigh,
+        Msg::LevelMedium,
+        Msg::LevelLow,
+        Msg::LevelInfo,
+        Msg::LevelGood,
+        Msg::KindPackage,
+        Msg::KindFile,
+        Msg::KindNetwork,
+        Msg::KindSetting,
+        Msg::KindOther,
+        Msg::DeviceOverview,
+        Msg::Device,
+        Msg::QueryingDevice,
+        Msg::QueryDeviceFailed,
+        Msg::AndroidVersion,
+        Msg::SecurityPatch,
+        Msg::InstalledApps,
+        Msg::InstalledAppsCount,
+        Msg::DeviceAdmins,
+        Msg::WirelessDebugging,
+        Msg::Enabled,
+        Msg::DisabledSetting,
+        Msg::ToStartScan,
+        Msg::ToExportProfile,
+        Msg::SelectRuleFiles,
+        Msg::RuleStatistics,
+        Msg::LoadRulesFailed,
+        Msg::Rules,
+        Msg::PackageNames,
+        Msg::Certificates,
+        Msg::Websites,
+        Msg::C2Domains,
+        Msg::C2Ips,
+        Msg::ConnectedDevices,
+        Msg::EnumeratingDevices,
+        Msg::Released,
+        Msg::UpdateAvailable,
+        Msg::StepConnect,
+        Msg::WaitingForDevice,
+        Msg::MultipleDevices,
+        Msg::PickDevice,
+        Msg::NoSuchDevice,
+        Msg::FoundDevice,
+        Msg::StepConsent,
+        Msg::StepScan,
+        Msg::ScanTakesMinutes,
+        Msg::ScanComplete,
+        Msg::StepReview,
+        Msg::NothingFound,
+        Msg::PressEnter,
+        Msg::GuideWarnings,
+    ];
+}
+
+fn en(msg: Msg) -> &'static str {
+    match msg {
+        Msg::ConsentTitle => "Authorization required",
+        Msg::Consent => "\
+Scanning a device gives access to private information of its owner.
+
+Only continue if the owner of the device has asked for this scan, or you are otherwise authorized to inspect it.",
+        Msg::ConsentKey => "Press y to confirm you are authorized to scan, any other key exits without connecting to a device.",
+        Msg::ConsentLine => "Type y and press Enter to confirm you are authorized to scan, anything else exits without connecting to a device: ",
+        Msg::Scanning => " scanning",
+        Msg::Downloading => " downloading - ",
+        Msg::Idle => "idle - ",
+        Msg::Press => "Press ",
+        Msg::ToToggle => " to toggle, ",
+        Msg::ToContinue => " to continue - ",
+        Msg::ToRerunChecks => " to re-run {n} checks with warnings - ",
+        Msg::ToHideLegend => " to hide the legend - ",
+        Msg::ForLegend => " for a legend - ",
+        Msg::ToGoBack => " to go back - ",
+        Msg::ToReturnToReport => " to return to the report - ",
+        Msg::ForRuleStatistics => " for rule statistics, ",
+        Msg::ToEditRules => " to edit rules - ",
+        Msg::ToExit => " to exit - ",
+        Msg::ToSelect => " to select, ",
+        Msg::ToSave => " to save, ",
+        Msg::ToConfirm => " to confirm, ",
+        Msg::ToCancel => " to cancel",
+        Msg::NoChecksToRerun => "There are no checks with warnings to run again",
+        Msg::NoReportToSwitchTo => "There's no report to switch to",
+        Msg::SelectRuleFile => "Select at least one rule file",
+        Msg::ToSkipApps => " to skip apps, ",
+        Msg::ToScanApps => " to scan apps, ",
+        Msg::ToUpdateRules => " to update rules, ",
+        Msg::NoRulesUrl => "Set rules-url in the config to update the rules from it",
+        Msg::AppsSkipped => "apps: skipped",
+        Msg::AppsScanned => "apps: scanned",
+        Msg::ScanRunning => "Wait for the running scan to finish before changing its settings",
+        Msg::ConnectHelp => "\
+Connect the phone with a usb cable and unlock it. If it's not detected:
+
+  1. Open Settings, About phone, and tap \"Build number\" seven times
+  2. Open Settings, Developer options, and turn on \"USB debugging\"
+  3. Confirm the \"Allow USB debugging?\" prompt on the phone
+",
+        Msg::ClosingAdvice => "\
+Before changing anything on the phone, talk with its owner about their safety.
+Removing an app or changing a setting may alert the person who set it up.
+Turn off USB debugging again once you're done.",
+        Msg::FindingOf => "Finding {n} of {total} ({level})",
+        Msg::FindingApp => "App: {app}",
+        Msg::WhatThisMeans => "What this means: ",
+        Msg::WhatToDo => "What to do: ",
+        Msg::MeaningRule => "This matches {rule}, a known stalkerware or monitoring app.",
+        Msg::MeaningHigh => {
+            "This is a strong sign that someone else can monitor or control the phone."
+        }
+        Msg::MeaningMedium => {
+            "This can be used to monitor the phone, ask the owner whether they set it up."
+        }
+        Msg::MeaningLow => "This is unusual, but often harmless on its own.",
+        Msg::MeaningNone => "This is not suspicious on its own.",
+        Msg::NextStepSevere => {
+            "Write it down and talk to the owner about it before removing or changing anything."
+        }
+        Msg::NextStepOther => {
+            "Ask the owner whether they recognize it, no action is needed if they do."
+        }
+        Msg::ConsentConfirmed => "Authorization to scan was confirmed at {time}",
+        Msg::Exiting => "Exiting...",
+        Msg::MaskingSerials => "Masking serials and account names on screen",
+        Msg::ShowingSerials => "Showing serials and account names on screen",
+        Msg::GroupingFindings => "Grouping findings by severity and kind, ENTER folds a group",
+        Msg::ListingFindings => "Listing findings by app",
+        Msg::FollowingFindings => "Following new findings",
+        Msg::StoppedFollowing => "Stopped following new findings",
+        Msg::NoHighBelow => "No more high severity findings below",
+        Msg::NoHighAbove => "No more high severity findings above",
+        Msg::ReportWritten => "Report written to {path}",
+        Msg::ExportFailed => "Failed to export report: {err}",
+        Msg::SaveFailed => "Failed to save report: {err}",
+        Msg::ReportCopied => "Copied report with {count} findings to the clipboard",
+        Msg::CopyFailed => "Failed to copy report: {err}",
+        Msg::HistoryFailed => "Failed to record scan history: {err}",
+        Msg::BundleWritten => "Evidence bundle with {count} collected files written to {path}",
+        Msg::BundleFailed => "Failed to write evidence bundle: {err}",
+        Msg::ProfileWritten => "Device profile written to {path}",
+        Msg::ProfileFailed => "Failed to export device profile: {err}",
+        Msg::SessionWritten => "Session written to {path}, open it with --session",
+        Msg::SessionFailed => "Failed to save session: {err}",
+        Msg::SessionOpened => "Opened session of {serial}",
+        Msg::RulesLoaded => "Loaded rules from {path} (sha256={sha256})",
+        Msg::DownloadingRules => "Downloading rules from {url}...",
+        Msg::RulesUpdated => "Updated rules from {url} (sha256={sha256})",
+        Msg::RulesUpdateFailed => "Failed to update rules: {err}",
+        Msg::RulesUpdateRunning => "The rules are already being updated",
+        Msg::RerunningChecks => "Running checks again: {checks}",
+        Msg::CapturingBugreport => "Capturing bugreport, this may take a few minutes...",
+        Msg::NoBugreport => "Not capturing a bugreport",
+        Msg::BugreportWritten => "Bugreport written to {path}",
+        Msg::BugreportFailed => "Failed to capture bugreport: {err}",
+        Msg::ConfirmBugreport => "Capture a bugreport of {serial} into {path}? It contains detailed system logs and can be over 100MB - proceed? y/N",
+        Msg::ConfirmUninstall => "This will uninstall {package} from {serial}, this can not be undone - proceed? y/N",
+        Msg::ConfirmDisable => "This will disable {package} on {serial} until it's enabled again - proceed? y/N",
+        Msg::NotUninstalling => "Not going to uninstall {package}",
+        Msg::NotDisabling => "Not going to disable {package}",
+        Msg::ReadOnlyUninstall => "Read-only mode, restart with --allow-remediation to uninstall apps",
+        Msg::ReadOnlyDisable => "Read-only mode, restart with --allow-remediation to disable apps",
+        Msg::Uninstalled => "Package {package} has been uninstalled",
+        Msg::Disabled => "Package {package} has been disabled",
+        Msg::Uninstalling => "Uninstalling {package}...",
+        Msg::Disabling => "Disabling {package}...",
+        Msg::ExportFormat => "Export format: ",
+        Msg::SaveReportTo => "Save {format} report to: ",
+        Msg::NotesPrompt => "Notes: ",
+        Msg::ScanFailed => "Scan has failed",
+        Msg::Findings => "Findings",
+        Msg::VisibleRange => "[{first}-{last} of {total}]",
+        Msg::HiddenByFilter => "{n} hidden by filter (f)",
+        Msg::FollowingTitle => "following new findings (F)",
+        Msg::NotesTitle => "Notes: {notes}",
+        Msg::WarningsTitle => "{n} warnings, scan may be incomplete",
+        Msg::ScanCancelled => "Scan cancelled - partial results",
+        Msg::Took => "took {duration}",
+        Msg::AppRow => "App {name}",
+        Msg::WarningTag => "[warning]",
+        Msg::Scanned => "Scanned {serial}",
+        Msg::NoFindings => "no findings",
+        Msg::SummaryPartial => "scan did not complete, partial results",
+        Msg::SummaryIncomplete => "scan may be incomplete, {n} warnings",
+        Msg::Saved => "Saved {path}",
+        Msg::LegendSeverity => "Severity: ",
+        Msg::LegendMarkers => "{indicator} matches a known indicator  {advisory} advisory",
+        Msg::LevelHigh => "high",
+        Msg::LevelMedium => "medium",
+        Msg::LevelLow => "low",
+        Msg::LevelInfo => "info",
+        Msg::LevelGood => "good",
+        Msg::KindPackage => "app",
+        Msg::KindFile => "file",
+        Msg::KindNetwork => "network",
+        Msg::KindSetting => "setting",
+        Msg::KindOther => "other",
+        Msg::DeviceOverview => "Device overview",
+        Msg::Device => "Device",
+        Msg::QueryingDevice => "Querying device...",
+        Msg::QueryDeviceFailed => "Failed to query device: {err}",
+        Msg::AndroidVersion => "Android version",
+        Msg::SecurityPatch => "Security patch level",
+        Msg::InstalledApps => "Installed apps",
+        Msg::InstalledAppsCount => "{total} ({system} system, {user} installed by the user)",
+        Msg::DeviceAdmins => "Device admins",
+        Msg::WirelessDebugging => "Wireless debugging",
+        Msg::Enabled => "enabled",
+        Msg::DisabledSetting => "disabled",
+        Msg::ToStartScan => " to start the scan, ",
+        Msg::ToExportProfile => " to export the device profile",
+        Msg::SelectRuleFiles => "Select rule files",
+        Msg::RuleStatistics => "Rule statistics",
+        Msg::LoadRulesFailed => "Failed to load rules: {err}",
+        Msg::Rules => "Rules",
+        Msg::PackageNames => "Package names (high)",
+        Msg::Certificates => "Certificates",
+        Msg::Websites => "Websites",
+        Msg::C2Domains => "C2 domains",
+        Msg::C2Ips => "C2 ips",
+        Msg::ConnectedDevices => "Connected devices",
+        Msg::EnumeratingDevices => "Enumerating devices...",
+        Msg::Released => "released:",
+        Msg::UpdateAvailable => "(database update available, press ctrl+R)",
+        Msg::StepConnect => "Step 1 of 4: Connect the device",
+        Msg::WaitingForDevice => "Waiting for a device...",
+        Msg::MultipleDevices => "More than one device is connected:",
+        Msg::PickDevice => "Type the number of the device to scan: ",
+        Msg::NoSuchDevice => "There's no device with number {answer}",
+        Msg::FoundDevice => "Found device: {device}",
+        Msg::StepConsent => "Step 2 of 4: Confirm you are authorized to scan",
+        Msg::StepScan => "Step 3 of 4: Scan the device",
+        Msg::ScanTakesMinutes => "This takes a few minutes, keep the phone connected and unlocked.",
+        Msg::ScanComplete => "The scan is complete.",
+        Msg::StepReview => "Step 4 of 4: Review the findings",
+        Msg::NothingFound => "Nothing suspicious was found. This doesn't guarantee the phone is safe.",
+        Msg::PressEnter => "Press Enter to continue ",
+        Msg::GuideWarnings => "Some parts of the phone could not be checked ({n} warnings), the expert view has details.",
+    }
+}
+
+fn fr(msg: Msg) -> Option<&'static str> {
+    Some(match msg {
+        Msg::ConsentTitle => "Autorisation requise",
+        Msg::Consent => "\
+Analyser un appareil donne accès aux informations privées de son propriétaire.
+
+Ne continuez que si le propriétaire de l'appareil a demandé cette analyse, ou si vous êtes autorisé à l'inspecter d'une autre manière.",
+        Msg::ConsentKey => "Appuyez sur y pour confirmer que vous êtes autorisé à analyser, toute autre touche quitte sans se connecter à un appareil.",
+        Msg::ConsentLine => "Tapez y puis Entrée pour confirmer que vous êtes autorisé à analyser, toute autre saisie quitte sans se connecter à un appareil : ",
+        Msg::Scanning => " analyse en cours",
+        Msg::Downloading => " téléchargement - ",
+        Msg::Idle => "inactif - ",
+        Msg::Press => "Appuyez sur ",
+        Msg::ToToggle => " pour cocher, ",
+        Msg::ToContinue => " pour continuer - ",
+        Msg::ToRerunChecks => " pour relancer {n} vérifications avec avertissements - ",
+        Msg::ToHideLegend => " pour masquer la légende - ",
+        Msg::ForLegend => " pour la légende - ",
+        Msg::ToGoBack => " pour revenir - ",
+        Msg::ToReturnToReport => " pour revenir au rapport - ",
+        Msg::ForRuleStatistics => " pour les statistiques des règles, ",
+        Msg::ToEditRules => " pour modifier les règles - ",
+        Msg::ToExit => " pour quitter - ",
+        Msg::ToSelect => " pour choisir, ",
+        Msg::ToSave => " pour enregistrer, ",
+        Msg::ToConfirm => " pour confirmer, ",
+        Msg::ToCancel => " pour annuler",
+        Msg::NoChecksToRerun => "Aucune vérification avec avertissements à relancer",
+        Msg::NoReportToSwitchTo => "Il n'y a pas d'autre rapport",
+        Msg::SelectRuleFile => "Choisissez au moins un fichier de règles",
+        Msg::ToSkipApps => " pour ignorer les applications, ",
+        Msg::ToScanApps => " pour analyser les applications, ",
+        Msg::ToUpdateRules => " pour mettre à jour les règles, ",
+        Msg::NoRulesUrl => {
+            "Définissez rules-url dans la configuration pour mettre à jour les règles"
+        }
+        Msg::AppsSkipped => "applications : ignorées",
+        Msg::AppsScanned => "applications : analysées",
+        Msg::ScanRunning => {
+            "Attendez la fin de l'analyse en cours avant de changer ses paramètres"
+        }
+        Msg::ConnectHelp => "\
+Branchez le téléphone avec un câble usb et déverrouillez-le. S'il n'est pas détecté :
+
+  1. Ouvrez Paramètres, À propos du téléphone, et touchez sept fois \"Numéro de build\"
+  2. Ouvrez Paramètres, Options pour les développeurs, et activez \"Débogage USB\"
+  3. Acceptez la demande \"Autoriser le débogage USB ?\" sur le téléphone
+",
+        Msg::ClosingAdvice => "\
+Avant de modifier quoi que ce soit sur le téléphone, parlez avec son propriétaire de sa propre sécurité.
+Supprimer une application ou changer un paramètre peut alerter la personne qui l'a installé.
+Désactivez à nouveau le débogage USB une fois terminé.",
+        Msg::FindingOf => "Résultat {n} sur {total} ({level})",
+        Msg::FindingApp => "Application : {app}",
+        Msg::WhatThisMeans => "Ce que cela signifie : ",
+        Msg::WhatToDo => "Que faire : ",
+        Msg::MeaningRule => {
+            "Cela correspond à {rule}, un logiciel espion ou de surveillance connu."
+        }
+        Msg::MeaningHigh => {
+            "C'est un signe fort que quelqu'un d'autre peut surveiller ou contrôler le téléphone."
+        }
+        Msg::MeaningMedium => {
+            "Cela peut servir à surveiller le téléphone, demandez au propriétaire s'il l'a mis en place."
+        }
+        Msg::MeaningLow => "C'est inhabituel, mais souvent sans danger en soi.",
+        Msg::MeaningNone => "Ce n'est pas suspect en soi.",
+        Msg::NextStepSevere => {
+            "Notez-le et parlez-en avec le propriétaire avant de supprimer ou de modifier quoi que ce soit."
+        }
+        Msg::NextStepOther => {
+            "Demandez au propriétaire s'il le reconnaît, rien n'est à faire si c'est le cas."
+        }
+        Msg::ConsentConfirmed => "L'autorisation d'analyser a été confirmée le {time}",
+        Msg::Exiting => "Fermeture...",
+        Msg::MaskingSerials => "Les numéros de série et noms de comptes sont masqués à l'écran",
+        Msg::ShowingSerials => "Les numéros de série et noms de comptes sont affichés à l'écran",
+        Msg::GroupingFindings => "Résultats groupés par gravité et type, ENTRÉE replie un groupe",
+        Msg::ListingFindings => "Résultats listés par application",
+        Msg::FollowingFindings => "Suivi des nouveaux résultats",
+        Msg::StoppedFollowing => "Les nouveaux résultats ne sont plus suivis",
+        Msg::NoHighBelow => "Plus de résultat de gravité élevée en dessous",
+        Msg::NoHighAbove => "Plus de résultat de gravité élevée au-dessus",
+        Msg::ReportWritten => "Rapport enregistré dans {path}",
+        Msg::ExportFailed => "Échec de l'export du rapport : {err}",
+        Msg::SaveFailed => "Échec de l'enregistrement du rapport : {err}",
+        Msg::ReportCopied => "Rapport avec {count} résultats copié dans le presse-papiers",
+        Msg::CopyFailed => "Échec de la copie du rapport : {err}",
+        Msg::HistoryFailed => "Échec de l'enregistrement de l'historique : {err}",
+        Msg::BundleWritten => "Archive de preuves avec {count} fichiers collectés enregistrée dans {path}",
+        Msg::BundleFailed => "Échec de l'écriture de l'archive de preuves : {err}",
+        Msg::ProfileWritten => "Profil de l'appareil enregistré dans {path}",
+        Msg::ProfileFailed => "Échec de l'export du profil de l'appareil : {err}",
+        Msg::SessionWritten => "Session enregistrée dans {path}, ouvrez-la avec --session",
+        Msg::SessionFailed => "Échec de l'enregistrement de la session : {err}",
+        Msg::SessionOpened => "Session de {serial} ouverte",
+        Msg::RulesLoaded => "Règles chargées depuis {path} (sha256={sha256})",
+        Msg::DownloadingRules => "Téléchargement des règles depuis {url}...",
+        Msg::RulesUpdated => "Règles mises à jour depuis {url} (sha256={sha256})",
+        Msg::RulesUpdateFailed => "Échec de la mise à jour des règles : {err}",
+        Msg::RulesUpdateRunning => "Les règles sont déjà en cours de mise à jour",
+        Msg::RerunningChecks => "Nouvelle exécution des vérifications : {checks}",
+        Msg::CapturingBugreport => "Capture du rapport de bug, cela peut prendre quelques minutes...",
+        Msg::NoBugreport => "Pas de capture du rapport de bug",
+        Msg::BugreportWritten => "Rapport de bug enregistré dans {path}",
+        Msg::BugreportFailed => "Échec de la capture du rapport de bug : {err}",
+        Msg::ConfirmBugreport => "Capturer un rapport de bug de {serial} dans {path} ? Il contient des journaux système détaillés et peut dépasser 100 Mo - continuer ? y/N",
+        Msg::ConfirmUninstall => "Cela va désinstaller {package} de {serial}, ce n'est pas réversible - continuer ? y/N",
+        Msg::ConfirmDisable => "Cela va désactiver {package} sur {serial} jusqu'à ce qu'elle soit réactivée - continuer ? y/N",
+        Msg::NotUninstalling => "{package} ne sera pas désinstallée",
+        Msg::NotDisabling => "{package} ne sera pas désactivée",
+        Msg::ReadOnlyUninstall => "Mode lecture seule, relancez avec --allow-remediation pour désinstaller des applications",
+        Msg::ReadOnlyDisable => "Mode lecture seule, relancez avec --allow-remediation pour désactiver des applications",
+        Msg::Uninstalled => "L'application {package} a été désinstallée",
+        Msg::Disabled => "L'application {package} a été désactivée",
+        Msg::Uninstalling => "Désinstallation de {package}...",
+        Msg::Disabling => "Désactivation de {package}...",
+        Msg::ExportFormat => "Format d'export : ",
+        Msg::SaveReportTo => "Enregistrer le rapport {format} dans : ",
+        Msg::NotesPrompt => "Notes : ",
+        Msg::ScanFailed => "L'analyse a échoué",
+        Msg::Findings => "Résultats",
+        Msg::VisibleRange => "[{first}-{last} sur {total}]",
+        Msg::HiddenByFilter => "{n} masqués par le filtre (f)",
+        Msg::FollowingTitle => "suivi des nouveaux résultats (F)",
+        Msg::NotesTitle => "Notes : {notes}",
+        Msg::WarningsTitle => "{n} avertissements, l'analyse peut être incomplète",
+        Msg::ScanCancelled => "Analyse annulée - résultats partiels",
+        Msg::Took => "durée {duration}",
+        Msg::AppRow => "Application {name}",
+        Msg::WarningTag => "[avertissement]",
+        Msg::Scanned => "Analysé {serial}",
+        Msg::NoFindings => "aucun résultat",
+        Msg::SummaryPartial => "l'analyse n'est pas terminée, résultats partiels",
+        Msg::SummaryIncomplete => "l'analyse peut être incomplète, {n} avertissements",
+        Msg::Saved => "Enregistré {path}",
+        Msg::LegendSeverity => "Gravité : ",
+        Msg::LegendMarkers => "{indicator} correspond à un indicateur connu  {advisory} indicatif",
+        Msg::LevelHigh => "élevée",
+        Msg::LevelMedium => "moyenne",
+        Msg::LevelLow => "faible",
+        Msg::LevelInfo => "info",
+        Msg::LevelGood => "bon",
+        Msg::KindPackage => "application",
+        Msg::KindFile => "fichier",
+        Msg::KindNetwork => "réseau",
+        Msg::KindSetting => "paramètre",
+        Msg::KindOther => "autre",
+        Msg::DeviceOverview => "Aperçu de l'appareil",
+        Msg::Device => "Appareil",
+        Msg::QueryingDevice => "Interrogation de l'appareil...",
+        Msg::QueryDeviceFailed => "Échec de l'interrogation de l'appareil : {err}",
+        Msg::AndroidVersion => "Version d'Android",
+        Msg::SecurityPatch => "Correctif de sécurité",
+        Msg::InstalledApps => "Applications installées",
+        Msg::InstalledAppsCount => "{total} ({system} système, {user} installées par l'utilisateur)",
+        Msg::DeviceAdmins => "Administrateurs de l'appareil",
+        Msg::WirelessDebugging => "Débogage sans fil",
+        Msg::Enabled => "activé",
+        Msg::DisabledSetting => "désactivé",
+        Msg::ToStartScan => " pour lancer l'analyse, ",
+        Msg::ToExportProfile => " pour exporter le profil de l'appareil",
+        Msg::SelectRuleFiles => "Choisissez les fichiers de règles",
+        Msg::RuleStatistics => "Statistiques des règles",
+        Msg::LoadRulesFailed => "Échec du chargement des règles : {err}",
+        Msg::Rules => "Règles",
+        Msg::PackageNames => "Noms d'applications (élevée)",
+        Msg::Certificates => "Certificats",
+        Msg::Websites => "Sites web",
+        Msg::C2Domains => "Domaines C2",
+        Msg::C2Ips => "Adresses ip C2",
+        Msg::ConnectedDevices => "Appareils connectés",
+        Msg::EnumeratingDevices => "Recherche des appareils...",
+        Msg::Released => "publiée :",
+        Msg::UpdateAvailable => "(mise à jour de la base disponible, appuyez sur ctrl+R)",
+        Msg::StepConnect => "Étape 1 sur 4 : Branchez l'appareil",
+        Msg::WaitingForDevice => "En attente d'un appareil...",
+        Msg::MultipleDevices => "Plusieurs appareils sont branchés :",
+        Msg::PickDevice => "Tapez le numéro de l'appareil à analyser : ",
+        Msg::NoSuchDevice => "Il n'y a pas d'appareil avec le numéro {answer}",
+        Msg::FoundDevice => "Appareil trouvé : {device}",
+        Msg::StepConsent => "Étape 2 sur 4 : Confirmez que vous êtes autorisé à analyser",
+        Msg::StepScan => "Étape 3 sur 4 : Analysez l'appareil",
+        Msg::ScanTakesMinutes => "Cela prend quelques minutes, gardez le téléphone branché et déverrouillé.",
+        Msg::ScanComplete => "L'analyse est terminée.",
+        Msg::StepReview => "Étape 4 sur 4 : Examinez les résultats",
+        Msg::NothingFound => "Rien de suspect n'a été trouvé. Cela ne garantit pas que le téléphone est sûr.",
+        Msg::PressEnter => "Appuyez sur Entrée pour continuer ",
+        Msg::GuideWarnings => "Certaines parties du téléphone n'ont pas pu être vérifiées ({n} avertissements), la vue experte donne les détails.",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(s: &str) -> Vec<&str> {
+        let mut out = s
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect::<Vec<_>>();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_translations() {
+        for msg in Msg::ALL {
+            let en = Lang::En.get(*msg);
+            assert!(!en.is_empty(), "{msg:?} has no english text");
+            for lang in Lang::value_variants() {
+                assert_eq!(
+                    placeholders(lang.get(*msg)),
+                    placeholders(en),
+                    "{msg:?} has different placeholders in {lang:?}"
+                );
+            }
+        }
+        assert_eq!(
+            Lang::Fr.format(Msg::ToRerunChecks, &[("n", "2")]),
+            " pour relancer 2 vérifications avec avertissements - "
+        );
+    }
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Lang::from_locale("fr_FR.UTF-8"), Some(Lang::Fr));
+        assert_eq!(Lang::from_locale("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::from_locale("C.UTF-8"), None);
+        assert_eq!(Lang::from_locale("de_DE"), None);
+    }
+}
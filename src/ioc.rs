@@ -6,8 +6,11 @@ use indexmap::IndexMap;
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
 use tokio::fs;
 
 // query the latest commit to detect if we need to update
@@ -18,39 +21,235 @@ const IOC_DOWNLOAD_URL: &str =
     "https://github.com/AssoEchap/stalkerware-indicators/raw/{{commit}}/{{filename}}";
 const IOC_DB_FILES: &[&str] = &["ioc.yaml", "watchware.yaml"];
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct Suspicion {
     pub level: SuspicionLevel,
     pub description: String,
+    #[serde(default)]
+    pub kind: Kind,
+    /// What to do about it, provided by the rule that matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+    /// The rule of a confirmed indicator, `None` for heuristic advisories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// How certain the rule is about the match, independent of its level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+    /// The secondary user or work profile of the app, `None` for the primary user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<u32>,
 }
 
 impl Suspicion {
-    pub fn to_terminal(&self) -> Vec<Span> {
-        vec![
+    pub fn new(level: SuspicionLevel, kind: Kind, description: impl Into<String>) -> Self {
+        Suspicion {
+            level,
+            description: description.into(),
+            kind,
+            remediation: None,
+            rule: None,
+            confidence: None,
+            user: None,
+        }
+    }
+
+    /// Mark this as a match of the given rule
+    pub fn with_rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+
+    pub fn with_remediation(mut self, remediation: Option<String>) -> Self {
+        self.remediation = remediation;
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: Option<Confidence>) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn to_terminal(&self) -> Vec<Span<'_>> {
+        let description = if self.is_ioc() {
+            Span::raw(&self.description)
+        } else {
             Span::styled(
-                match self.level {
-                    SuspicionLevel::High => "high",
-                    SuspicionLevel::Medium => "medium",
-                    SuspicionLevel::Low => "low",
-                    SuspicionLevel::Good => "good",
-                },
-                self.level.terminal_color(),
-            ),
+                &self.description,
+                Style::default().add_modifier(Modifier::DIM),
+            )
+        };
+        let mut spans = vec![
+            Span::styled(self.level.as_str(), self.level.terminal_color()),
             Span::raw(": "),
-            Span::raw(&self.description),
-        ]
+            description,
+        ];
+        if let Some(note) = self.confidence_note() {
+            spans.push(Span::styled(
+                note,
+                Style::default().add_modifier(Modifier::ITALIC),
+            ));
+        }
+        spans
+    }
+
+    /// Appended to the description of findings that aren't certain, eg. ` (low confidence)`, or ` (advisory)` for heuristics
+    pub fn confidence_note(&self) -> Option<String> {
+        if !self.is_ioc() {
+            return Some(" (advisory)".to_string());
+        }
+        match self.confidence? {
+            Confidence::High => None,
+            confidence => Some(format!(" ({} confidence)", confidence.as_str())),
+        }
+    }
+
+    /// Whether this is a match of a known indicator, instead of a heuristic advisory
+    pub fn is_ioc(&self) -> bool {
+        self.rule.is_some()
+    }
+
+    /// A solid marker for confirmed indicators and a hollow one for advisories
+    pub fn marker(&self, unicode: bool) -> &'static str {
+        Self::marker_for(self.is_ioc(), unicode)
     }
+
+    pub fn marker_for(ioc: bool, unicode: bool) -> &'static str {
+        match (ioc, unicode) {
+            (true, true) => "●",
+            (false, true) => "○",
+            (true, false) => "[ioc]",
+            (false, false) => "[adv]",
+        }
+    }
+
+    /// Raise the level of a rule match to the minimum of its rule, advisories and matches below low are left as they are
+    pub fn escalate(mut self, escalations: &HashMap<String, SuspicionLevel>) -> Self {
+        let min = self.rule.as_ref().and_then(|rule| escalations.get(rule));
+        if let Some(min) = min {
+            if self.level >= SuspicionLevel::Low && self.level < *min {
+                self.level = *min;
+            }
+        }
+        self
+    }
+}
+
+/// What part of the device a finding is about
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Package,
+    File,
+    Network,
+    Setting,
+    #[default]
+    Other,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+impl Kind {
+    pub const ALL: &'static [Kind] = &[
+        Kind::Package,
+        Kind::File,
+        Kind::Network,
+        Kind::Setting,
+        Kind::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Package => "package",
+            Kind::File => "file",
+            Kind::Network => "network",
+            Kind::Setting => "setting",
+            Kind::Other => "other",
+        }
+    }
+
+    /// A single-width glyph, or a fixed-width ascii tag if the terminal can't display unicode
+    pub fn symbol(&self, unicode: bool) -> &'static str {
+        match (self, unicode) {
+            (Kind::Package, true) => "▣",
+            (Kind::File, true) => "▤",
+            (Kind::Network, true) => "⇄",
+            (Kind::Setting, true) => "⚙",
+            (Kind::Other, true) => "•",
+            (Kind::Package, false) => "[pkg]",
+            (Kind::File, false) => "[fil]",
+            (Kind::Network, false) => "[net]",
+            (Kind::Setting, false) => "[set]",
+            (Kind::Other, false) => "[---]",
+        }
+    }
+}
+
+/// How likely a match of a rule is a true positive, set by the author of the rule
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Confidence::High => "high",
+            Confidence::Medium => "medium",
+            Confidence::Low => "low",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SuspicionLevel {
     Good,
+    /// Not suspicious on its own, but worth reviewing
+    Info,
     Low,
     Medium,
     High,
 }
 
+impl FromStr for SuspicionLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "high" => Ok(SuspicionLevel::High),
+            "medium" => Ok(SuspicionLevel::Medium),
+            "low" => Ok(SuspicionLevel::Low),
+            "info" => Ok(SuspicionLevel::Info),
+            "good" => Ok(SuspicionLevel::Good),
+            _ => bail!("Unknown severity level: {s:?}"),
+        }
+    }
+}
+
 impl SuspicionLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuspicionLevel::High => "high",
+            SuspicionLevel::Medium => "medium",
+            SuspicionLevel::Low => "low",
+            SuspicionLevel::Info => "info",
+            SuspicionLevel::Good => "good",
+        }
+    }
+
+    /// A marker that conveys the level without relying on color
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SuspicionLevel::High => "[!!!]",
+            SuspicionLevel::Medium => "[!!] ",
+            SuspicionLevel::Low => "[!]  ",
+            SuspicionLevel::Info => "[i]  ",
+            SuspicionLevel::Good => "[ok] ",
+        }
+    }
+
     pub fn terminal_color(&self) -> Style {
         match self {
             SuspicionLevel::High => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
@@ -58,6 +257,7 @@ impl SuspicionLevel {
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
             SuspicionLevel::Low => Style::default().add_modifier(Modifier::BOLD),
+            SuspicionLevel::Info => Style::default(),
             SuspicionLevel::Good => Style::default()
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
@@ -75,6 +275,17 @@ pub struct RepositoryContent {
     pub files: IndexMap<String, String>,
 }
 
+/// A file that rules can be loaded from
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleFile {
+    pub name: String,
+    pub sha256: String,
+    /// release date of the repository, or modification time of custom files
+    pub date: i64,
+    pub data: String,
+    pub custom: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Repository {
     pub path: PathBuf,
@@ -180,6 +391,129 @@ impl Repository {
         Ok(())
     }
 
+    /// File that records every modification of a device that has been requested
+    pub fn audit_log_path(&self) -> PathBuf {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        dir.join("audit.log")
+    }
+
+    /// File with the progress of an interrupted scan
+    pub fn checkpoint_path(&self) -> PathBuf {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        dir.join("checkpoint.json")
+    }
+
+    /// File with the device fingerprints recorded during previous scans
+    pub fn fingerprints_path(&self) -> PathBuf {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        dir.join("fingerprints.json")
+    }
+
+    /// Directory with the findings of past scans
+    pub fn history_path(&self) -> PathBuf {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        dir.join("history")
+    }
+
+    /// Directory for additional rule files maintained by the user
+    pub fn custom_rules_path(&self) -> PathBuf {
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        dir.join("rules")
+    }
+
+    /// Custom rules that are edited from within the tui
+    pub fn local_rules_path(&self) -> PathBuf {
+        self.custom_rules_path().join("local.yaml")
+    }
+
+    /// Custom rules that are downloaded with `update_from_url`
+    pub fn remote_rules_path(&self) -> PathBuf {
+        self.custom_rules_path().join("remote.yaml")
+    }
+
+    /// Download a rules file and replace the remote rules with it, returns the sha256 of the new rules
+    pub async fn update_from_url(&self, url: &str) -> Result<String> {
+        info!("Downloading rules from {url:?}...");
+        let buf = self
+            .client
+            .get(url)
+            .await?
+            .bytes()
+            .await
+            .context("Failed to download HTTP response")?;
+        let sha256 = install_rules(&self.remote_rules_path(), &buf)
+            .await
+            .with_context(|| anyhow!("Rules downloaded from {url:?} were not installed"))?;
+        Ok(sha256)
+    }
+
+    /// All rule files that are available, the downloaded ones first
+    pub async fn rule_files(&self) -> Result<Vec<RuleFile>> {
+        let mut files = Vec::new();
+        if let Some(content) = &self.content {
+            for (name, data) in &content.files {
+                files.push(RuleFile {
+                    name: name.to_string(),
+                    sha256: utils::sha256(data.as_bytes()),
+                    date: content.released,
+                    data: data.to_string(),
+                    custom: false,
+                });
+            }
+        }
+
+        let dir = self.custom_rules_path();
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(files),
+            Err(err) => {
+                return Err(err).with_context(|| anyhow!("Failed to list rules in {dir:?}"))
+            }
+        };
+        let mut custom = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if !rules::is_rule_file(&path) {
+                continue;
+            }
+            let data = fs::read_to_string(&path)
+                .await
+                .with_context(|| anyhow!("Failed to read rules from file: {path:?}"))?;
+            let date = entry
+                .metadata()
+                .await?
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default();
+            custom.push(RuleFile {
+                name: path.to_string_lossy().into_owned(),
+                sha256: utils::sha256(data.as_bytes()),
+                date,
+                data,
+                custom: true,
+            });
+        }
+        custom.sort_by(|a, b| a.name.cmp(&b.name));
+        files.extend(custom);
+
+        Ok(files)
+    }
+
+    /// Load the rule files with the given names, or the downloaded ones if `None`
+    pub async fn load_rules(&self, selected: Option<&[String]>) -> Result<rules::Rules> {
+        let Some(selected) = selected else {
+            return self.parse_rules();
+        };
+        let mut rules = rules::Rules::default();
+        for file in self.rule_files().await? {
+            if selected.contains(&file.name) {
+                rules.load_yaml(&file.name, file.data.as_bytes())?;
+            }
+        }
+        Ok(rules)
+    }
+
     pub fn parse_rules(&self) -> Result<rules::Rules> {
         let content = self
             .content
@@ -192,3 +526,166 @@ impl Repository {
         Ok(rules)
     }
 }
+
+/// Validate the rules and atomically replace the file with them, an invalid file is never written
+pub async fn install_rules(path: &Path, buf: &[u8]) -> Result<String> {
+    let name = path.to_string_lossy();
+    let mut rules = rules::Rules::default();
+    let sha256 = rules.load_yaml(&name, buf).context("Rules are invalid")?;
+    if rules.sources().iter().all(|source| source.rules() == 0) {
+        bail!("Rules file doesn't contain any rules");
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| anyhow!("Failed to create directory at {parent:?}"))?;
+    }
+    // not a rule file, so it's never loaded while it's incomplete
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, buf)
+        .await
+        .with_context(|| anyhow!("Failed to write rules to {tmp:?}"))?;
+    fs::rename(&tmp, path)
+        .await
+        .with_context(|| anyhow!("Failed to replace rules at {path:?}"))?;
+    Ok(sha256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        for level in [
+            SuspicionLevel::High,
+            SuspicionLevel::Medium,
+            SuspicionLevel::Low,
+            SuspicionLevel::Info,
+            SuspicionLevel::Good,
+        ] {
+            assert_eq!(level.as_str().parse::<SuspicionLevel>().unwrap(), level);
+        }
+        assert!("critical".parse::<SuspicionLevel>().is_err());
+    }
+
+    #[test]
+    fn test_kind() {
+        // findings recorded before kinds existed
+        let sus = serde_json::from_str::<Suspicion>(
+            r#"{"level":"high","description":"Found known stalkerware"}"#,
+        )
+        .unwrap();
+        assert_eq!(sus.kind, Kind::Other);
+        assert_eq!(Kind::Network.symbol(true), "⇄");
+        assert_eq!(Kind::Network.symbol(false), "[net]");
+    }
+
+    #[test]
+    fn test_escalate() {
+        let escalations = HashMap::from([("TheTruthSpy".to_string(), SuspicionLevel::High)]);
+        let sus = |level, rule: Option<&str>| {
+            let sus = Suspicion::new(level, Kind::Network, "Connected to a known c2 server");
+            match rule {
+                Some(rule) => sus.with_rule(rule),
+                None => sus,
+            }
+        };
+        assert_eq!(
+            sus(SuspicionLevel::Low, Some("TheTruthSpy")).escalate(&escalations),
+            sus(SuspicionLevel::High, Some("TheTruthSpy"))
+        );
+        assert_eq!(
+            sus(SuspicionLevel::Low, Some("SpyLive360")).escalate(&escalations),
+            sus(SuspicionLevel::Low, Some("SpyLive360"))
+        );
+        assert_eq!(
+            sus(SuspicionLevel::Low, None).escalate(&escalations),
+            sus(SuspicionLevel::Low, None)
+        );
+        assert_eq!(
+            sus(SuspicionLevel::Info, Some("TheTruthSpy")).escalate(&escalations),
+            sus(SuspicionLevel::Info, Some("TheTruthSpy"))
+        );
+    }
+
+    #[test]
+    fn test_marker() {
+        let mut sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware",
+        );
+        assert!(!sus.is_ioc());
+        assert_eq!(sus.marker(true), "○");
+        assert_eq!(sus.marker(false), "[adv]");
+        sus.rule = Some("SpyLive360".to_string());
+        assert!(sus.is_ioc());
+        assert_eq!(sus.marker(true), "●");
+        assert_eq!(sus.marker(false), "[ioc]");
+    }
+
+    #[tokio::test]
+    async fn test_custom_rule_files() {
+        let dir = std::env::temp_dir().join(format!("spytrap-rules-{}", std::process::id()));
+        let mut repo = Repository::init_at(&dir).await.unwrap();
+        repo.content = Some(RepositoryContent {
+            last_update_check: 0,
+            update_available: false,
+            released: 1719963254,
+            git_commit: "0000".to_string(),
+            files: IndexMap::from([(
+                "ioc.yaml".to_string(),
+                "- name: Default\n  type: stalkerware\n  packages:\n  - com.wifi0\n".to_string(),
+            )]),
+        });
+        std::fs::create_dir_all(repo.custom_rules_path()).unwrap();
+        let custom = repo.custom_rules_path().join("custom.yaml");
+        std::fs::write(
+            &custom,
+            "- name: Custom\n  type: stalkerware\n  packages:\n  - com.example\n",
+        )
+        .unwrap();
+        std::fs::write(repo.custom_rules_path().join("README"), "").unwrap();
+
+        let files = repo.rule_files().await.unwrap();
+        let names = files
+            .iter()
+            .map(|file| (file.name.as_str(), file.custom))
+            .collect::<Vec<_>>();
+        let custom = custom.to_string_lossy();
+        assert_eq!(names, &[("ioc.yaml", false), (custom.as_ref(), true)]);
+
+        let rules = repo.load_rules(Some(&[custom.to_string()])).await.unwrap();
+        assert_eq!(rules.get("com.example").map(String::as_str), Some("Custom"));
+        assert_eq!(rules.get("com.wifi0"), None);
+
+        let rules = repo.load_rules(None).await.unwrap();
+        assert_eq!(rules.get("com.wifi0").map(String::as_str), Some("Default"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_rules() {
+        let dir = std::env::temp_dir().join(format!("spytrap-remote-{}", std::process::id()));
+        let path = dir.join("rules").join("remote.yaml");
+        let valid = b"- name: Remote\n  type: stalkerware\n  packages:\n  - com.example\n";
+
+        let sha256 = install_rules(&path, valid).await.unwrap();
+        assert_eq!(sha256, utils::sha256(valid));
+        assert_eq!(std::fs::read(&path).unwrap(), valid);
+
+        // a broken download keeps the previous rules
+        assert!(install_rules(&path, b"<html>Not Found</html>")
+            .await
+            .is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), valid);
+        assert!(install_rules(&path, b"[]\n").await.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), valid);
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,15 +1,64 @@
+//! Scan android devices for stalkerware over adb
+//!
+//! The scan can be used without the terminal interface:
+//!
+//! ```no_run
+//! # async fn example() -> spytrap_adb::errors::Result<()> {
+//! use spytrap_adb::{rules::Rules, scan};
+//!
+//! let mut rules = Rules::default();
+//! rules.load_yaml("ioc.yaml", &std::fs::read("ioc.yaml")?)?;
+//! let host = forensic_adb::Host::default();
+//! let findings = scan::collect(&host, None, &rules, &scan::Settings::default()).await?;
+//! for sus in findings.iter() {
+//!     println!("[{}] {}", sus.level.as_str(), sus.description);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Use [`scan::stream`] to receive every finding as soon as it's known.
 pub mod accessibility;
+pub mod adb;
+pub mod adb_keys;
 pub mod args;
+pub mod bugreport;
+pub mod bundle;
+pub mod checkpoint;
+pub mod checks;
+pub mod clipboard;
+pub mod compare;
+pub mod config;
+pub mod dashboard;
+pub mod device_policy;
 pub mod dumpsys;
 pub mod errors;
+pub mod export;
+pub mod files;
+pub mod fingerprint;
+pub mod guide;
+pub mod history;
 pub mod http;
+pub mod i18n;
 pub mod ioc;
+pub mod network;
+pub mod offline;
 pub mod package;
 pub mod parsers;
 pub mod pm;
+pub mod probes;
+pub mod progress;
+pub mod remediate;
 pub mod remote_clock;
+pub mod risk;
 pub mod rules;
 pub mod scan;
+pub mod selftest;
+pub mod session;
 pub mod settings;
+pub mod shell;
+pub mod stix;
+pub mod syslog;
 pub mod tui;
 pub mod utils;
+pub mod watch;
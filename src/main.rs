@@ -1,17 +1,39 @@
 use clap::Parser;
 use env_logger::Env;
 use forensic_adb::{AndroidStorageInput, Host};
+use spytrap_adb::adb;
 use spytrap_adb::args::{self, Args, SubCommand};
+use spytrap_adb::checkpoint::Checkpoint;
+use spytrap_adb::checks;
+use spytrap_adb::compare;
+use spytrap_adb::config;
+use spytrap_adb::dashboard::{self, Profile};
 use spytrap_adb::errors::*;
+use spytrap_adb::export;
+use spytrap_adb::guide;
+use spytrap_adb::history;
+use spytrap_adb::i18n::{Lang, Msg};
 use spytrap_adb::ioc;
+use spytrap_adb::offline::OfflineShell;
+use spytrap_adb::progress::{self, JsonProgress};
 use spytrap_adb::rules;
 use spytrap_adb::scan;
+use spytrap_adb::selftest;
+use spytrap_adb::session;
+use spytrap_adb::shell::Shell;
+use spytrap_adb::syslog::Syslog;
 use spytrap_adb::tui;
 use spytrap_adb::utils;
-use tokio::fs;
+use spytrap_adb::watch;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use tokio::process::Command;
 
-async fn ensure_adb_running(choice: &args::AdbServerChoice) -> Result<()> {
+/// Start the adb server if needed, returns a warning if its version is unknown or incompatible
+async fn ensure_adb_running(
+    adb_host: &Host,
+    choice: &args::AdbServerChoice,
+) -> Result<Option<String>> {
     if *choice != args::AdbServerChoice::Never {
         debug!("Making sure adb server is running...");
         let status = Command::new("adb")
@@ -24,14 +46,45 @@ async fn ensure_adb_running(choice: &args::AdbServerChoice) -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(adb::check_server(adb_host).await)
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+/// Load the given rule files, or the latest downloaded ones if there are none
+async fn load_rules(repo: &ioc::Repository, paths: &[PathBuf]) -> Result<rules::Rules> {
+    if paths.is_empty() {
+        return Ok(repo.parse_rules().map_err(ScanError::Rules)?);
+    }
+    let mut rules = rules::Rules::default();
+    for path in paths {
+        rules.load_path(path).await.map_err(ScanError::Rules)?;
+    }
+    Ok(rules)
+}
+
+async fn load_config(path: Option<&PathBuf>) -> Result<config::Config> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => config::default_path()?,
+    };
+    config::load(&path).await
+}
+
+/// Subcommands that connect to a device ask for authorization first, if the config or `--require-consent` requires it
+async fn require_consent(config: Option<&PathBuf>, required: bool, lang: Lang) -> Result<()> {
+    let config = load_config(config).await?;
+    if !required && !config.require_consent {
+        return Ok(());
+    }
+    if !guide::ask_consent(lang).await? {
+        bail!("Authorization to scan has not been confirmed");
+    }
+    let time = utils::format_datetime(utils::now());
+    info!("{}", lang.format(Msg::ConsentConfirmed, &[("time", &time)]));
+    Ok(())
+}
 
-    if args.subcommand.is_some() {
+async fn run(args: Args) -> Result<()> {
+    if args.subcommand.is_some() || args.self_test {
         let logging = match args.verbose {
             0 => "info",
             1 => "spytrap_adb=debug,info",
@@ -42,62 +95,155 @@ async fn main() -> Result<()> {
         env_logger::init_from_env(Env::default().default_filter_or(logging));
     }
 
+    if args.self_test {
+        return selftest::run().await;
+    }
+
+    if args.list_checks {
+        let manifest = serde_json::to_string_pretty(&checks::manifest())?;
+        println!("{manifest}");
+        return Ok(());
+    }
+
     let adb_host = Host::default();
+    let lang = args.lang.unwrap_or_else(Lang::from_env);
 
     match args.subcommand {
         Some(SubCommand::Scan(scan)) => {
-            ensure_adb_running(&args.start_adb_server).await?;
+            if scan.offline.is_none() {
+                require_consent(args.config.as_ref(), args.require_consent, lang).await?;
+                ensure_adb_running(&adb_host, &args.start_adb_server).await?;
+            }
 
-            let rules = if scan.rules.is_empty() {
-                let repo = ioc::Repository::init().await?;
-                repo.parse_rules()?
-            } else {
-                let mut rules = rules::Rules::default();
-                for path in &scan.rules {
-                    let buf = fs::read(&path)
-                        .await
-                        .with_context(|| anyhow!("Failed to read rules from file: {path:?}"))?;
-                    rules.load_yaml(path, &buf)?;
-                }
-                rules
-            };
+            let repo = ioc::Repository::init().await?;
+            let rules = load_rules(&repo, &scan.rules).await?;
 
             if scan.test_load_only {
                 info!("Rules loaded successfully");
                 return Ok(());
             }
 
-            let device = adb_host
-                .device_or_default(scan.serial.as_ref(), AndroidStorageInput::Auto)
-                .await
-                .with_context(|| anyhow!("Failed to access device: {:?}", scan.serial))?;
+            let config = load_config(args.config.as_ref()).await?;
+            let keep_history = args.keep_history || config.keep_history;
+            let mut settings = scan::Settings::from(&scan);
+            // the additional commands are only configured in the config file
+            settings.probes = config.scan.probes;
+            // the fingerprints are keyed by serial, so they're not recorded for anonymized scans
+            if keep_history && !args.anonymize && !scan.no_fingerprint {
+                settings.fingerprints = Some(repo.fingerprints_path());
+            }
+
+            if scan.watch {
+                return watch::run(
+                    adb_host,
+                    &rules,
+                    &settings,
+                    &scan.report_dir,
+                    keep_history.then(|| repo.history_path()).as_deref(),
+                    args.anonymize,
+                )
+                .await;
+            }
+
+            let (device, serial): (Box<dyn Shell>, String) = if let Some(path) = &scan.offline {
+                info!("Scanning offline snapshot: {path:?}");
+                let serial = path.display().to_string();
+                (Box::new(OfflineShell::new(path)), serial)
+            } else {
+                let device = adb_host
+                    .device_or_default(scan.serial.as_ref(), AndroidStorageInput::Auto)
+                    .await
+                    .map_err(|source| ScanError::DeviceUnavailable {
+                        serial: scan.serial.clone(),
+                        source,
+                    })?;
+                let serial = device.serial.clone();
+                (Box::new(device), serial)
+            };
+
+            let json = if let Some(path) = &scan.json_progress {
+                Some(JsonProgress::open(path).await?)
+            } else if let Some(path) = &scan.json_socket {
+                Some(JsonProgress::listen(path, scan.json_socket_group).await?)
+            } else {
+                None
+            };
+            let mut report = if let Some(mut out) = json {
+                out.send(&progress::Event::Started { serial: &serial })
+                    .await?;
+                scan::ScanNotifier::Json(out)
+            } else if scan.syslog {
+                scan::ScanNotifier::Syslog(Syslog::connect(&serial)?)
+            } else {
+                scan::ScanNotifier::Collect(scan::Findings::default())
+            };
+
+            if scan.checkpoint || scan.resume {
+                let checkpoint =
+                    Checkpoint::open(repo.checkpoint_path(), &serial, scan.resume).await?;
+                report = scan::ScanNotifier::with_checkpoint(report, checkpoint).await?;
+            }
+
+            let ret = scan::run(device.as_ref(), &rules, &settings, &mut report).await;
+
+            if ret.is_ok() {
+                if let scan::ScanNotifier::Checkpoint(_, checkpoint) = &report {
+                    checkpoint.remove().await?;
+                }
+            }
+
+            if let scan::ScanNotifier::Json(out) = report.inner_mut() {
+                if let Err(err) = &ret {
+                    out.send(&progress::Event::Error {
+                        message: format!("{err:#}"),
+                    })
+                    .await?;
+                }
+                out.send(&progress::Event::Ended).await?;
+            }
+            // without json or syslog, the findings are printed for a human reader
+            if let scan::ScanNotifier::Collect(findings) = report.inner_mut() {
+                let device = BTreeMap::new();
+                let mut out = findings.report(&serial, &device);
+                if args.anonymize {
+                    out = out.anonymize();
+                }
+                print!("{}", export::render_text(&out));
+                if keep_history {
+                    let record = history::Record::from_report(&out, utils::now());
+                    history::save(&repo.history_path(), &record).await?;
+                }
+            }
+            ret?;
+        }
+        Some(SubCommand::Compare(compare)) => {
+            require_consent(args.config.as_ref(), args.require_consent, lang).await?;
+            ensure_adb_running(&adb_host, &args.start_adb_server).await?;
 
-            scan::run(
-                &device,
+            let repo = ioc::Repository::init().await?;
+            let rules = load_rules(&repo, &compare.rules).await?;
+            compare::run(
+                adb_host,
                 &rules,
-                &scan::Settings::from(&scan),
-                &mut scan::ScanNotifier::Null,
+                &scan::Settings::default(),
+                &compare.first,
+                &compare.second,
             )
             .await?;
         }
         Some(SubCommand::List(_)) => {
-            ensure_adb_running(&args.start_adb_server).await?;
+            require_consent(args.config.as_ref(), args.require_consent, lang).await?;
+            ensure_adb_running(&adb_host, &args.start_adb_server).await?;
 
             debug!("Listing devices from adb...");
             let devices = adb_host
                 .devices::<Vec<_>>()
                 .await
-                .map_err(|e| anyhow!("Failed to list devices from adb: {}", e))?;
+                .map_err(ScanError::AdbUnavailable)?;
 
             for device in devices {
                 debug!("Found device: {:?}", device);
-                println!(
-                    "{:30} device={:?}, model={:?}, product={:?}",
-                    device.serial,
-                    utils::human_option_str(device.info.get("device")),
-                    utils::human_option_str(device.info.get("model")),
-                    utils::human_option_str(device.info.get("product")),
-                );
+                println!("{}", utils::format_device(&device));
             }
         }
         Some(SubCommand::DownloadIoc(_download)) => {
@@ -109,18 +255,103 @@ async fn main() -> Result<()> {
         Some(SubCommand::Completions(completions)) => {
             completions.generate()?;
         }
+        Some(SubCommand::Guide(guide)) => {
+            ensure_adb_running(&adb_host, &args.start_adb_server).await?;
+
+            let repo = ioc::Repository::init().await?;
+            let rules = load_rules(&repo, &guide.rules).await?;
+            guide::run(&adb_host, &rules, lang).await?;
+        }
+        Some(SubCommand::Profile(profile)) => {
+            require_consent(args.config.as_ref(), args.require_consent, lang).await?;
+            ensure_adb_running(&adb_host, &args.start_adb_server).await?;
+
+            // the model and product are only known from the device list
+            let devices = adb_host
+                .devices::<Vec<_>>()
+                .await
+                .map_err(ScanError::AdbUnavailable)?;
+            let device = adb_host
+                .device_or_default(profile.serial.as_ref(), AndroidStorageInput::Auto)
+                .await
+                .map_err(|source| ScanError::DeviceUnavailable {
+                    serial: profile.serial.clone(),
+                    source,
+                })?;
+            let info = devices
+                .into_iter()
+                .find(|info| info.serial == device.serial)
+                .map(|info| info.info)
+                .unwrap_or_default();
+            info!("Collecting profile of device: {:?}", device.serial);
+            let overview = dashboard::collect(&device).await?;
+            let mut out = Profile {
+                serial: device.serial.as_str().into(),
+                device: &info,
+                time: utils::now(),
+                dashboard: &overview,
+            };
+            if args.anonymize {
+                out = out.anonymize();
+            }
+            dashboard::write(&profile.output, &out).await?;
+        }
+        Some(SubCommand::History(query)) => {
+            let repo = ioc::Repository::init().await?;
+            let records = history::load(&repo.history_path()).await?;
+            let matches = history::search(&records, &query.term);
+            print!("{}", history::render(&records, &query.term, &matches));
+        }
         None => {
-            ensure_adb_running(&args.start_adb_server).await?;
+            let config = load_config(args.config.as_ref()).await?;
 
+            let consent = if args.require_consent || config.require_consent {
+                if !tui::ask_consent(lang).await? {
+                    bail!("Authorization to scan has not been confirmed");
+                }
+                Some(utils::now())
+            } else {
+                None
+            };
+
+            let adb_warning = ensure_adb_running(&adb_host, &args.start_adb_server).await?;
             let repo = ioc::Repository::init().await?;
-            let mut app = tui::App::new(adb_host, repo);
+            let mut app = tui::App::new(adb_host, repo, tui::Options::from(&args), config);
             app.init().await?;
+            if let Some(path) = &args.session {
+                app.open_session(session::load(path).await?).await?;
+            }
+            if let Some(warning) = adb_warning {
+                app.set_status(warning);
+            }
             let mut terminal = tui::setup()?;
             let ret = tui::run(&mut terminal, &mut app).await;
             tui::cleanup(&mut terminal).ok();
+            if let Some(time) = consent {
+                let time = utils::format_datetime(time);
+                println!("{}", lang.format(Msg::ConsentConfirmed, &[("time", &time)]));
+            }
+            for line in app.exit_summary() {
+                println!("{line}");
+            }
             ret?;
         }
     }
 
     Ok(())
 }
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    if let Err(err) = run(args).await {
+        eprintln!("Error: {err:?}");
+        let code = if let Some(err) = err.downcast_ref::<ScanError>() {
+            eprintln!("Hint: {}", err.guidance());
+            err.exit_code()
+        } else {
+            1
+        };
+        std::process::exit(code);
+    }
+}
@@ -0,0 +1,435 @@
+use crate::checks::Check;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::settings;
+use crate::shell::Shell;
+use crate::utils;
+use async_trait::async_trait;
+use bstr::ByteSlice;
+use std::collections::BTreeSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const HOSTS_FILE: &str = "/system/etc/hosts";
+const CONNECTION_TABLES: &[&str] = &[
+    "/proc/net/tcp",
+    "/proc/net/tcp6",
+    "/proc/net/udp",
+    "/proc/net/udp6",
+];
+
+/// Read a file from the device, `None` if it's not accessible
+pub async fn cat(device: &dyn Shell, path: &str) -> Result<Option<String>> {
+    let cmd = format!("cat {path} 2>&1");
+    debug!("Executing {:?}", cmd);
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    let output = String::from_utf8_lossy(&output).into_owned();
+    if output.starts_with("cat: ") {
+        if utils::is_permission_denied(&output) {
+            info!("File is not accessible: {:?}", output.trim());
+        } else {
+            warn!("Failed to read file: {:?}", output.trim());
+        }
+        Ok(None)
+    } else {
+        Ok(Some(output))
+    }
+}
+
+/// The addresses and hostnames of a hosts file
+fn parse_hosts(hosts: &str) -> Vec<(&str, Vec<&str>)> {
+    hosts
+        .lines()
+        .filter_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let addr = words.next()?;
+            Some((addr, words.collect()))
+        })
+        .collect()
+}
+
+fn parse_hex_addr(addr: &str) -> Option<IpAddr> {
+    let (ip, _port) = addr.split_once(':')?;
+    match ip.len() {
+        8 => {
+            let ip = u32::from_str_radix(ip, 16).ok()?;
+            Some(IpAddr::V4(Ipv4Addr::from(ip.swap_bytes())))
+        }
+        32 => {
+            // four 32-bit words, each in host byte order
+            let mut octets = [0u8; 16];
+            for (i, word) in octets.chunks_mut(4).enumerate() {
+                let value = u32::from_str_radix(&ip[i * 8..(i + 1) * 8], 16).ok()?;
+                word.copy_from_slice(&value.swap_bytes().to_be_bytes());
+            }
+            let ip = Ipv6Addr::from(octets);
+            Some(
+                ip.to_ipv4_mapped()
+                    .map(IpAddr::V4)
+                    .unwrap_or(IpAddr::V6(ip)),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// The remote addresses of all connections in a `/proc/net/{tcp,udp}` table
+fn parse_connections(table: &[u8]) -> Vec<IpAddr> {
+    let mut out = Vec::new();
+    for line in table.lines().skip(1) {
+        let line = String::from_utf8_lossy(line);
+        let Some(remote) = line.split_whitespace().nth(2) else {
+            continue;
+        };
+        if let Some(ip) = parse_hex_addr(remote) {
+            if !ip.is_unspecified() && !out.contains(&ip) {
+                out.push(ip);
+            }
+        }
+    }
+    out
+}
+
+fn c2_domain(rules: &Rules, domain: &str, source: &str) -> Option<Suspicion> {
+    let rule = rules.get_domain(domain)?;
+    Some(
+        Suspicion::new(
+            rules.get_level(rule),
+            Kind::Network,
+            format!("{source} contains c2 domain of known stalkerware {rule:?}: {domain:?}"),
+        )
+        .with_remediation(rules.get_remediation(rule).cloned())
+        .with_rule(rule.clone())
+        .with_confidence(rules.get_confidence(rule)),
+    )
+}
+
+fn c2_ip(rules: &Rules, ip: &IpAddr, source: &str) -> Option<Suspicion> {
+    let rule = rules.get_ip(ip)?;
+    Some(
+        Suspicion::new(
+            rules.get_level(rule),
+            Kind::Network,
+            format!("{source} contains c2 address of known stalkerware {rule:?}: {ip}"),
+        )
+        .with_remediation(rules.get_remediation(rule).cloned())
+        .with_rule(rule.clone())
+        .with_confidence(rules.get_confidence(rule)),
+    )
+}
+
+/// Entries of the hosts file with c2 servers, a c2 domain that points to a loopback or unspecified address is blocked
+fn audit_hosts(rules: &Rules, hosts: &str) -> Vec<Suspicion> {
+    let mut findings = Vec::new();
+    for (addr, names) in parse_hosts(hosts) {
+        let ip = addr.parse::<IpAddr>().ok();
+        if let Some(ip) = &ip {
+            findings.extend(c2_ip(rules, ip, "Hosts file"));
+        }
+        let blocked = ip.is_some_and(|ip| ip.is_loopback() || ip.is_unspecified());
+        for name in names {
+            let Some(sus) = c2_domain(rules, name, "Hosts file") else {
+                continue;
+            };
+            if blocked {
+                let rule = sus.rule.clone().unwrap_or_default();
+                findings.push(
+                    Suspicion::new(
+                        SuspicionLevel::Info,
+                        Kind::Network,
+                        format!(
+                            "Hosts file blocks c2 domain of known stalkerware {rule:?}: {name:?}"
+                        ),
+                    )
+                    .with_rule(rule),
+                );
+            } else {
+                findings.push(sus);
+            }
+        }
+    }
+    findings
+}
+
+/// c2 domains that the dns resolver knows about, eg. hostnames that have been resolved recently
+fn audit_dns_resolver(rules: &Rules, dump: &str) -> Vec<Suspicion> {
+    let mut seen = BTreeSet::new();
+    dump.split(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+        .map(|word| word.trim_matches('.'))
+        .filter(|word| word.contains('.') && seen.insert(word.to_ascii_lowercase()))
+        .filter_map(|word| c2_domain(rules, word, "DNS resolver"))
+        .collect()
+}
+
+/// A proxy that has been configured for all traffic of the device, an advisory unless it's a c2 server
+fn audit_proxy(rules: &Rules, setting: &str, value: &str) -> Option<Suspicion> {
+    // `:0` is what's left behind after a proxy has been removed with `settings put`
+    let value = value.trim();
+    if value.is_empty() || value == ":0" {
+        return None;
+    }
+    let host = value.rsplit_once(':').map_or(value, |(host, _)| host);
+    let c2 = c2_domain(rules, host, "Proxy setting").or_else(|| {
+        let ip = host.parse::<IpAddr>().ok()?;
+        c2_ip(rules, &ip, "Proxy setting")
+    });
+    Some(c2.unwrap_or_else(|| {
+        Suspicion::new(
+            SuspicionLevel::Info,
+            Kind::Network,
+            format!("Device traffic is sent through a proxy ({setting}): {value:?}"),
+        )
+    }))
+}
+
+/// An always-on vpn, an advisory unless the app is known stalkerware
+fn audit_vpn(rules: &Rules, app: &str, lockdown: bool) -> Suspicion {
+    let lockdown = if lockdown {
+        ", connections without vpn are blocked"
+    } else {
+        ""
+    };
+    let description = format!("Always-on vpn is enabled for app {app:?}{lockdown}");
+    match rules.get(app) {
+        Some(rule) => Suspicion::new(rules.get_level(rule), Kind::Network, description)
+            .with_remediation(rules.get_remediation(rule).cloned())
+            .with_rule(rule.clone())
+            .with_confidence(rules.get_confidence(rule)),
+        None => Suspicion::new(SuspicionLevel::Info, Kind::Network, description),
+    }
+}
+
+pub struct NetworkCheck;
+
+#[async_trait]
+impl Check for NetworkCheck {
+    fn id(&self) -> &'static str {
+        "network"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look for c2 servers of known stalkerware in the private dns, proxy and vpn settings, dns cache, hosts file and active connections"
+    }
+
+    fn network(&self) -> bool {
+        true
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        rules: &Rules,
+        _settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        let mut findings = Vec::new();
+
+        info!("Checking private dns setting");
+        if let Some(hostname) = settings::get(device, "global", "private_dns_specifier").await? {
+            findings.extend(c2_domain(rules, &hostname, "Private DNS setting"));
+        }
+
+        info!("Checking proxy and vpn settings");
+        for setting in ["http_proxy", "global_http_proxy_host"] {
+            if let Some(value) = settings::get(device, "global", setting).await? {
+                findings.extend(audit_proxy(rules, setting, &value));
+            }
+        }
+        if let Some(app) = settings::get(device, "secure", "always_on_vpn_app").await? {
+            let lockdown = settings::get(device, "secure", "always_on_vpn_lockdown").await?;
+            findings.push(audit_vpn(rules, &app, lockdown.as_deref() == Some("1")));
+        }
+
+        info!("Checking dns cache");
+        let cmd = "dumpsys dnsresolver";
+        match device.exec(cmd).await {
+            Ok(dump) => findings.extend(audit_dns_resolver(rules, &String::from_utf8_lossy(&dump))),
+            Err(err) => {
+                report
+                    .warning(format!(
+                        "Failed to run {cmd:?}, the dns cache can't be checked: {err:#}"
+                    ))
+                    .await?
+            }
+        }
+
+        info!("Checking hosts file");
+        if let Some(hosts) = cat(device, HOSTS_FILE).await? {
+            findings.extend(audit_hosts(rules, &hosts));
+        } else {
+            report
+                .warning(format!("Hosts file {HOSTS_FILE:?} is not readable"))
+                .await?;
+        }
+
+        info!("Checking active connections");
+        for table in CONNECTION_TABLES {
+            let Some(connections) = cat(device, table).await? else {
+                report
+                    .warning(format!(
+                        "Connection table {table:?} is not readable, active connections can't be checked"
+                    ))
+                    .await?;
+                continue;
+            };
+            for ip in parse_connections(connections.as_bytes()) {
+                findings.extend(c2_ip(rules, &ip, "Active connections"));
+            }
+        }
+
+        for sus in findings {
+            warn!("Suspicious {:?}: {}", sus.level, sus.description);
+            report.sus(sus).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hosts() {
+        let hosts = parse_hosts(
+            "127.0.0.1       localhost
+::1             ip6-localhost
+# 10.0.0.1 commented.example
+176.9.42.16 reptilicus.net www.reptilicus.net # added
+",
+        );
+        assert_eq!(
+            hosts,
+            &[
+                ("127.0.0.1", vec!["localhost"]),
+                ("::1", vec!["ip6-localhost"]),
+                ("176.9.42.16", vec!["reptilicus.net", "www.reptilicus.net"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_hosts() {
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "test.yaml",
+                b"- name: Reptilicus\n  type: stalkerware\n  c2:\n    domains:\n    - reptilicus.net\n",
+            )
+            .unwrap();
+        let findings = audit_hosts(
+            &rules,
+            "176.9.42.16 reptilicus.net\n127.0.0.1 reptilicus.net\n0.0.0.0 reptilicus.net\n",
+        );
+        let levels = findings.iter().map(|sus| sus.level).collect::<Vec<_>>();
+        assert_eq!(
+            levels,
+            &[
+                SuspicionLevel::High,
+                SuspicionLevel::Info,
+                SuspicionLevel::Info
+            ]
+        );
+        assert_eq!(
+            findings[1].description,
+            "Hosts file blocks c2 domain of known stalkerware \"Reptilicus\": \"reptilicus.net\""
+        );
+    }
+
+    #[test]
+    fn test_audit_dns_resolver() {
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "test.yaml",
+                b"- name: Reptilicus\n  type: stalkerware\n  c2:\n    domains:\n    - reptilicus.net\n",
+            )
+            .unwrap();
+        let findings = audit_dns_resolver(
+            &rules,
+            "NetId: 100
+  DnsEvent: query api.reptilicus.net. type=A
+  DnsEvent: query API.REPTILICUS.NET type=AAAA
+  DnsEvent: query www.example.com type=A
+  Servers: [8.8.8.8] Domains: []
+",
+        );
+        assert_eq!(
+            findings,
+            &[Suspicion::new(
+                SuspicionLevel::High,
+                Kind::Network,
+                "DNS resolver contains c2 domain of known stalkerware \"Reptilicus\": \"api.reptilicus.net\""
+            )
+            .with_rule("Reptilicus")]
+        );
+    }
+
+    #[test]
+    fn test_audit_proxy() {
+        let rules = Rules::default();
+        assert_eq!(audit_proxy(&rules, "http_proxy", ":0"), None);
+        assert_eq!(
+            audit_proxy(&rules, "http_proxy", "176.9.42.16:8080\n"),
+            Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Network,
+                "Device traffic is sent through a proxy (http_proxy): \"176.9.42.16:8080\""
+            ))
+        );
+        assert_eq!(
+            audit_vpn(&rules, "org.example.vpn", true),
+            Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Network,
+                "Always-on vpn is enabled for app \"org.example.vpn\", connections without vpn are blocked"
+            )
+        );
+
+        // only a c2 server or known stalkerware is a finding of its own
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "test.yaml",
+                b"- name: Reptilicus\n  type: stalkerware\n  packages:\n  - net.vpn.reptilicus\n  c2:\n    ips:\n    - 176.9.42.16\n",
+            )
+            .unwrap();
+        let sus = audit_proxy(&rules, "http_proxy", "176.9.42.16:8080").unwrap();
+        assert_eq!(sus.level, SuspicionLevel::High);
+        assert_eq!(sus.rule.as_deref(), Some("Reptilicus"));
+        let sus = audit_vpn(&rules, "net.vpn.reptilicus", false);
+        assert_eq!(sus.level, SuspicionLevel::High);
+        assert_eq!(sus.rule.as_deref(), Some("Reptilicus"));
+    }
+
+    #[test]
+    fn test_parse_connections() {
+        let ips = parse_connections(
+            b"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:13AD 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 12345 1 0000000000000000 100 0 0 10 0
+   1: 0F02000A:B2C4 102A09B0:01BB 01 00000000:00000000 00:00000000 00000000 10123        0 23456 1 0000000000000000 20 4 30 10 -1
+",
+        );
+        assert_eq!(ips, &["176.9.42.16".parse::<IpAddr>().unwrap()]);
+
+        let ips = parse_connections(
+            b"  sl  local_address                         remote_address                        st
+   0: 0000000000000000FFFF00000F02000A:B2C4 0000000000000000FFFF0000102A09B0:01BB 01
+   1: B80D01200000000000000000AA000000:B2C4 B80D0120000000000000000001000000:01BB 01
+",
+        );
+        assert_eq!(
+            ips,
+            &[
+                "176.9.42.16".parse::<IpAddr>().unwrap(),
+                "2001:db8::1".parse::<IpAddr>().unwrap(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::errors::*;
+use crate::shell::Shell;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// Directories of an extracted backup or file dump that contain one directory per app
+const APP_DIRECTORIES: &[&str] = &["apps", "data/data"];
+
+/// Serves the commands used by the offline-capable checks from an extracted snapshot
+///
+/// The directory is treated like the root of the device filesystem, for example a
+/// pulled `/sdcard/Download` is expected at `<root>/sdcard/Download`.
+#[derive(Debug)]
+pub struct OfflineShell {
+    root: PathBuf,
+}
+
+impl OfflineShell {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn resolve(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    async fn list_packages(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        for dir in APP_DIRECTORIES {
+            let path = self.root.join(dir);
+            let Ok(mut entries) = fs::read_dir(&path).await else {
+                continue;
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_dir() {
+                    let id = entry.file_name();
+                    out.extend(b"package:");
+                    out.extend(id.to_string_lossy().as_bytes());
+                    out.push(b'\n');
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    async fn find(&self, root: &str) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut queue = vec![(self.resolve(root), root.trim_end_matches('/').to_string())];
+        while let Some((path, display)) = queue.pop() {
+            let mut entries = match fs::read_dir(&path).await {
+                Ok(entries) => entries,
+                Err(err) => {
+                    out.extend(format!("find: '{display}': {err}\n").as_bytes());
+                    continue;
+                }
+            };
+            while let Some(entry) = entries.next_entry().await? {
+                let name = entry.file_name();
+                let display = format!("{display}/{}", name.to_string_lossy());
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    queue.push((entry.path(), display));
+                } else if file_type.is_file() {
+                    out.extend(display.as_bytes());
+                    out.push(b'\n');
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// The directory argument of a `find <dir> -type f 2>&1` command, as issued by the files check
+fn parse_find(cmd: &str) -> Option<String> {
+    let args = cmd.strip_prefix("find ")?.strip_suffix(" -type f 2>&1")?;
+    let path = if let Some(quoted) = args.strip_prefix('\'') {
+        quoted.strip_suffix('\'')?.replace("'\\''", "'")
+    } else {
+        args.to_string()
+    };
+    Some(path)
+}
+
+#[async_trait]
+impl Shell for OfflineShell {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        if cmd.starts_with("pm list packages") {
+            self.list_packages().await
+        } else if let Some(path) = parse_find(cmd) {
+            self.find(&path).await
+        } else {
+            bail!("Command is not available in offline mode: {cmd:?}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_find() {
+        assert_eq!(
+            parse_find("find /sdcard/Download -type f 2>&1"),
+            Some("/sdcard/Download".to_string())
+        );
+        assert_eq!(
+            parse_find("find '/sdcard/My Files' -type f 2>&1"),
+            Some("/sdcard/My Files".to_string())
+        );
+        assert_eq!(parse_find("dumpsys -l"), None);
+    }
+
+    #[tokio::test]
+    async fn test_offline_snapshot() {
+        let root = std::env::temp_dir().join(format!("spytrap-offline-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("apps/com.wifi0/f")).unwrap();
+        std::fs::create_dir_all(root.join("sdcard/Download/nested")).unwrap();
+        std::fs::write(root.join("sdcard/Download/nested/com.wifi0.apk"), b"").unwrap();
+
+        let shell = OfflineShell::new(&root);
+        let packages = shell.exec("pm list packages -f -i").await.unwrap();
+        let files = shell
+            .exec("find /sdcard/Download -type f 2>&1")
+            .await
+            .unwrap();
+        let missing = shell
+            .exec("find /sdcard/Documents -type f 2>&1")
+            .await
+            .unwrap();
+        let unsupported = shell.exec("dumpsys -l").await;
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(packages, b"package:com.wifi0\n");
+        assert_eq!(files, b"/sdcard/Download/nested/com.wifi0.apk\n");
+        assert!(missing.starts_with(b"find: '/sdcard/Documents': "));
+        assert!(unsupported.is_err());
+    }
+}
@@ -1,22 +1,325 @@
+use crate::checks::Check;
 use crate::errors::*;
-use crate::ioc::{Suspicion, SuspicionLevel};
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
 use crate::parsers::{self, package::PackageInfo, package::Permission};
-use forensic_adb::Device;
+use crate::pm;
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use crate::utils;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use futures::stream::{self, StreamExt};
 use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 
-pub async fn dump(device: &Device, package: &str) -> Result<PackageInfo> {
+pub async fn dump(device: &dyn Shell, package: &str) -> Result<PackageInfo> {
     let cmd = format!(
         "dumpsys package {}",
         shell_escape::escape(Cow::Borrowed(package))
     );
     debug!("Executing {:?}", cmd);
     let output = device
-        .execute_host_exec_out_command(&cmd)
+        .exec(&cmd)
         .await
         .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
     parsers::package::parse_output(&output, package)
 }
 
+pub struct AppsCheck;
+
+#[async_trait]
+impl Check for AppsCheck {
+    fn id(&self) -> &'static str {
+        "apps"
+    }
+
+    fn description(&self) -> &'static str {
+        "Compare installed apps of all users with known stalkerware and audit their installer, launcher icon and permissions"
+    }
+
+    fn enabled(&self, settings: &scan::Settings) -> bool {
+        !settings.skip_apps
+    }
+
+    fn offline(&self) -> bool {
+        true
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        rules: &Rules,
+        settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        info!("Comparing list of installed apps with known stalkerware ids");
+
+        let mut installed_apps = pm::list_packages(device).await?;
+        let mut profiles = HashMap::new();
+        if !settings.offline {
+            match list_secondary_apps(device, &installed_apps).await {
+                Ok((apps, users)) => {
+                    installed_apps.extend(apps);
+                    profiles = users;
+                }
+                Err(err) => {
+                    warn!("Failed to list apps of secondary users: {err:#}");
+                    report
+                        .warning(format!(
+                            "Failed to list apps of secondary users, work profiles can't be checked: {err:#}"
+                        ))
+                        .await?;
+                }
+            }
+        }
+        installed_apps.retain(|pkg| !report.is_app_done(&pkg.id));
+
+        let launchable = if settings.offline {
+            None
+        } else {
+            match pm::list_launchable(device).await {
+                Ok(launchable) => Some(launchable),
+                Err(err) => {
+                    warn!("Failed to list launchable apps: {err:#}");
+                    report
+                        .warning(format!(
+                            "Failed to list launchable apps, hidden apps can't be detected: {err:#}"
+                        ))
+                        .await?;
+                    None
+                }
+            }
+        };
+
+        let default_handlers = if settings.offline {
+            Vec::new()
+        } else {
+            list_default_handlers(device, report).await?
+        };
+
+        // package details are only available from a live device, fetch them in parallel
+        let pending = installed_apps
+            .iter()
+            .map(|pkg| fetch_info(device, settings.offline, &pkg.id))
+            .collect::<Vec<_>>();
+        let mut infos = stream::iter(pending).buffered(settings.concurrency.max(1));
+
+        for (progress, pkg) in installed_apps.iter().enumerate() {
+            if progress % 100 == 0 {
+                info!(
+                    "Scanning installed apps ({}/{})",
+                    progress,
+                    installed_apps.len()
+                );
+            }
+
+            // results arrive in the same order as the list of apps
+            let info = infos.next().await.transpose();
+            let outdated = match (settings.since, &info) {
+                (Some(since), Ok(Some(Some(info)))) => {
+                    info.last_update_time().is_some_and(|time| time < since)
+                }
+                _ => false,
+            };
+
+            // TODO: maybe fetch apk and inspect eg. cert
+
+            if let Some(name) = rules.get(&pkg.id) {
+                let sus = Suspicion::new(
+                    rules.get_level(name),
+                    Kind::Package,
+                    format!(
+                        "Found known stalkerware with rule: {:?} ({:?})",
+                        pkg.id, name
+                    ),
+                )
+                .with_remediation(rules.get_remediation(name).cloned())
+                .with_rule(name.clone())
+                .with_confidence(rules.get_confidence(name));
+                let sus = pkg.tag(sus);
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.app(pkg.id.clone(), sus).await?;
+
+                if let Some(sus) = profiles
+                    .get(&pkg.id)
+                    .and_then(|users| audit_profiles(&pkg.id, name, rules, users))
+                {
+                    warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                    report.app(pkg.id.clone(), sus).await?;
+                }
+            }
+
+            // known stalkerware is always reported, only the heuristics are skipped for old apps
+            if outdated {
+                debug!(
+                    "Skipping audits of package {:?}, not updated since the given date",
+                    pkg.id
+                );
+                report.app_done(&pkg.id).await?;
+                report
+                    .count(self.id(), progress + 1, Some(installed_apps.len()))
+                    .await?;
+                continue;
+            }
+
+            if let Some(sus) = pkg.audit_name().map(|sus| pkg.tag(sus)) {
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.app(pkg.id.clone(), sus).await?;
+            }
+
+            if let Some(sus) = pkg.audit_installer().map(|sus| pkg.tag(sus)) {
+                info!("Advisory {:?}: {}", sus.level, sus.description);
+                report.app(pkg.id.clone(), sus).await?;
+            }
+
+            // launcher activities have only been queried for the primary user
+            if let Some(sus) = launchable
+                .as_ref()
+                .filter(|_| pkg.user.is_none())
+                .and_then(|launchable| pkg.audit_launcher(launchable))
+            {
+                info!("Advisory {:?}: {}", sus.level, sus.description);
+                report.app(pkg.id.clone(), sus).await?;
+            }
+
+            match info {
+                Ok(Some(Some(info))) => {
+                    trace!("package infos {:?}: {:#?}", pkg.id, info);
+                    for sus in info.audit().into_iter().map(|sus| pkg.tag(sus)) {
+                        warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                        report.app(pkg.id.clone(), sus).await?;
+                    }
+                    if let Some(sus) = info
+                        .audit_communication_access(&default_handlers)
+                        .filter(|_| !pkg.is_system())
+                        .map(|sus| pkg.tag(sus))
+                    {
+                        info!("Advisory {:?}: {}", sus.level, sus.description);
+                        report.app(pkg.id.clone(), sus).await?;
+                    }
+                    if let Some(sus) = info
+                        .audit_permission_combination(&default_handlers)
+                        .filter(|_| !pkg.is_system())
+                        .map(|sus| pkg.tag(sus))
+                    {
+                        info!("Advisory {:?}: {}", sus.level, sus.description);
+                        report.app(pkg.id.clone(), sus).await?;
+                    }
+                    if let Some(sus) = info
+                        .audit_install_capability()
+                        .filter(|_| !pkg.is_system())
+                        .map(|sus| pkg.tag(sus))
+                    {
+                        info!("Advisory {:?}: {}", sus.level, sus.description);
+                        report.app(pkg.id.clone(), sus).await?;
+                    }
+                }
+                Ok(_) => (),
+                Err(err) => {
+                    warn!("Failed to inspect package {:?}: {err:#}", pkg.id);
+                    report
+                        .warning(format!("Failed to inspect package {:?}: {err:#}", pkg.id))
+                        .await?;
+                }
+            }
+
+            report.app_done(&pkg.id).await?;
+            report
+                .count(self.id(), progress + 1, Some(installed_apps.len()))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The default sms and phone apps, a failed lookup is reported and treated as no default apps
+async fn list_default_handlers(
+    device: &dyn Shell,
+    report: &mut ScanNotifier,
+) -> Result<Vec<String>> {
+    let mut handlers = Vec::new();
+    for role in pm::COMMUNICATION_ROLES {
+        match pm::list_role_holders(device, role).await {
+            Ok(holders) => handlers.extend(holders),
+            Err(err) => {
+                warn!("Failed to list holders of role {role:?}: {err:#}");
+                report
+                    .warning(format!(
+                        "Failed to find the default app for {role:?}, it may be reported for reading messages or calls: {err:#}"
+                    ))
+                    .await?;
+            }
+        }
+    }
+    Ok(handlers)
+}
+
+/// Apps that are only installed for a secondary user, eg. in a work profile, and the users of apps that are installed more than once
+async fn list_secondary_apps(
+    device: &dyn Shell,
+    primary: &[pm::Apk],
+) -> Result<(Vec<pm::Apk>, HashMap<String, BTreeSet<u32>>)> {
+    let mut apps = Vec::<pm::Apk>::new();
+    let mut profiles = primary
+        .iter()
+        .map(|pkg| (pkg.id.clone(), BTreeSet::from([pm::PRIMARY_USER])))
+        .collect::<HashMap<_, _>>();
+    for user in pm::list_users(device).await? {
+        if user.id == pm::PRIMARY_USER {
+            continue;
+        }
+        info!(
+            "Listing installed apps of user {} ({:?})",
+            user.id, user.name
+        );
+        for pkg in pm::list_packages_for_user(device, user.id).await? {
+            let users = profiles.entry(pkg.id.clone()).or_default();
+            // the first user an app was found for is the one it's inspected as
+            if users.is_empty() {
+                apps.push(pkg);
+            }
+            users.insert(user.id);
+        }
+    }
+    profiles.retain(|_, users| users.len() > 1);
+    Ok((apps, profiles))
+}
+
+/// Known stalkerware that is installed for more than one user, eg. also hidden in a work profile
+fn audit_profiles(id: &str, rule: &str, rules: &Rules, users: &BTreeSet<u32>) -> Option<Suspicion> {
+    if users.len() < 2 {
+        return None;
+    }
+    let users = users
+        .iter()
+        .map(|user| user.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(
+        Suspicion::new(
+            rules.get_level(rule),
+            Kind::Package,
+            format!("Known stalkerware {id:?} is installed for several users or profiles: {users}"),
+        )
+        .with_rule(rule.to_string())
+        .with_confidence(rules.get_confidence(rule)),
+    )
+}
+
+async fn fetch_info(
+    device: &dyn Shell,
+    offline: bool,
+    package: &str,
+) -> Result<Option<PackageInfo>> {
+    if offline {
+        Ok(None)
+    } else {
+        dump(device, package).await.map(Some)
+    }
+}
+
 fn is_permission_suspcious(permission: &Permission) -> Option<SuspicionLevel> {
     match permission.name.as_str() {
         // sus: high
@@ -44,7 +347,142 @@ fn is_permission_suspcious(permission: &Permission) -> Option<SuspicionLevel> {
     }
 }
 
+/// Capabilities of a surveillance app, each granted by any of the listed permissions
+const SURVEILLANCE_CAPABILITIES: &[(&str, &[&str])] = &[
+    (
+        "location",
+        &[
+            "android.permission.ACCESS_FINE_LOCATION",
+            "android.permission.ACCESS_COARSE_LOCATION",
+        ],
+    ),
+    (
+        "background location",
+        &["android.permission.ACCESS_BACKGROUND_LOCATION"],
+    ),
+    (
+        "sms",
+        &[
+            "android.permission.READ_SMS",
+            "android.permission.RECEIVE_SMS",
+        ],
+    ),
+    ("microphone", &["android.permission.RECORD_AUDIO"]),
+    ("camera", &["android.permission.CAMERA"]),
+    ("call log", &["android.permission.READ_CALL_LOG"]),
+];
+
+/// Permissions to read messages and calls, only expected for the default sms and phone app
+const COMMUNICATION_PERMISSIONS: &[&str] = &[
+    "android.permission.READ_SMS",
+    "android.permission.RECEIVE_SMS",
+    "android.permission.READ_CALL_LOG",
+];
+
+/// Allows an app to install other apps, once the user allows it as a source of unknown apps
+const INSTALL_PACKAGES_PERMISSION: &str = "android.permission.REQUEST_INSTALL_PACKAGES";
+
 impl PackageInfo {
+    /// Install and runtime permissions that are currently granted
+    pub fn granted_permissions(&self) -> impl Iterator<Item = &str> {
+        self.install_permissions
+            .iter()
+            .chain(self.runtime_permissions.iter())
+            .filter(|permission| {
+                permission.fields.get("granted").map(String::as_str) == Some("true")
+            })
+            .map(|permission| permission.name.as_str())
+    }
+
+    /// Report apps that have been granted enough capabilities to monitor somebody, the default sms and phone apps need them
+    pub fn audit_permission_combination(&self, default_handlers: &[String]) -> Option<Suspicion> {
+        if default_handlers.contains(&self.id) {
+            return None;
+        }
+        let granted = self.granted_permissions().collect::<Vec<_>>();
+
+        let mut capabilities = Vec::new();
+        let mut permissions = Vec::<&str>::new();
+        for (capability, names) in SURVEILLANCE_CAPABILITIES {
+            let matches = names
+                .iter()
+                .copied()
+                .filter(|name| granted.contains(name))
+                .collect::<Vec<_>>();
+            if !matches.is_empty() {
+                capabilities.push(*capability);
+                permissions.extend(matches);
+            }
+        }
+
+        if capabilities.len() < 3 {
+            return None;
+        }
+        Some(Suspicion::new(
+            SuspicionLevel::Info,
+            Kind::Package,
+            format!(
+                "Package {:?} has been granted a suspicious combination of permissions ({}): {:?}",
+                self.id,
+                capabilities.join(", "),
+                permissions
+            ),
+        ))
+    }
+
+    /// Report apps that can read messages or calls without being the default sms or phone app
+    pub fn audit_communication_access(&self, default_handlers: &[String]) -> Option<Suspicion> {
+        if default_handlers.contains(&self.id) {
+            return None;
+        }
+        let granted = self
+            .install_permissions
+            .iter()
+            .map(|permission| ("install", permission))
+            .chain(
+                self.runtime_permissions
+                    .iter()
+                    .map(|permission| ("runtime", permission)),
+            )
+            .filter(|(_, permission)| {
+                COMMUNICATION_PERMISSIONS.contains(&permission.name.as_str())
+                    && permission.fields.get("granted").map(String::as_str) == Some("true")
+            })
+            .map(
+                |(source, permission)| match permission.fields.get("flags") {
+                    Some(flags) => format!("{} ({source}, flags={flags})", permission.name),
+                    None => format!("{} ({source})", permission.name),
+                },
+            )
+            .collect::<Vec<_>>();
+        if granted.is_empty() {
+            return None;
+        }
+        Some(Suspicion::new(SuspicionLevel::Info, Kind::Package, format!(
+                "Package {:?} can read text messages or call logs but is not the default sms or phone app: {}",
+                self.id,
+                granted.join(", ")
+            )))
+    }
+
+    /// Report apps from outside of an app store that could install additional payloads
+    pub fn audit_install_capability(&self) -> Option<Suspicion> {
+        if self.installer_package_name().is_some_and(pm::is_app_store) {
+            return None;
+        }
+        if !self
+            .requested_permissions
+            .iter()
+            .any(|permission| permission.name == INSTALL_PACKAGES_PERMISSION)
+        {
+            return None;
+        }
+        Some(Suspicion::new(SuspicionLevel::Info, Kind::Package, format!(
+                "Package {:?} has not been installed from an app store and can install other apps: {INSTALL_PACKAGES_PERMISSION:?}",
+                self.id
+            )))
+    }
+
     pub fn audit(&self) -> Vec<Suspicion> {
         debug!("Scanning package: {:?}", self.id);
 
@@ -54,20 +492,24 @@ impl PackageInfo {
             Some("com.android.vending") => {
                 // TODO: authenticate this application is a legitimate google play store .apk
             }
+            // reported as an advisory by the installer audit of the app list
+            Some(installer) if pm::is_app_store(installer) => (),
             Some("com.android.packageinstaller") => {
-                sus.push(Suspicion {
-                    level: SuspicionLevel::High,
-                    description: format!("Package {:?} was manually installed", self.id),
-                });
+                sus.push(Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::Package,
+                    format!("Package {:?} was manually installed", self.id),
+                ));
             }
             Some(installer) => {
-                sus.push(Suspicion {
-                    level: SuspicionLevel::High,
-                    description: format!(
+                sus.push(Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::Package,
+                    format!(
                         "Package {:?} was manually installed by an unknown installer: {:?}",
                         self.id, installer
                     ),
-                });
+                ));
             }
             None => (),
         }
@@ -76,13 +518,14 @@ impl PackageInfo {
             // warn!("requested permission: {:?}", permission);
             // println!("permission {:?}", permission.name);
             if let Some(level) = is_permission_suspcious(permission) {
-                sus.push(Suspicion {
+                sus.push(Suspicion::new(
                     level,
-                    description: format!(
+                    Kind::Package,
+                    format!(
                         "Package {:?} has requested permission {:?}",
                         self.id, permission
                     ),
-                });
+                ));
             }
         }
 
@@ -90,13 +533,14 @@ impl PackageInfo {
             // warn!("install permission: {:?}", permission);
             // println!("permission {:?}", permission.name);
             if let Some(level) = is_permission_suspcious(permission) {
-                sus.push(Suspicion {
+                sus.push(Suspicion::new(
                     level,
-                    description: format!(
+                    Kind::Package,
+                    format!(
                         "Package {:?} has install permission {:?}",
                         self.id, permission
                     ),
-                });
+                ));
             }
         }
 
@@ -104,19 +548,40 @@ impl PackageInfo {
             // warn!("runtime permission: {:?}", permission);
             // println!("permission {:?}", permission.name);
             if let Some(level) = is_permission_suspcious(permission) {
-                sus.push(Suspicion {
+                sus.push(Suspicion::new(
                     level,
-                    description: format!(
+                    Kind::Package,
+                    format!(
                         "Package {:?} has runtime permission {:?}",
                         self.id, permission
                     ),
-                });
+                ));
             }
         }
 
+        if self.invalid_utf8_lines > 0 {
+            sus.push(Suspicion::new(
+                SuspicionLevel::Low,
+                Kind::Package,
+                format!(
+                    "Package {:?} has {} lines of package info with invalid UTF-8",
+                    self.id, self.invalid_utf8_lines
+                ),
+            ));
+        }
+
         sus
     }
 
+    /// When the app has been installed or updated the last time, in the timezone of the device
+    pub fn last_update_time(&self) -> Option<NaiveDateTime> {
+        ["firstInstallTime", "lastUpdateTime"]
+            .iter()
+            .filter_map(|key| self.fields.get(*key))
+            .filter_map(|value| utils::parse_datetime(value).ok())
+            .max()
+    }
+
     pub fn installer_package_name(&self) -> Option<&str> {
         self.fields.get("installerPackageName").map(String::as_str)
     }
@@ -125,6 +590,71 @@ impl PackageInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ioc::Confidence;
+
+    #[tokio::test]
+    async fn test_since_matches_rules() {
+        let device = crate::selftest::mock_device();
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "test",
+                b"- name: SpyLive360\n  type: stalkerware\n  packages:\n  - com.wifi0\n",
+            )
+            .unwrap();
+        // the package has last been updated in 2021
+        let settings = scan::Settings {
+            since: Some(utils::parse_datetime("2024-06-01").unwrap()),
+            ..Default::default()
+        };
+        let mut report = ScanNotifier::Collect(scan::Findings::default());
+        AppsCheck
+            .run(&device, &rules, &settings, &mut report)
+            .await
+            .unwrap();
+        let ScanNotifier::Collect(findings) = report else {
+            unreachable!()
+        };
+        let descriptions = findings.apps["com.wifi0"]
+            .iter()
+            .map(|sus| sus.description.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            &["Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")"]
+        );
+    }
+
+    #[test]
+    fn test_audit_profiles() {
+        let mut rules = Rules::default();
+        assert_eq!(
+            audit_profiles("com.wifi0", "SpyLive360", &rules, &BTreeSet::from([0])),
+            None
+        );
+        assert_eq!(
+            audit_profiles("com.wifi0", "SpyLive360", &rules, &BTreeSet::from([0, 10])),
+            Some(
+                Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::Package,
+                    "Known stalkerware \"com.wifi0\" is installed for several users or profiles: 0, 10",
+                )
+                .with_rule("SpyLive360")
+            )
+        );
+
+        rules
+            .load_yaml(
+                "unit-test",
+                b"- name: SpyLive360\n  type: stalkerware\n  packages:\n  - com.wifi0\n  level: medium\n  confidence: low\n",
+            )
+            .unwrap();
+        let sus =
+            audit_profiles("com.wifi0", "SpyLive360", &rules, &BTreeSet::from([0, 10])).unwrap();
+        assert_eq!(sus.level, SuspicionLevel::Medium);
+        assert_eq!(sus.confidence, Some(Confidence::Low));
+    }
 
     #[test]
     fn test_audit_package_spylive360() {
@@ -132,35 +662,32 @@ mod tests {
         let pkginfo = parsers::package::parse_output(data, "com.wifi0").unwrap();
         let sus = pkginfo.audit();
         assert_eq!(&sus, &[
-            Suspicion {
-                level: SuspicionLevel::High,
-                description: "Package \"com.wifi0\" was manually installed".to_string(),
-            },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"restricted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::High, description: "Package \"com.wifi0\" has requested permission Permission { name: \"ACTION_NOTIFICATION_LISTENER_SETTINGS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_SMS\", fields: {\"restricted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_CALL_LOG\", fields: {\"restricted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.CAMERA\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::High, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACTION_MANAGE_OVERLAY_PERMISSION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.REQUEST_IGNORE_BATTERY_OPTIMIZATIONS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.RECEIVE_SMS\", fields: {\"restricted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::High, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.BIND_ACCESSIBILITY_SERVICE\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.QUERY_ALL_PACKAGES\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has install permission Permission { name: \"android.permission.REQUEST_IGNORE_BATTERY_OPTIMIZATIONS\", fields: {\"granted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has install permission Permission { name: \"android.permission.QUERY_ALL_PACKAGES\", fields: {\"granted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_SMS\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_CALL_LOG\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.RECEIVE_SMS\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.CAMERA\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }".to_string() },
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.wifi0\" was manually installed"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"restricted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"ACTION_NOTIFICATION_LISTENER_SETTINGS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_SMS\", fields: {\"restricted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.READ_CALL_LOG\", fields: {\"restricted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.CAMERA\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.ACTION_MANAGE_OVERLAY_PERMISSION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.REQUEST_IGNORE_BATTERY_OPTIMIZATIONS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.RECEIVE_SMS\", fields: {\"restricted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.BIND_ACCESSIBILITY_SERVICE\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has requested permission Permission { name: \"android.permission.QUERY_ALL_PACKAGES\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has install permission Permission { name: \"android.permission.REQUEST_IGNORE_BATTERY_OPTIMIZATIONS\", fields: {\"granted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has install permission Permission { name: \"android.permission.QUERY_ALL_PACKAGES\", fields: {\"granted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_SMS\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_CALL_LOG\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.RECEIVE_SMS\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.CAMERA\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {\"flags\": \"[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"com.wifi0\" has runtime permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }"),
         ]);
     }
 
@@ -170,8 +697,8 @@ mod tests {
         let pkginfo = parsers::package::parse_output(data, "com.android.contacts").unwrap();
         let sus = pkginfo.audit();
         assert_eq!(&sus, &[
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.contacts\" has requested permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.contacts\" has runtime permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {\"flags\": \"[ GRANTED_BY_DEFAULT|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }".to_string() },
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.contacts\" has requested permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.contacts\" has runtime permission Permission { name: \"android.permission.READ_CONTACTS\", fields: {\"flags\": \"[ GRANTED_BY_DEFAULT|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }"),
         ]);
     }
 
@@ -181,33 +708,41 @@ mod tests {
         let pkginfo = parsers::package::parse_output(data, "org.fdroid.fdroid").unwrap();
         let sus = pkginfo.audit();
         assert_eq!(&sus, &[
-            Suspicion {
-                level: SuspicionLevel::High,
-                description: "Package \"org.fdroid.fdroid\" was manually installed".to_string(),
-            },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.fdroid.fdroid\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"org.fdroid.fdroid\" has requested permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"restricted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.fdroid.fdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Medium, description: "Package \"org.fdroid.fdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"flags\": \"[ REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }".to_string() },
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"org.fdroid.fdroid\" was manually installed"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.fdroid.fdroid\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"org.fdroid.fdroid\" has requested permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"restricted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.fdroid.fdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Medium, Kind::Package, "Package \"org.fdroid.fdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_BACKGROUND_LOCATION\", fields: {\"flags\": \"[ REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]\", \"granted\": \"false\"} }"),
         ]);
     }
 
+    #[test]
+    fn test_audit_package_from_app_store() {
+        let pkginfo = PackageInfo {
+            id: "org.jitsi.meet".to_string(),
+            fields: [(
+                "installerPackageName".to_string(),
+                "org.fdroid.fdroid".to_string(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        assert_eq!(pkginfo.audit(), &[]);
+    }
+
     #[test]
     fn test_audit_package_gpstest() {
         let data = include_bytes!("../test_data/dumpsys/package/gpstest.txt");
         let pkginfo = parsers::package::parse_output(data, "com.android.gpstest.osmdroid").unwrap();
         let sus = pkginfo.audit();
         assert_eq!(&sus, &[
-            Suspicion {
-                level: SuspicionLevel::High,
-                description: "Package \"com.android.gpstest.osmdroid\" was manually installed".to_string(),
-            },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_LOCATION_EXTRA_COMMANDS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has install permission Permission { name: \"android.permission.ACCESS_LOCATION_EXTRA_COMMANDS\", fields: {\"granted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"com.android.gpstest.osmdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_SET|REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }".to_string() },
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"com.android.gpstest.osmdroid\" was manually installed"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_LOCATION_EXTRA_COMMANDS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has requested permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has install permission Permission { name: \"android.permission.ACCESS_LOCATION_EXTRA_COMMANDS\", fields: {\"granted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_FINE_LOCATION\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"com.android.gpstest.osmdroid\" has runtime permission Permission { name: \"android.permission.ACCESS_COARSE_LOCATION\", fields: {\"flags\": \"[ USER_SET|REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }"),
         ]);
     }
 
@@ -217,13 +752,136 @@ mod tests {
         let pkginfo = parsers::package::parse_output(data, "org.jitsi.meet").unwrap();
         let sus = pkginfo.audit();
         assert_eq!(&sus, &[
-            Suspicion { level: SuspicionLevel::High, description: "Package \"org.jitsi.meet\" was manually installed".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.CAMERA\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.MODIFY_AUDIO_SETTINGS\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has install permission Permission { name: \"android.permission.MODIFY_AUDIO_SETTINGS\", fields: {\"granted\": \"true\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has runtime permission Permission { name: \"android.permission.CAMERA\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }".to_string() },
-            Suspicion { level: SuspicionLevel::Low, description: "Package \"org.jitsi.meet\" has runtime permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }".to_string() },
+            Suspicion::new(SuspicionLevel::High, Kind::Package, "Package \"org.jitsi.meet\" was manually installed"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.CAMERA\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.MODIFY_AUDIO_SETTINGS\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has requested permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has install permission Permission { name: \"android.permission.MODIFY_AUDIO_SETTINGS\", fields: {\"granted\": \"true\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has runtime permission Permission { name: \"android.permission.CAMERA\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"false\"} }"),
+            Suspicion::new(SuspicionLevel::Low, Kind::Package, "Package \"org.jitsi.meet\" has runtime permission Permission { name: \"android.permission.RECORD_AUDIO\", fields: {\"flags\": \"[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]\", \"granted\": \"true\"} }"),
         ]);
     }
+
+    #[test]
+    fn test_audit_permission_combination() {
+        let granted = |name: &str, granted: bool| Permission {
+            name: name.to_string(),
+            fields: [("granted".to_string(), granted.to_string())].into(),
+        };
+        let mut pkginfo = PackageInfo {
+            id: "com.example.tracker".to_string(),
+            install_permissions: vec![granted("android.permission.INTERNET", true)],
+            runtime_permissions: vec![
+                granted("android.permission.ACCESS_FINE_LOCATION", true),
+                granted("android.permission.RECORD_AUDIO", true),
+                granted("android.permission.READ_SMS", false),
+                granted("android.permission.CAMERA", true),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            pkginfo.audit_permission_combination(&[]),
+            Some(Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"com.example.tracker\" has been granted a suspicious combination of permissions (location, microphone, camera): [\"android.permission.ACCESS_FINE_LOCATION\", \"android.permission.RECORD_AUDIO\", \"android.permission.CAMERA\"]"))
+        );
+
+        pkginfo.runtime_permissions.push(granted(
+            "android.permission.ACCESS_BACKGROUND_LOCATION",
+            true,
+        ));
+        assert_eq!(
+            pkginfo
+                .audit_permission_combination(&[])
+                .map(|sus| sus.level),
+            Some(SuspicionLevel::Info)
+        );
+        // the default sms or phone app is expected to have these permissions
+        assert_eq!(
+            pkginfo.audit_permission_combination(&["com.example.tracker".to_string()]),
+            None
+        );
+
+        pkginfo.runtime_permissions.truncate(2);
+        assert_eq!(pkginfo.audit_permission_combination(&[]), None);
+    }
+
+    #[test]
+    fn test_audit_communication_access() {
+        let permission = |name: &str, granted: bool| Permission {
+            name: name.to_string(),
+            fields: [
+                ("granted".to_string(), granted.to_string()),
+                ("flags".to_string(), "[ USER_SET ]".to_string()),
+            ]
+            .into(),
+        };
+        let pkginfo = PackageInfo {
+            id: "com.example.tracker".to_string(),
+            install_permissions: vec![Permission {
+                name: "android.permission.RECEIVE_SMS".to_string(),
+                fields: [("granted".to_string(), "true".to_string())].into(),
+            }],
+            runtime_permissions: vec![
+                permission("android.permission.READ_SMS", false),
+                permission("android.permission.READ_CALL_LOG", true),
+                permission("android.permission.CAMERA", true),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            pkginfo.audit_communication_access(&[]),
+            Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Package,
+                "Package \"com.example.tracker\" can read text messages or call logs but is not the default sms or phone app: android.permission.RECEIVE_SMS (install), android.permission.READ_CALL_LOG (runtime, flags=[ USER_SET ])",
+            ))
+        );
+        assert_eq!(
+            pkginfo.audit_communication_access(&["com.example.tracker".to_string()]),
+            None
+        );
+
+        let data = include_bytes!("../test_data/dumpsys/package/spylive360.txt");
+        let pkginfo = parsers::package::parse_output(data, "com.wifi0").unwrap();
+        assert_eq!(pkginfo.audit_communication_access(&[]), None);
+    }
+
+    #[test]
+    fn test_audit_install_capability() {
+        let mut pkginfo = PackageInfo {
+            id: "com.example.updater".to_string(),
+            requested_permissions: vec![Permission {
+                name: "android.permission.REQUEST_INSTALL_PACKAGES".to_string(),
+                fields: Default::default(),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            pkginfo.audit_install_capability(),
+            Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Package,
+                "Package \"com.example.updater\" has not been installed from an app store and can install other apps: \"android.permission.REQUEST_INSTALL_PACKAGES\"",
+            ))
+        );
+        pkginfo.fields.insert(
+            "installerPackageName".to_string(),
+            "org.fdroid.fdroid".to_string(),
+        );
+        assert_eq!(pkginfo.audit_install_capability(), None);
+
+        let data = include_bytes!("../test_data/dumpsys/package/spylive360.txt");
+        let pkginfo = parsers::package::parse_output(data, "com.wifi0").unwrap();
+        assert_eq!(pkginfo.audit_install_capability(), None);
+    }
+
+    #[test]
+    fn test_last_update_time() {
+        let data = include_bytes!("../test_data/dumpsys/package/spylive360.txt");
+        let pkginfo = parsers::package::parse_output(data, "com.wifi0").unwrap();
+        let time = pkginfo.last_update_time().unwrap();
+        assert_eq!(time, utils::parse_datetime("2021-12-15 17:52:55").unwrap());
+        assert!(time > utils::parse_datetime("2021-12-15").unwrap());
+        assert!(time < utils::parse_datetime("2021-12-15 18:00").unwrap());
+        assert!(utils::parse_datetime("15.12.2021").is_err());
+    }
 }
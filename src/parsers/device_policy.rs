@@ -0,0 +1,171 @@
+use crate::errors::*;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Default)]
+pub struct DevicePolicy {
+    pub device_owner: Option<Owner>,
+    pub profile_owners: Vec<Owner>,
+    pub admins: Vec<DeviceAdmin>,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct Owner {
+    pub user: u32,
+    pub package: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DeviceAdmin {
+    pub user: u32,
+    pub component: String,
+}
+
+impl DeviceAdmin {
+    pub fn package(&self) -> &str {
+        self.component
+            .split_once('/')
+            .map(|(package, _)| package)
+            .unwrap_or(&self.component)
+    }
+}
+
+enum Section {
+    DeviceOwner(Owner),
+    ProfileOwner(Owner),
+    Admins(u32),
+    Other,
+}
+
+impl DevicePolicy {
+    fn close_section(&mut self, section: Section) {
+        match section {
+            Section::DeviceOwner(owner) if !owner.package.is_empty() => {
+                self.device_owner = Some(owner)
+            }
+            Section::ProfileOwner(owner) if !owner.package.is_empty() => {
+                self.profile_owners.push(owner)
+            }
+            _ => (),
+        }
+    }
+}
+
+fn parse_user(s: &str) -> Result<u32> {
+    let user = s
+        .split([',', ')'])
+        .next()
+        .context("Missing user id")?
+        .parse()
+        .with_context(|| anyhow!("Failed to parse user id: {s:?}"))?;
+    Ok(user)
+}
+
+impl FromStr for DevicePolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut out = DevicePolicy::default();
+        let mut section = Section::Other;
+
+        for line in s.lines() {
+            let indent = line.chars().take_while(|ch| *ch == ' ').count();
+            let line = line.trim();
+
+            match indent {
+                2 => {
+                    let next = if line == "Device Owner:" {
+                        Section::DeviceOwner(Owner::default())
+                    } else if let Some(user) = line.strip_prefix("Profile Owner (User ") {
+                        Section::ProfileOwner(Owner {
+                            user: parse_user(user)?,
+                            ..Default::default()
+                        })
+                    } else if let Some(user) = line.strip_prefix("Enabled Device Admins (User ") {
+                        Section::Admins(parse_user(user)?)
+                    } else {
+                        Section::Other
+                    };
+                    out.close_section(std::mem::replace(&mut section, next));
+                }
+                4 => match &mut section {
+                    Section::DeviceOwner(owner) | Section::ProfileOwner(owner) => {
+                        if let Some(package) = line.strip_prefix("package=") {
+                            owner.package = package.to_string();
+                        } else if let Some(user) = line.strip_prefix("User ID: ") {
+                            owner.user = parse_user(user)?;
+                        }
+                    }
+                    Section::Admins(user) => {
+                        if let Some(component) = line.strip_suffix(':') {
+                            debug!("Found device admin for user {user}: {component:?}");
+                            out.admins.push(DeviceAdmin {
+                                user: *user,
+                                component: component.to_string(),
+                            });
+                        }
+                    }
+                    Section::Other => (),
+                },
+                _ => (),
+            }
+        }
+        out.close_section(section);
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plain() {
+        let data = include_str!("../../test_data/dumpsys/device_policy/plain.txt");
+        let policy = data.parse::<DevicePolicy>().unwrap();
+        assert_eq!(
+            policy,
+            DevicePolicy {
+                device_owner: None,
+                profile_owners: vec![],
+                admins: vec![DeviceAdmin {
+                    user: 0,
+                    component: "com.google.android.gms/com.google.android.gms.mdm.receivers.MdmDeviceAdminReceiver".to_string(),
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_spyware() {
+        let data = include_str!("../../test_data/dumpsys/device_policy/spyware.txt");
+        let policy = data.parse::<DevicePolicy>().unwrap();
+        assert_eq!(
+            policy,
+            DevicePolicy {
+                device_owner: Some(Owner {
+                    user: 0,
+                    package: "com.wifi0".to_string(),
+                }),
+                profile_owners: vec![Owner {
+                    user: 10,
+                    package: "com.example.mdm".to_string(),
+                }],
+                admins: vec![
+                    DeviceAdmin {
+                        user: 0,
+                        component: "com.wifi0/com.wifi0.AdminReceiver".to_string(),
+                    },
+                    DeviceAdmin {
+                        user: 0,
+                        component: "com.google.android.gms/com.google.android.gms.mdm.receivers.MdmDeviceAdminReceiver".to_string(),
+                    },
+                    DeviceAdmin {
+                        user: 10,
+                        component: "com.example.mdm/com.example.mdm.ProfileReceiver".to_string(),
+                    },
+                ],
+            }
+        );
+    }
+}
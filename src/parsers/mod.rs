@@ -1,2 +1,3 @@
 pub mod accessibility;
+pub mod device_policy;
 pub mod package;
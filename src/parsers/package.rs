@@ -1,4 +1,5 @@
 use crate::errors::*;
+use crate::utils;
 use bstr::ByteSlice;
 use std::collections::BTreeMap;
 use std::str;
@@ -11,6 +12,8 @@ pub struct PackageInfo {
     pub requested_permissions: Vec<Permission>,
     pub install_permissions: Vec<Permission>,
     pub runtime_permissions: Vec<Permission>,
+    /// number of lines in the output that were not valid UTF-8
+    pub invalid_utf8_lines: usize,
 }
 
 #[derive(Debug, PartialEq, Default)]
@@ -55,7 +58,10 @@ pub fn parse_output(output: &[u8], package: &str) -> Result<PackageInfo> {
 
     let mut indent = 0;
     for line in output.lines() {
-        let line = String::from_utf8_lossy(line);
+        let (line, invalid_utf8) = utils::decode_lossy(line);
+        if invalid_utf8 {
+            info.invalid_utf8_lines += 1;
+        }
         let trimmed_line = line.trim();
 
         match count_whitespace_prefix(&line) {
@@ -223,6 +229,7 @@ mod tests {
                 "android.permission.ACCESS_BACKGROUND_LOCATION: granted=false, flags=[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]",
                 "android.permission.ACCESS_MEDIA_LOCATION: granted=false, flags=[ USER_FIXED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
             ].into_iter().map(|s| s.parse().unwrap()).collect(),
+            invalid_utf8_lines: 0,
         });
     }
 
@@ -314,6 +321,7 @@ mod tests {
                 "android.permission.GET_ACCOUNTS: granted=true, flags=[ GRANTED_BY_DEFAULT|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
                 "android.permission.READ_CONTACTS: granted=true, flags=[ GRANTED_BY_DEFAULT|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
             ].into_iter().map(|s| s.parse().unwrap()).collect(),
+            invalid_utf8_lines: 0,
         });
     }
 
@@ -393,6 +401,7 @@ mod tests {
                 "android.permission.ACCESS_BACKGROUND_LOCATION: granted=false, flags=[ REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]",
                 "android.permission.ACCESS_MEDIA_LOCATION: granted=false, flags=[ REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
             ].into_iter().map(|s| s.parse().unwrap()).collect(),
+            invalid_utf8_lines: 0,
         });
     }
 
@@ -451,6 +460,7 @@ mod tests {
                 "android.permission.ACCESS_COARSE_LOCATION: granted=true, flags=[ USER_SET|REVOKE_WHEN_REQUESTED|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
                 "android.permission.WRITE_EXTERNAL_STORAGE: granted=false, flags=[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED|RESTRICTION_INSTALLER_EXEMPT]",
             ].into_iter().map(|s| s.parse().unwrap()).collect(),
+            invalid_utf8_lines: 0,
         });
     }
 
@@ -520,6 +530,7 @@ mod tests {
                 "android.permission.WRITE_CALENDAR: granted=false, flags=[ USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
                 "android.permission.RECORD_AUDIO: granted=true, flags=[ USER_SET|USER_SENSITIVE_WHEN_GRANTED|USER_SENSITIVE_WHEN_DENIED]",
             ].into_iter().map(|s| s.parse().unwrap()).collect(),
+            invalid_utf8_lines: 0,
         });
     }
 
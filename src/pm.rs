@@ -1,36 +1,296 @@
 use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::shell::Shell;
+use crate::utils;
 use bstr::ByteSlice;
-use forensic_adb::Device;
+use std::collections::HashSet;
 use std::str;
 
-const CMD: &str = "pm list packages";
+const CMD: &str = "pm list packages -f -i";
+const SYSTEM_PACKAGES_CMD: &str = "pm list packages -s";
+const USER_PACKAGES_CMD: &str = "pm list packages -3";
+const USERS_CMD: &str = "pm list users";
+/// The owner of the device, `pm` defaults to this user
+pub const PRIMARY_USER: u32 = 0;
+const ROLE_HOLDERS_CMD: &str = "cmd role get-role-holders";
+/// The roles of the default sms and phone app, they are expected to access messages and calls
+pub const COMMUNICATION_ROLES: &[&str] = &["android.app.role.SMS", "android.app.role.DIALER"];
+const LAUNCHER_CMD: &str =
+    "cmd package query-activities --brief -a android.intent.action.MAIN -c android.intent.category.LAUNCHER";
 
-#[derive(Debug, PartialEq)]
+/// Partitions that only contain apps shipped with the firmware
+const SYSTEM_PATHS: &[&str] = &[
+    "/system/",
+    "/system_ext/",
+    "/product/",
+    "/vendor/",
+    "/odm/",
+    "/apex/",
+];
+
+/// App stores other than google play that are commonly used on regular devices
+const KNOWN_APP_STORES: &[&str] = &[
+    "com.amazon.venezia",
+    "com.huawei.appmarket",
+    "com.sec.android.app.samsungapps",
+    "com.xiaomi.market",
+    "org.fdroid.fdroid",
+];
+
+/// Whether apps installed by this package come from an app store
+pub fn is_app_store(installer: &str) -> bool {
+    installer == "com.android.vending" || KNOWN_APP_STORES.contains(&installer)
+}
+
+#[derive(Debug, PartialEq, Default)]
 pub struct Apk {
     pub id: String,
+    pub path: Option<String>,
+    pub installer: Option<String>,
+    /// the line describing this package was not valid UTF-8
+    pub invalid_utf8: bool,
+    /// the secondary user this app is installed for, `None` for the primary user
+    pub user: Option<u32>,
+}
+
+impl Apk {
+    /// If the app is part of the firmware image
+    pub fn is_system(&self) -> bool {
+        self.path
+            .as_deref()
+            .map(|path| SYSTEM_PATHS.iter().any(|prefix| path.starts_with(prefix)))
+            .unwrap_or(false)
+    }
+
+    /// Mention the user in findings about apps of secondary users
+    pub fn tag(&self, mut sus: Suspicion) -> Suspicion {
+        if let Some(user) = self.user {
+            sus.description = format!("{} (user {user})", sus.description);
+            sus.user = Some(user);
+        }
+        sus
+    }
+
+    /// Package ids are limited to ascii letters, digits, underscores and dots
+    pub fn audit_name(&self) -> Option<Suspicion> {
+        if self.invalid_utf8 {
+            Some(Suspicion::new(
+                SuspicionLevel::Medium,
+                Kind::Package,
+                format!("Package {:?} has a name with invalid UTF-8", self.id),
+            ))
+        } else if !self
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            Some(Suspicion::new(
+                SuspicionLevel::Medium,
+                Kind::Package,
+                format!("Package {:?} has a name with unusual characters", self.id),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Advisories about where the app has been installed from
+    pub fn audit_installer(&self) -> Option<Suspicion> {
+        // without the apk path it's unknown if this is a system app
+        if self.path.is_none() || self.is_system() {
+            return None;
+        }
+        match self.installer.as_deref() {
+            None => Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Package,
+                format!(
+                    "Package {:?} has no installer, it has likely been sideloaded",
+                    self.id
+                ),
+            )),
+            Some(installer) if KNOWN_APP_STORES.contains(&installer) => Some(Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Package,
+                format!(
+                    "Package {:?} has been installed from a third-party app store: {:?}",
+                    self.id, installer
+                ),
+            )),
+            // unknown installers are reported by the package audit
+            Some(_) => None,
+        }
+    }
+
+    /// Apps without a launcher icon don't show up in the app drawer
+    pub fn audit_launcher(&self, launchable: &HashSet<String>) -> Option<Suspicion> {
+        if self.path.is_none() || self.is_system() || launchable.contains(&self.id) {
+            return None;
+        }
+        Some(Suspicion::new(
+            SuspicionLevel::Info,
+            Kind::Package,
+            format!(
+                "Package {:?} has no launcher icon, it's hidden from the app drawer",
+                self.id
+            ),
+        ))
+    }
+}
+
+/// The packages that have an activity that can be started from the launcher
+pub async fn list_launchable(device: &dyn Shell) -> Result<HashSet<String>> {
+    let output = device
+        .exec(LAUNCHER_CMD)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", LAUNCHER_CMD))?;
+    Ok(parse_launchable(&output))
+}
+
+fn parse_launchable(output: &[u8]) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = str::from_utf8(line).ok()?.trim();
+            // activities are listed as `<package>/<class>`
+            let (package, _class) = line.split_once('/')?;
+            if package.is_empty() || package.contains(char::is_whitespace) {
+                return None;
+            }
+            Some(package.to_string())
+        })
+        .collect()
+}
+
+/// The packages that currently hold a role, eg. the default sms app
+pub async fn list_role_holders(device: &dyn Shell, role: &str) -> Result<Vec<String>> {
+    let cmd = format!("{ROLE_HOLDERS_CMD} {role}");
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    let output = str::from_utf8(&output).context("Role holders are not valid UTF-8")?;
+    if output.contains("Unknown command") || output.contains("Exception") {
+        bail!("Failed to query role holders: {:?}", output.trim());
+    }
+    Ok(parse_role_holders(output))
+}
+
+fn parse_role_holders(output: &str) -> Vec<String> {
+    output
+        .split([';', '\n'])
+        .map(str::trim)
+        .filter(|package| !package.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
 }
 
-pub async fn list_packages(device: &Device) -> Result<Vec<Apk>> {
+/// All users of the device, including work profiles
+pub async fn list_users(device: &dyn Shell) -> Result<Vec<User>> {
     let output = device
-        .execute_host_exec_out_command(CMD)
+        .exec(USERS_CMD)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", USERS_CMD))?;
+    Ok(parse_users(&output))
+}
+
+fn parse_users(output: &[u8]) -> Vec<User> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = str::from_utf8(line).ok()?;
+            // users are listed as `UserInfo{<id>:<name>:<flags>} running`
+            let (_, info) = line.split_once("UserInfo{")?;
+            let (info, _) = info.split_once('}')?;
+            let (id, info) = info.split_once(':')?;
+            let name = info.rsplit_once(':').map_or(info, |(name, _flags)| name);
+            Some(User {
+                id: id.parse().ok()?,
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The packages that are installed for a secondary user
+pub async fn list_packages_for_user(device: &dyn Shell, user: u32) -> Result<Vec<Apk>> {
+    let cmd = format!("{CMD} --user {user}");
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    let mut pkgs = parse_output(&output)?;
+    for pkg in &mut pkgs {
+        pkg.user = Some(user);
+    }
+    Ok(pkgs)
+}
+
+pub async fn list_packages(device: &dyn Shell) -> Result<Vec<Apk>> {
+    let output = device
+        .exec(CMD)
         .await
         .with_context(|| anyhow!("Failed to run: {:?}", CMD))?;
     parse_output(&output)
 }
 
+/// The number of system apps and of apps that have been installed by the user
+pub async fn count_packages(device: &dyn Shell) -> Result<(usize, usize)> {
+    let mut counts = [0; 2];
+    for (count, cmd) in counts
+        .iter_mut()
+        .zip([SYSTEM_PACKAGES_CMD, USER_PACKAGES_CMD])
+    {
+        let output = device
+            .exec(cmd)
+            .await
+            .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+        *count = parse_output(&output)?.len();
+    }
+    Ok((counts[0], counts[1]))
+}
+
 fn parse_output(output: &[u8]) -> Result<Vec<Apk>> {
     let mut pkgs = Vec::new();
     for line in output.lines() {
         if line.is_empty() {
             continue;
         }
-        let line = String::from_utf8_lossy(line);
+        let (line, invalid_utf8) = utils::decode_lossy(line);
+        if invalid_utf8 {
+            warn!("Package list contains invalid UTF-8: {line:?}");
+        }
+
+        if let Some(line) = line.strip_prefix("package:") {
+            let mut fields = line.split_whitespace();
+            let Some(package) = fields.next() else {
+                continue;
+            };
+
+            // with `-f` the package is prefixed with the path of its apk
+            let (path, id) = match package.rsplit_once('=') {
+                Some((path, id)) => (Some(path.to_string()), id),
+                None => (None, package),
+            };
 
-        if let Some(package) = line.strip_prefix("package:") {
-            debug!("discovered package={:?}", package);
+            let installer = fields
+                .find_map(|field| field.strip_prefix("installer="))
+                .filter(|installer| *installer != "null")
+                .map(String::from);
 
+            debug!("discovered package={id:?}, path={path:?}, installer={installer:?}");
             pkgs.push(Apk {
-                id: package.to_string(),
+                id: id.to_string(),
+                path,
+                installer,
+                invalid_utf8,
+                user: None,
             });
         }
     }
@@ -58,27 +318,204 @@ package:org.lineageos.overlay.customization.blacktheme
             &pkgs,
             &[
                 Apk {
-                    id: "org.jitsi.meet".to_string()
+                    id: "org.jitsi.meet".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "org.lineageos.overlay.accent.black".to_string()
+                    id: "org.lineageos.overlay.accent.black".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "com.android.cts.priv.ctsshim".to_string()
+                    id: "com.android.cts.priv.ctsshim".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "org.lineageos.overlay.accent.brown".to_string()
+                    id: "org.lineageos.overlay.accent.brown".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "org.lineageos.overlay.accent.green".to_string()
+                    id: "org.lineageos.overlay.accent.green".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "com.android.internal.display.cutout.emulation.corner".to_string()
+                    id: "com.android.internal.display.cutout.emulation.corner".to_string(),
+                    ..Default::default()
                 },
                 Apk {
-                    id: "org.lineageos.overlay.customization.blacktheme".to_string()
+                    id: "org.lineageos.overlay.customization.blacktheme".to_string(),
+                    ..Default::default()
                 }
             ]
         );
     }
+
+    #[test]
+    pub fn test_parse_output_with_installers() {
+        let data = b"package:/data/app/~~yOGa2vl0xQ==/com.wifi0-Vn9Pp8g==/base.apk=com.wifi0  installer=null
+package:/product/app/Chrome/Chrome.apk=com.android.chrome  installer=com.android.vending
+package:/system/priv-app/Settings/Settings.apk=com.android.settings  installer=null
+package:/data/app/~~c3Jr==/org.jitsi.meet-Rc1==/base.apk=org.jitsi.meet  installer=org.fdroid.fdroid
+";
+
+        let pkgs = parse_output(data).unwrap();
+        assert_eq!(
+            &pkgs,
+            &[
+                Apk {
+                    id: "com.wifi0".to_string(),
+                    path: Some("/data/app/~~yOGa2vl0xQ==/com.wifi0-Vn9Pp8g==/base.apk".to_string()),
+                    installer: None,
+                    invalid_utf8: false,
+                    user: None,
+                },
+                Apk {
+                    id: "com.android.chrome".to_string(),
+                    path: Some("/product/app/Chrome/Chrome.apk".to_string()),
+                    installer: Some("com.android.vending".to_string()),
+                    invalid_utf8: false,
+                    user: None,
+                },
+                Apk {
+                    id: "com.android.settings".to_string(),
+                    path: Some("/system/priv-app/Settings/Settings.apk".to_string()),
+                    installer: None,
+                    invalid_utf8: false,
+                    user: None,
+                },
+                Apk {
+                    id: "org.jitsi.meet".to_string(),
+                    path: Some("/data/app/~~c3Jr==/org.jitsi.meet-Rc1==/base.apk".to_string()),
+                    installer: Some("org.fdroid.fdroid".to_string()),
+                    invalid_utf8: false,
+                    user: None,
+                },
+            ]
+        );
+
+        let sus = pkgs
+            .iter()
+            .filter_map(Apk::audit_installer)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            &sus,
+            &[
+                Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"com.wifi0\" has no installer, it has likely been sideloaded"),
+                Suspicion::new(SuspicionLevel::Info, Kind::Package, "Package \"org.jitsi.meet\" has been installed from a third-party app store: \"org.fdroid.fdroid\""),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_output_invalid_utf8() {
+        let data = b"package:/data/app/base.apk=com.wifi\xff  installer=null
+package:/data/app/base.apk=com.\xd0\xb0pple  installer=null
+package:/data/app/base.apk=com.wifi0  installer=null
+";
+        let pkgs = parse_output(data).unwrap();
+        assert_eq!(pkgs[0].id, "com.wifi\u{FFFD}");
+        assert!(pkgs[0].invalid_utf8);
+
+        let sus = pkgs.iter().filter_map(Apk::audit_name).collect::<Vec<_>>();
+        assert_eq!(
+            &sus,
+            &[
+                Suspicion::new(
+                    SuspicionLevel::Medium,
+                    Kind::Package,
+                    "Package \"com.wifi\u{FFFD}\" has a name with invalid UTF-8"
+                ),
+                Suspicion::new(
+                    SuspicionLevel::Medium,
+                    Kind::Package,
+                    "Package \"com.\u{430}pple\" has a name with unusual characters"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_audit_launcher() {
+        let launchable = parse_launchable(
+            b"3 activities found:
+  Activity #0:
+    priority=0 preferredOrder=0 match=0x108000 specificIndex=-1 isDefault=false
+    com.android.settings/.Settings
+  Activity #1:
+    org.jitsi.meet/.MainActivity
+",
+        );
+        assert_eq!(
+            launchable,
+            HashSet::from([
+                "com.android.settings".to_string(),
+                "org.jitsi.meet".to_string()
+            ])
+        );
+
+        let data = b"package:/data/app/~~yOGa2vl0xQ==/com.wifi0-Vn9Pp8g==/base.apk=com.wifi0  installer=null
+package:/system/priv-app/Telephony/Telephony.apk=com.android.phone  installer=null
+package:/data/app/~~c3Jr==/org.jitsi.meet-Rc1==/base.apk=org.jitsi.meet  installer=org.fdroid.fdroid
+";
+        let pkgs = parse_output(data).unwrap();
+        let sus = pkgs
+            .iter()
+            .filter_map(|pkg| pkg.audit_launcher(&launchable))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            &sus,
+            &[Suspicion::new(
+                SuspicionLevel::Info,
+                Kind::Package,
+                "Package \"com.wifi0\" has no launcher icon, it's hidden from the app drawer"
+            )]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_users() {
+        let users = parse_users(
+            b"Users:
+\tUserInfo{0:Owner:c13} running
+\tUserInfo{10:Work profile:1030} running
+\tUserInfo{11:Guest: Visitor:814}
+",
+        );
+        assert_eq!(
+            users,
+            &[
+                User {
+                    id: 0,
+                    name: "Owner".to_string(),
+                },
+                User {
+                    id: 10,
+                    name: "Work profile".to_string(),
+                },
+                User {
+                    id: 11,
+                    name: "Guest: Visitor".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(
+            parse_role_holders("com.google.android.apps.messaging\n"),
+            &["com.google.android.apps.messaging"]
+        );
+        assert_eq!(parse_role_holders("a;b\n"), &["a", "b"]);
+        assert!(parse_role_holders("\n").is_empty());
+
+        let apk = Apk {
+            id: "com.wifi0".to_string(),
+            user: Some(10),
+            ..Default::default()
+        };
+        let sus = apk.tag(Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware",
+        ));
+        assert_eq!(sus.description, "Found known stalkerware (user 10)");
+        assert_eq!(sus.user, Some(10));
+    }
 }
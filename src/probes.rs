@@ -0,0 +1,231 @@
+//! Additional shell commands from the config, with patterns that turn their output into findings
+use crate::checks::Check;
+use crate::errors::*;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+
+/// Commands that only read from the device, by their leading words
+const READ_ONLY_COMMANDS: &[&[&str]] = &[
+    &["getprop"],
+    &["cat"],
+    &["ls"],
+    &["stat"],
+    &["id"],
+    &["ps"],
+    &["df"],
+    &["getenforce"],
+    &["dumpsys"],
+    &["settings", "get"],
+    &["settings", "list"],
+    &["pm", "list"],
+    &["pm", "path"],
+    &["pm", "dump"],
+    &["appops", "get"],
+    &["service", "list"],
+];
+/// Arguments of dumpsys that only change what is printed, some services also accept commands like `set`
+const DUMPSYS_ARGS: &[&str] = &["-a", "-c", "--short", "--checkin"];
+/// Characters that would allow chaining commands or redirecting output
+const SHELL_METACHARACTERS: &[char] = &[';', '|', '&', '>', '<', '`', '$', '(', ')', '\n', '\r'];
+
+fn default_level() -> SuspicionLevel {
+    SuspicionLevel::Low
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Probe {
+    pub name: String,
+    /// A read-only shell command, eg. `getprop ro.debuggable`
+    pub command: String,
+    /// A regular expression that is searched for in every line of the output
+    pub pattern: String,
+    #[serde(default = "default_level")]
+    pub level: SuspicionLevel,
+    #[serde(default)]
+    pub kind: Kind,
+    /// Shown instead of the name of the probe when it matches
+    pub description: Option<String>,
+}
+
+impl Probe {
+    /// Make sure the command can't modify the device and the pattern is valid
+    pub fn validate(&self) -> Result<Regex> {
+        if let Some(c) = self
+            .command
+            .chars()
+            .find(|c| SHELL_METACHARACTERS.contains(c))
+        {
+            bail!("Command of probe {:?} contains {c:?}, only a single command without redirection is allowed", self.name);
+        }
+        let words = self.command.split_whitespace().collect::<Vec<_>>();
+        if !READ_ONLY_COMMANDS
+            .iter()
+            .any(|prefix| words.starts_with(prefix))
+        {
+            bail!(
+                "Command of probe {:?} is not known to be read-only: {:?}",
+                self.name,
+                self.command
+            );
+        }
+        if let ["dumpsys", args @ ..] = words.as_slice() {
+            validate_dumpsys(args).with_context(|| {
+                anyhow!(
+                    "Command of probe {:?} is not known to be read-only: {:?}",
+                    self.name,
+                    self.command
+                )
+            })?;
+        }
+        Regex::new(&self.pattern)
+            .with_context(|| anyhow!("Invalid pattern for probe {:?}", self.name))
+    }
+
+    /// The finding for the first line of output that matches the pattern
+    pub fn matches(&self, pattern: &Regex, output: &str) -> Option<Suspicion> {
+        let line = output.lines().find(|line| pattern.is_match(line))?;
+        let description = match &self.description {
+            Some(description) => description.clone(),
+            None => format!("Probe {:?} has matched", self.name),
+        };
+        Some(Suspicion::new(
+            self.level,
+            self.kind,
+            format!("{description}: {:?}", line.trim()),
+        ))
+    }
+}
+
+/// Only allow `dumpsys -l` or the dump of a single service, with arguments that don't modify it
+fn validate_dumpsys(args: &[&str]) -> Result<()> {
+    match args {
+        ["-l"] => Ok(()),
+        [service, args @ ..] => {
+            if service.starts_with('-') {
+                bail!("Expected the name of a service, found {service:?}");
+            }
+            if let Some(arg) = args.iter().find(|arg| !DUMPSYS_ARGS.contains(arg)) {
+                bail!("Argument {arg:?} of dumpsys is not allowed, only {DUMPSYS_ARGS:?}");
+            }
+            Ok(())
+        }
+        [] => bail!("The name of a service to dump is required"),
+    }
+}
+
+pub struct ProbesCheck;
+
+#[async_trait]
+impl Check for ProbesCheck {
+    fn id(&self) -> &'static str {
+        "probes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run the additional read-only commands of the config and match their output against its patterns"
+    }
+
+    fn enabled(&self, settings: &scan::Settings) -> bool {
+        !settings.probes.is_empty()
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        _rules: &Rules,
+        settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        for probe in &settings.probes {
+            let pattern = match probe.validate() {
+                Ok(pattern) => pattern,
+                Err(err) => {
+                    report.warning(format!("Skipping probe: {err:#}")).await?;
+                    continue;
+                }
+            };
+            info!("Running probe {:?}: {:?}", probe.name, probe.command);
+            let output = match device.exec(&probe.command).await {
+                Ok(output) => output,
+                Err(err) => {
+                    report
+                        .warning(format!("Probe {:?} has failed: {err:#}", probe.name))
+                        .await?;
+                    continue;
+                }
+            };
+            if let Some(sus) = probe.matches(&pattern, &String::from_utf8_lossy(&output)) {
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.sus(sus).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(command: &str, pattern: &str) -> Probe {
+        Probe {
+            name: "debuggable".to_string(),
+            command: command.to_string(),
+            pattern: pattern.to_string(),
+            level: SuspicionLevel::Medium,
+            kind: Kind::Setting,
+            description: Some("Build is debuggable".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(probe("getprop ro.debuggable", "^1$").validate().is_ok());
+        assert!(probe("settings get global adb_enabled", "1")
+            .validate()
+            .is_ok());
+        assert!(probe("settings put global adb_enabled 0", "1")
+            .validate()
+            .is_err());
+        assert!(probe("getprop; reboot", "1").validate().is_err());
+        assert!(probe("cat /proc/version > /sdcard/x", "1")
+            .validate()
+            .is_err());
+        assert!(probe("rm -rf /sdcard", "1").validate().is_err());
+        assert!(probe("getprop ro.debuggable", "(").validate().is_err());
+
+        assert!(probe("dumpsys -l", "vpn").validate().is_ok());
+        assert!(probe("dumpsys device_policy", "owner").validate().is_ok());
+        assert!(probe("dumpsys battery --checkin", "1").validate().is_ok());
+        assert!(probe("dumpsys", "1").validate().is_err());
+        assert!(probe("dumpsys battery set level 1", "1")
+            .validate()
+            .is_err());
+        assert!(probe("dumpsys battery unplug", "1").validate().is_err());
+        assert!(probe("dumpsys deviceidle force-idle", "1")
+            .validate()
+            .is_err());
+        assert!(probe("dumpsys -t 60 battery", "1").validate().is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let probe = probe("getprop ro.debuggable", "^1$");
+        let pattern = probe.validate().unwrap();
+        assert_eq!(probe.matches(&pattern, "0\n"), None);
+        assert_eq!(
+            probe.matches(&pattern, "1\n"),
+            Some(Suspicion::new(
+                SuspicionLevel::Medium,
+                Kind::Setting,
+                "Build is debuggable: \"1\""
+            ))
+        );
+    }
+}
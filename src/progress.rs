@@ -0,0 +1,356 @@
+use crate::errors::*;
+use crate::ioc::Suspicion;
+use serde::Serialize;
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::{self, AsyncWrite, AsyncWriteExt};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use tokio::sync::mpsc::{self, error::TrySendError};
+#[cfg(unix)]
+use tokio::sync::Mutex;
+#[cfg(unix)]
+use tokio::task::JoinHandle;
+
+/// A single line of the `--json-progress` stream
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    Started {
+        serial: &'a str,
+    },
+    /// A check has started, or how many of its items have been inspected, eg. apps
+    Progress {
+        check: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        done: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total: Option<usize>,
+    },
+    Suspicion {
+        sus: &'a Suspicion,
+    },
+    App {
+        name: &'a str,
+        sus: &'a Suspicion,
+    },
+    Warning {
+        message: &'a str,
+    },
+    Error {
+        message: String,
+    },
+    Ended,
+}
+
+/// Writes scan events as newline-delimited json, flushed after every event
+pub struct JsonProgress {
+    out: Output,
+}
+
+enum Output {
+    Writer(Box<dyn AsyncWrite + Unpin + Send>),
+    #[cfg(unix)]
+    Socket(Socket),
+}
+
+/// Events that are queued for a client, a client that falls this far behind is disconnected
+#[cfg(unix)]
+const CLIENT_QUEUE_SIZE: usize = 1024;
+
+/// Clients connected to the socket, new clients are sent all previous events first
+#[cfg(unix)]
+#[derive(Default)]
+struct Clients {
+    history: Vec<u8>,
+    queues: Vec<mpsc::Sender<Arc<[u8]>>>,
+}
+
+#[cfg(unix)]
+struct Socket {
+    path: PathBuf,
+    clients: Arc<Mutex<Clients>>,
+    accept: JoinHandle<()>,
+}
+
+#[cfg(unix)]
+impl Socket {
+    /// The socket is only accessible by the current user and the given group, other users are also refused when connecting
+    async fn bind(path: &Path, group: Option<u32>) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // the path may be a socket of another scan that is still running
+        if tokio::fs::symlink_metadata(path).await.is_ok() {
+            bail!("Refusing to replace existing file, remove it if it's left over from a previous scan: {path:?}");
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| anyhow!("Failed to listen on socket: {path:?}"))?;
+        let mode = if let Some(gid) = group {
+            std::os::unix::fs::chown(path, None, Some(gid))
+                .with_context(|| anyhow!("Failed to change group of socket to {gid}: {path:?}"))?;
+            0o660
+        } else {
+            0o600
+        };
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .await
+            .with_context(|| anyhow!("Failed to restrict permissions of socket: {path:?}"))?;
+        info!("Sending scan events to clients of socket: {path:?}");
+
+        let clients = Arc::new(Mutex::new(Clients::default()));
+        let accept = tokio::spawn({
+            let clients = clients.clone();
+            async move {
+                let uid = unsafe { libc::getuid() };
+                loop {
+                    let stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            warn!("Failed to accept client on event socket: {err:#}");
+                            continue;
+                        }
+                    };
+                    match stream.peer_cred() {
+                        Ok(cred)
+                            if cred.uid() == uid || group.is_some_and(|gid| cred.gid() == gid) => {}
+                        _ => {
+                            warn!("Refusing client of event socket that belongs to another user");
+                            continue;
+                        }
+                    }
+                    let (tx, rx) = mpsc::channel(CLIENT_QUEUE_SIZE);
+                    let mut clients = clients.lock().await;
+                    tokio::spawn(write_events(stream, clients.history.clone(), rx));
+                    debug!("Client has connected to event socket");
+                    clients.queues.push(tx);
+                }
+            }
+        });
+
+        Ok(Socket {
+            path: path.to_owned(),
+            clients,
+            accept,
+        })
+    }
+
+    async fn send(&self, buf: &[u8]) {
+        let buf = Arc::<[u8]>::from(buf);
+        let mut clients = self.clients.lock().await;
+        clients.history.extend(buf.iter());
+        clients.queues.retain(|tx| match tx.try_send(buf.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                warn!("Client of event socket is not reading, disconnecting it");
+                false
+            }
+            Err(TrySendError::Closed(_)) => {
+                debug!("Client has disconnected from event socket");
+                false
+            }
+        });
+    }
+}
+
+/// Write the previous events and then everything that is queued, a client that stops reading only blocks itself
+#[cfg(unix)]
+async fn write_events(mut stream: UnixStream, history: Vec<u8>, mut rx: mpsc::Receiver<Arc<[u8]>>) {
+    if stream.write_all(&history).await.is_err() {
+        return;
+    }
+    while let Some(buf) = rx.recv().await {
+        if stream.write_all(&buf).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Socket {
+    fn drop(&mut self) {
+        self.accept.abort();
+        // the clients are disconnected once their queued events have been written
+        if let Ok(mut clients) = self.clients.try_lock() {
+            clients.queues.clear();
+        }
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove socket {:?}: {err:#}", self.path);
+        }
+    }
+}
+
+impl JsonProgress {
+    /// Open the given file for writing, `-` writes to stdout
+    pub async fn open(path: &Path) -> Result<Self> {
+        let out: Box<dyn AsyncWrite + Unpin + Send> = if path == Path::new("-") {
+            Box::new(io::stdout())
+        } else {
+            let file = File::create(path)
+                .await
+                .with_context(|| anyhow!("Failed to open progress file: {path:?}"))?;
+            Box::new(file)
+        };
+        Ok(JsonProgress {
+            out: Output::Writer(out),
+        })
+    }
+
+    /// Listen on a unix domain socket and stream the events to every client of the current user or the group
+    pub async fn listen(path: &Path, group: Option<u32>) -> Result<Self> {
+        #[cfg(unix)]
+        {
+            let socket = Socket::bind(path, group).await?;
+            Ok(JsonProgress {
+                out: Output::Socket(socket),
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = group;
+            bail!("Unix domain sockets are not supported on this platform: {path:?}")
+        }
+    }
+
+    #[cfg(all(test, unix))]
+    async fn clients(&self) -> usize {
+        match &self.out {
+            Output::Socket(socket) => socket.clients.lock().await.queues.len(),
+            Output::Writer(_) => 0,
+        }
+    }
+
+    pub async fn send(&mut self, event: &Event<'_>) -> Result<()> {
+        let mut buf = serde_json::to_vec(event)?;
+        buf.push(b'\n');
+        match &mut self.out {
+            Output::Writer(out) => {
+                out.write_all(&buf)
+                    .await
+                    .context("Failed to write progress event")?;
+                out.flush().await?;
+            }
+            #[cfg(unix)]
+            Output::Socket(socket) => socket.send(&buf).await,
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for JsonProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonProgress").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Kind, SuspicionLevel};
+
+    #[test]
+    fn test_serialize_events() {
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Package \"com.wifi0\" is the device owner",
+        );
+        let lines = [
+            Event::Started { serial: "ABC123" },
+            Event::Progress {
+                check: "apps",
+                done: None,
+                total: None,
+            },
+            Event::Progress {
+                check: "apps",
+                done: Some(12),
+                total: Some(192),
+            },
+            Event::App {
+                name: "com.wifi0",
+                sus: &sus,
+            },
+            Event::Error {
+                message: "Failed to run: \"dumpsys -l\"".to_string(),
+            },
+            Event::Ended,
+        ]
+        .iter()
+        .map(|event| serde_json::to_string(event).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            &[
+                r#"{"event":"started","serial":"ABC123"}"#,
+                r#"{"event":"progress","check":"apps"}"#,
+                r#"{"event":"progress","check":"apps","done":12,"total":192}"#,
+                r#"{"event":"app","name":"com.wifi0","sus":{"level":"high","description":"Package \"com.wifi0\" is the device owner","kind":"package"}}"#,
+                r#"{"event":"error","message":"Failed to run: \"dumpsys -l\""}"#,
+                r#"{"event":"ended"}"#,
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_listen() {
+        use tokio::io::AsyncReadExt;
+
+        let path = std::env::temp_dir().join(format!("spytrap-events-{}.sock", std::process::id()));
+        let mut progress = JsonProgress::listen(&path, None).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions();
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&mode) & 0o777,
+            0o600
+        );
+        // the socket of a running scan is never replaced
+        assert!(JsonProgress::listen(&path, None).await.is_err());
+        let mut early = UnixStream::connect(&path).await.unwrap();
+        progress
+            .send(&Event::Started { serial: "ABC123" })
+            .await
+            .unwrap();
+        // connected after the first event, it's replayed
+        let mut late = UnixStream::connect(&path).await.unwrap();
+        while progress.clients().await < 2 {
+            tokio::task::yield_now().await;
+        }
+        progress.send(&Event::Ended).await.unwrap();
+        drop(progress);
+        assert!(!path.exists());
+
+        for client in [&mut early, &mut late] {
+            let mut buf = String::new();
+            client.read_to_string(&mut buf).await.unwrap();
+            assert_eq!(
+                buf,
+                "{\"event\":\"started\",\"serial\":\"ABC123\"}\n{\"event\":\"ended\"}\n"
+            );
+        }
+    }
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_listen_group() {
+        let path =
+            std::env::temp_dir().join(format!("spytrap-events-group-{}.sock", std::process::id()));
+        let gid = unsafe { libc::getgid() };
+        let progress = JsonProgress::listen(&path, Some(gid)).await.unwrap();
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(
+            std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o777,
+            0o660
+        );
+        assert_eq!(std::os::unix::fs::MetadataExt::gid(&metadata), gid);
+        let _client = UnixStream::connect(&path).await.unwrap();
+        while progress.clients().await < 1 {
+            tokio::task::yield_now().await;
+        }
+        drop(progress);
+        assert!(!path.exists());
+    }
+}
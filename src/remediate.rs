@@ -0,0 +1,191 @@
+use crate::errors::*;
+use crate::shell::Shell;
+use crate::utils;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::path::Path;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+/// Actions that modify the device, only available if remediation has been enabled explicitly
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Remediation {
+    Uninstall,
+    Disable,
+}
+
+impl Remediation {
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Remediation::Uninstall => "uninstall",
+            Remediation::Disable => "disable",
+        }
+    }
+
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            Remediation::Uninstall => "uninstalled",
+            Remediation::Disable => "disabled",
+        }
+    }
+
+    /// The command for the app of the given user, `None` is the primary user
+    pub fn command(&self, package: &str, user: Option<u32>) -> String {
+        let package = shell_escape::escape(Cow::Borrowed(package));
+        match (self, user) {
+            (Remediation::Uninstall, None) => format!("pm uninstall {package}"),
+            (Remediation::Uninstall, Some(user)) => format!("pm uninstall --user {user} {package}"),
+            (Remediation::Disable, user) => {
+                format!("pm disable-user --user {} {package}", user.unwrap_or(0))
+            }
+        }
+    }
+}
+
+/// The package as it's shown in prompts, with the user if it's not the primary one
+pub fn label(package: &str, user: Option<u32>) -> String {
+    match user {
+        Some(user) => format!("{package:?} (user {user})"),
+        None => format!("{package:?}"),
+    }
+}
+
+/// A line in the audit log, written for every remediation that has been requested
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AuditEntry<'a> {
+    pub time: i64,
+    pub serial: &'a str,
+    pub action: Remediation,
+    pub package: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<u32>,
+    #[serde(flatten)]
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Outcome {
+    Declined,
+    Succeeded,
+    Failed { error: String },
+}
+
+impl<'a> AuditEntry<'a> {
+    pub fn new(
+        serial: &'a str,
+        action: Remediation,
+        package: &'a str,
+        user: Option<u32>,
+        outcome: Outcome,
+    ) -> Self {
+        AuditEntry {
+            time: utils::now(),
+            serial,
+            action,
+            package,
+            user,
+            outcome,
+        }
+    }
+}
+
+/// Append an entry to the audit log
+pub async fn audit(path: &Path, entry: &AuditEntry<'_>) -> Result<()> {
+    let mut buf = serde_json::to_vec(entry)?;
+    buf.push(b'\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| anyhow!("Failed to open audit log: {path:?}"))?;
+    file.write_all(&buf)
+        .await
+        .with_context(|| anyhow!("Failed to write to audit log: {path:?}"))?;
+    Ok(())
+}
+
+pub async fn run(
+    device: &dyn Shell,
+    action: Remediation,
+    package: &str,
+    user: Option<u32>,
+) -> Result<()> {
+    let cmd = action.command(package, user);
+    info!("Executing {:?}", cmd);
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    check_output(&String::from_utf8_lossy(&output))
+        .with_context(|| anyhow!("Failed to {} package {package:?}", action.verb()))
+}
+
+fn check_output(output: &str) -> Result<()> {
+    let output = output.trim();
+    // `pm disable-user` reports the new state instead of `Success`
+    if output == "Success" || output.ends_with("new state: disabled-user") {
+        Ok(())
+    } else {
+        bail!("Unexpected output from package manager: {output:?}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            Remediation::Uninstall.command("com.wifi0", None),
+            "pm uninstall com.wifi0"
+        );
+        assert_eq!(
+            Remediation::Disable.command("com.wifi0; reboot", None),
+            "pm disable-user --user 0 'com.wifi0; reboot'"
+        );
+        // the app is only installed in the work profile
+        assert_eq!(
+            Remediation::Uninstall.command("com.wifi0", Some(10)),
+            "pm uninstall --user 10 com.wifi0"
+        );
+        assert_eq!(
+            Remediation::Disable.command("com.wifi0", Some(10)),
+            "pm disable-user --user 10 com.wifi0"
+        );
+        assert_eq!(label("com.wifi0", Some(10)), "\"com.wifi0\" (user 10)");
+    }
+
+    #[test]
+    fn test_check_output() {
+        check_output("Success\n").unwrap();
+        check_output("Package com.wifi0 new state: disabled-user\n").unwrap();
+        assert!(check_output("Failure [DELETE_FAILED_INTERNAL_ERROR]\n").is_err());
+    }
+
+    #[test]
+    fn test_serialize_audit_entry() {
+        let mut entry = AuditEntry {
+            time: 1719963254,
+            serial: "ABC123",
+            action: Remediation::Uninstall,
+            package: "com.wifi0",
+            user: None,
+            outcome: Outcome::Declined,
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"time":1719963254,"serial":"ABC123","action":"uninstall","package":"com.wifi0","outcome":"declined"}"#
+        );
+        entry.outcome = Outcome::Failed {
+            error: "Unexpected output".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&entry).unwrap(),
+            r#"{"time":1719963254,"serial":"ABC123","action":"uninstall","package":"com.wifi0","outcome":"failed","error":"Unexpected output"}"#
+        );
+    }
+}
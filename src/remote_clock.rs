@@ -1,12 +1,12 @@
 use crate::errors::*;
+use crate::shell::Shell;
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use forensic_adb::Device;
 
 const DATE_COMMAND: &str = "date -u '+%Y-%m-%d %T %N'";
 
-pub async fn determine(device: &Device) -> Result<(DateTime<Utc>, DateTime<Utc>, Duration)> {
+pub async fn determine(device: &dyn Shell) -> Result<(DateTime<Utc>, DateTime<Utc>, Duration)> {
     let output = device
-        .execute_host_exec_out_command(DATE_COMMAND)
+        .exec(DATE_COMMAND)
         .await
         .with_context(|| anyhow!("Failed to run date command: {:?}", DATE_COMMAND))?;
     let local_time = Utc::now();
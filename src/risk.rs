@@ -0,0 +1,151 @@
+use crate::ioc::{Suspicion, SuspicionLevel};
+use std::fmt;
+
+/// Weight of a single finding in the overall score
+fn weight(level: SuspicionLevel) -> usize {
+    match level {
+        SuspicionLevel::High => 10,
+        SuspicionLevel::Medium => 3,
+        SuspicionLevel::Low => 1,
+        SuspicionLevel::Info | SuspicionLevel::Good => 0,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum RiskRating {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskRating {
+    pub fn level(&self) -> SuspicionLevel {
+        match self {
+            RiskRating::None => SuspicionLevel::Good,
+            RiskRating::Low => SuspicionLevel::Low,
+            RiskRating::Medium => SuspicionLevel::Medium,
+            RiskRating::High => SuspicionLevel::High,
+        }
+    }
+}
+
+impl fmt::Display for RiskRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskRating::None => write!(f, "No known risk"),
+            RiskRating::Low => write!(f, "Low risk"),
+            RiskRating::Medium => write!(f, "Medium risk"),
+            RiskRating::High => write!(f, "High risk"),
+        }
+    }
+}
+
+/// The number of findings per level, the raw components of the risk rating
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct RiskScore {
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub info: usize,
+}
+
+impl RiskScore {
+    pub fn from_findings<'a, I: IntoIterator<Item = &'a Suspicion>>(findings: I) -> Self {
+        let mut score = Self::default();
+        for sus in findings {
+            score.add(sus.level);
+        }
+        score
+    }
+
+    pub fn add(&mut self, level: SuspicionLevel) {
+        match level {
+            SuspicionLevel::High => self.high += 1,
+            SuspicionLevel::Medium => self.medium += 1,
+            SuspicionLevel::Low => self.low += 1,
+            SuspicionLevel::Info => self.info += 1,
+            SuspicionLevel::Good => (),
+        }
+    }
+
+    /// Weighted sum of all findings
+    pub fn score(&self) -> usize {
+        self.high * weight(SuspicionLevel::High)
+            + self.medium * weight(SuspicionLevel::Medium)
+            + self.low * weight(SuspicionLevel::Low)
+    }
+
+    pub fn rating(&self) -> RiskRating {
+        if self.high > 0 {
+            RiskRating::High
+        } else if self.medium > 0 {
+            RiskRating::Medium
+        } else if self.low > 0 {
+            RiskRating::Low
+        } else {
+            RiskRating::None
+        }
+    }
+}
+
+impl fmt::Display for RiskScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} high, {} medium, {} low indicators (score {})",
+            self.rating(),
+            self.high,
+            self.medium,
+            self.low,
+            self.score()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::Kind;
+
+    fn sus(level: SuspicionLevel) -> Suspicion {
+        Suspicion::new(level, Kind::Other, String::new())
+    }
+
+    #[test]
+    fn test_risk_score_empty() {
+        let score =
+            RiskScore::from_findings(&[sus(SuspicionLevel::Good), sus(SuspicionLevel::Info)]);
+        assert_eq!(score.rating(), RiskRating::None);
+        assert_eq!(score.score(), 0);
+        assert_eq!(
+            score.to_string(),
+            "No known risk: 0 high, 0 medium, 0 low indicators (score 0)"
+        );
+    }
+
+    #[test]
+    fn test_risk_score_high() {
+        let score = RiskScore::from_findings(&[
+            sus(SuspicionLevel::High),
+            sus(SuspicionLevel::Medium),
+            sus(SuspicionLevel::Low),
+            sus(SuspicionLevel::Low),
+            sus(SuspicionLevel::High),
+        ]);
+        assert_eq!(
+            score,
+            RiskScore {
+                high: 2,
+                medium: 1,
+                low: 2,
+                info: 0,
+            }
+        );
+        assert_eq!(score.rating(), RiskRating::High);
+        assert_eq!(
+            score.to_string(),
+            "High risk: 2 high, 1 medium, 2 low indicators (score 25)"
+        );
+    }
+}
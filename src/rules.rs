@@ -1,26 +1,231 @@
 use crate::errors::*;
+use crate::ioc::{Confidence, SuspicionLevel};
 use crate::utils;
-use std::collections::HashMap;
-use std::fmt;
+use serde::Deserialize;
+use stalkerware_indicators::Rule;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Extensions of files that are loaded from a rules directory, json is also valid yaml
+const RULE_FILE_EXTENSIONS: &[&str] = &["yaml", "yml", "json"];
+
+pub fn is_rule_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RULE_FILE_EXTENSIONS.contains(&ext))
+}
+
+/// Fields we support in addition to the upstream stalkerware-indicators format
+#[derive(Debug, Deserialize)]
+struct RuleExtension {
+    name: String,
+    #[serde(default)]
+    remediation: Option<String>,
+    /// The severity of matches, they are high if it's not set and can't be `good`
+    #[serde(default)]
+    level: Option<SuspicionLevel>,
+    #[serde(default)]
+    confidence: Option<Confidence>,
+    #[serde(default)]
+    applies_to: Option<Applicability>,
+}
+
+/// Restricts a rule to some devices, rules without it apply to all of them
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Applicability {
+    /// Values of `ro.product.manufacturer`, compared case-insensitive
+    #[serde(default)]
+    pub manufacturers: Vec<String>,
+    pub min_sdk: Option<u32>,
+    pub max_sdk: Option<u32>,
+}
+
+impl Applicability {
+    /// Properties of the device that are unknown are assumed to match
+    pub fn matches(&self, target: &Target) -> bool {
+        let manufacturer = match &target.manufacturer {
+            Some(manufacturer) if !self.manufacturers.is_empty() => self
+                .manufacturers
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(manufacturer)),
+            _ => true,
+        };
+        let sdk = target.sdk.is_none_or(|sdk| {
+            self.min_sdk.is_none_or(|min| sdk >= min) && self.max_sdk.is_none_or(|max| sdk <= max)
+        });
+        manufacturer && sdk
+    }
+}
+
+/// The properties of the scanned device that rules can be restricted to
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Target {
+    pub manufacturer: Option<String>,
+    pub sdk: Option<u32>,
+}
+
+/// Parse the rules one by one, so an error can point to the rule that is malformed
+fn parse_rules(buf: &[u8]) -> Result<Vec<(Rule, RuleExtension)>> {
+    let entries = serde_yaml::from_slice::<Vec<serde_yaml::Value>>(buf)
+        .context("Failed to parse rules, expected a yaml list of rules")?;
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let parsed = serde_yaml::from_value::<Rule>(entry.clone()).and_then(|rule| {
+                let ext = serde_yaml::from_value::<RuleExtension>(entry.clone())?;
+                Ok((rule, ext))
+            });
+            parsed.or_else(|err| {
+                // parsing the whole document again gives an error with the path and line of the field
+                let err = serde_yaml::from_slice::<Vec<Rule>>(buf)
+                    .err()
+                    .or_else(|| serde_yaml::from_slice::<Vec<RuleExtension>>(buf).err())
+                    .unwrap_or(err);
+                let rule = match entry.get("name").and_then(|name| name.as_str()) {
+                    Some(name) => format!("Rule {name:?} at index {i}"),
+                    None => format!("Rule at index {i}"),
+                };
+                Err(err).with_context(|| anyhow!("{rule} is invalid"))
+            })
+        })
+        .collect()
+}
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Rules {
     map: HashMap<String, String>,
+    domains: HashMap<String, String>,
+    ips: HashMap<IpAddr, String>,
+    /// remediation guidance by rule name
+    remediations: HashMap<String, String>,
+    /// the severity of a match if it's not high, by rule name
+    levels: HashMap<String, SuspicionLevel>,
+    /// how certain a match is, by rule name
+    confidences: HashMap<String, Confidence>,
+    /// the devices a rule applies to, by rule name
+    applicability: HashMap<String, Applicability>,
+    /// the type of the rule, eg. `stalkerware`, by rule name
+    categories: HashMap<String, String>,
+    sources: Vec<RuleSource>,
+}
+
+/// Statistics about a file rules have been loaded from
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RuleSource {
+    pub name: String,
+    pub sha256: String,
+    /// number of rules by their type, eg. `stalkerware`
+    pub types: BTreeMap<String, usize>,
+    pub packages: usize,
+    pub certificates: usize,
+    pub websites: usize,
+    pub domains: usize,
+    pub ips: usize,
+}
+
+impl RuleSource {
+    pub fn rules(&self) -> usize {
+        self.types.values().sum()
+    }
 }
 
 impl Rules {
-    pub fn load_yaml<F: fmt::Debug>(&mut self, name: F, buf: &[u8]) -> Result<String> {
+    /// Load a rule file, or every rule file of a directory
+    pub async fn load_path(&mut self, path: &Path) -> Result<()> {
+        let metadata = fs::metadata(path)
+            .await
+            .with_context(|| anyhow!("Failed to access rules at {path:?}"))?;
+        if metadata.is_dir() {
+            self.load_dir(path).await?;
+        } else {
+            let buf = fs::read(path)
+                .await
+                .with_context(|| anyhow!("Failed to read rules from file: {path:?}"))?;
+            self.load_yaml(&path.to_string_lossy(), &buf)
+                .with_context(|| anyhow!("Failed to load rules from file: {path:?}"))?;
+        }
+        Ok(())
+    }
+
+    /// Load and merge all rule files in a directory, in alphabetical order
+    pub async fn load_dir(&mut self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut entries = fs::read_dir(dir)
+            .await
+            .with_context(|| anyhow!("Failed to list rules in {dir:?}"))?;
+        let mut paths = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if is_rule_file(&path) && entry.file_type().await?.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in &paths {
+            debug!("Loading rules from {path:?}");
+            let buf = fs::read(path)
+                .await
+                .with_context(|| anyhow!("Failed to read rules from file: {path:?}"))?;
+            self.load_yaml(&path.to_string_lossy(), &buf)
+                .with_context(|| anyhow!("Failed to load rules from file: {path:?}"))?;
+        }
+        Ok(paths)
+    }
+
+    pub fn load_yaml(&mut self, name: &str, buf: &[u8]) -> Result<String> {
         let sha256 = utils::sha256(buf);
 
-        let list = stalkerware_indicators::parse_from_buf(buf)
-            .context("Failed to load stalkerware-indicators yaml")?;
+        let list = parse_rules(buf)?;
         let num_of_rules = list.len();
 
-        for rule in list {
+        let mut source = RuleSource {
+            name: name.to_string(),
+            sha256: sha256.clone(),
+            ..Default::default()
+        };
+        for (rule, ext) in list {
+            if let Some(applies_to) = ext.applies_to {
+                self.applicability.insert(ext.name.clone(), applies_to);
+            }
+            if let Some(level) = ext.level {
+                if level == SuspicionLevel::Good {
+                    bail!(
+                        "Rule {:?} has level good, matches of a rule are never a good sign",
+                        ext.name
+                    );
+                }
+                self.levels.insert(ext.name.clone(), level);
+            }
+            if let Some(confidence) = ext.confidence {
+                self.confidences.insert(ext.name.clone(), confidence);
+            }
+            if let Some(remediation) = ext.remediation {
+                self.remediations.insert(ext.name, remediation);
+            }
+            self.categories
+                .insert(rule.name.to_string(), rule.r#type.clone());
+            *source.types.entry(rule.r#type).or_default() += 1;
+            source.packages += rule.packages.len();
+            source.certificates += rule.certificates.len();
+            source.websites += rule.websites.len();
+            source.domains += rule.c2.domains.len();
+            source.ips += rule.c2.ips.len();
             for package in rule.packages {
                 self.map.insert(package, rule.name.to_string());
             }
+            for domain in rule.c2.domains {
+                self.domains
+                    .insert(domain.to_ascii_lowercase(), rule.name.to_string());
+            }
+            for ip in rule.c2.ips {
+                self.ips.insert(ip, rule.name.to_string());
+            }
         }
+        self.sources.push(source);
 
         info!("Loaded {num_of_rules} rules from {name:?} (sha256={sha256})",);
 
@@ -30,6 +235,85 @@ impl Rules {
     pub fn get(&self, pkg_id: &str) -> Option<&String> {
         self.map.get(pkg_id)
     }
+
+    /// Match a domain and its parent domains against known c2 servers
+    pub fn get_domain(&self, domain: &str) -> Option<&String> {
+        let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+        let mut domain = domain.as_str();
+        loop {
+            if let Some(rule) = self.domains.get(domain) {
+                return Some(rule);
+            }
+            domain = domain.split_once('.')?.1;
+        }
+    }
+
+    pub fn get_ip(&self, ip: &IpAddr) -> Option<&String> {
+        self.ips.get(ip)
+    }
+
+    /// Guidance on what to do if the rule has matched
+    pub fn get_remediation(&self, rule: &str) -> Option<&String> {
+        self.remediations.get(rule)
+    }
+
+    /// The severity of a match of the rule, high unless the rule says otherwise
+    pub fn get_level(&self, rule: &str) -> SuspicionLevel {
+        self.levels
+            .get(rule)
+            .copied()
+            .unwrap_or(SuspicionLevel::High)
+    }
+
+    /// The minimum level of matches of each rule, from the minimum levels of their categories
+    pub fn escalations(
+        &self,
+        categories: &BTreeMap<String, SuspicionLevel>,
+    ) -> HashMap<String, SuspicionLevel> {
+        self.categories
+            .iter()
+            .filter_map(|(rule, category)| Some((rule.clone(), *categories.get(category)?)))
+            .collect()
+    }
+
+    /// How likely a match of the rule is a true positive, if its author has said so
+    pub fn get_confidence(&self, rule: &str) -> Option<Confidence> {
+        self.confidences.get(rule).copied()
+    }
+
+    /// If some of the rules only apply to certain devices
+    pub fn has_applicability(&self) -> bool {
+        !self.applicability.is_empty()
+    }
+
+    /// The rules that apply to the device, with the indicators of all others removed
+    pub fn for_target(&self, target: &Target) -> Rules {
+        let skipped = self
+            .applicability
+            .iter()
+            .filter(|(_, applies_to)| !applies_to.matches(target))
+            .map(|(name, _)| name.as_str())
+            .collect::<HashSet<_>>();
+        if !skipped.is_empty() {
+            info!(
+                "Skipping {} rules that don't apply to this device: {:?}",
+                skipped.len(),
+                skipped
+            );
+        }
+        let mut rules = self.clone();
+        rules.map.retain(|_, rule| !skipped.contains(rule.as_str()));
+        rules
+            .domains
+            .retain(|_, rule| !skipped.contains(rule.as_str()));
+        rules.ips.retain(|_, rule| !skipped.contains(rule.as_str()));
+        rules
+    }
+
+    /// The files that have been loaded, in order
+    pub fn sources(&self) -> &[RuleSource] {
+        &self.sources
+    }
 }
 
 #[cfg(test)]
@@ -143,5 +427,184 @@ mod tests {
                 "com.android.core.mngp".to_string() => "Snoopza".to_string(),
             ]
         );
+        assert_eq!(
+            rules.get_domain("cabinet.vkur.se").map(String::as_str),
+            Some("Reptilicus")
+        );
+        assert_eq!(
+            rules.get_domain("EU.API.Snoopza.com.").map(String::as_str),
+            Some("Snoopza")
+        );
+        assert_eq!(rules.get_domain("vkur.se"), None);
+        assert_eq!(
+            rules
+                .get_ip(&"176.9.42.16".parse().unwrap())
+                .map(String::as_str),
+            Some("Reptilicus")
+        );
+        assert_eq!(
+            rules.sources(),
+            &[RuleSource {
+                name: "unit-test".to_string(),
+                sha256: "d23925d55f381f2052ba4b76632efa5d38513b7294675b63ac106f0c5828f15b"
+                    .to_string(),
+                types: BTreeMap::from([("stalkerware".to_string(), 2)]),
+                packages: 17,
+                certificates: 8,
+                websites: 3,
+                domains: 19,
+                ips: 3,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("spytrap-rules-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("b.yaml"),
+            "- name: SpyLive360\n  type: stalkerware\n  packages:\n  - com.wifi0\n  remediation: Uninstall the app\n  confidence: medium\n  level: medium\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("a.json"),
+            r#"[{"name": "Example", "type": "watchware", "packages": ["org.example"]}]"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("README.md"), "not a rule file").unwrap();
+
+        let mut rules = Rules::default();
+        let paths = rules.load_dir(&dir).await.unwrap();
+        assert_eq!(paths, &[dir.join("a.json"), dir.join("b.yaml")]);
+        assert_eq!(
+            rules.get("com.wifi0").map(String::as_str),
+            Some("SpyLive360")
+        );
+        assert_eq!(
+            rules.get("org.example").map(String::as_str),
+            Some("Example")
+        );
+        assert_eq!(
+            rules.get_remediation("SpyLive360").map(String::as_str),
+            Some("Uninstall the app")
+        );
+        assert_eq!(rules.get_remediation("Example"), None);
+        assert_eq!(
+            rules.escalations(&BTreeMap::from([(
+                "watchware".to_string(),
+                SuspicionLevel::High
+            )])),
+            HashMap::from([("Example".to_string(), SuspicionLevel::High)])
+        );
+        assert_eq!(rules.get_confidence("SpyLive360"), Some(Confidence::Medium));
+        assert_eq!(rules.get_confidence("Example"), None);
+        assert_eq!(rules.get_level("SpyLive360"), SuspicionLevel::Medium);
+        assert_eq!(rules.get_level("Example"), SuspicionLevel::High);
+        assert!(Rules::default()
+            .load_yaml(
+                "unit-test",
+                b"- name: Example\n  type: stalkerware\n  level: good\n"
+            )
+            .is_err());
+        assert_eq!(rules.sources().len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let err = Rules::default()
+            .load_yaml(
+                "unit-test",
+                b"- name: SpyLive360
+  type: stalkerware
+- name: Snoopza
+  packages:
+  - com.android.core.mngi
+",
+            )
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Rule \"Snoopza\" at index 1 is invalid: .[1]: missing field `type` at line 3 column 3"
+        );
+
+        let err = Rules::default()
+            .load_yaml(
+                "unit-test",
+                b"- name: Snoopza
+  type: stalkerware
+  c2:
+    ips:
+    - 217.182.250
+",
+            )
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Rule \"Snoopza\" at index 0 is invalid: .[0].c2.ips[0]: invalid IP address syntax at line 5 column 7"
+        );
+
+        let err = Rules::default()
+            .load_yaml("unit-test", b"- type: stalkerware\n")
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Rule at index 0 is invalid: .[0]: missing field `name` at line 1 column 3"
+        );
+
+        assert!(Rules::default()
+            .load_yaml("unit-test", b"name: Snoopza\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_for_target() {
+        let mut rules = Rules::default();
+        rules
+            .load_yaml(
+                "unit-test",
+                b"- name: Generic
+  type: stalkerware
+  packages:
+  - com.wifi0
+- name: VendorSpecific
+  type: stalkerware
+  packages:
+  - com.samsung.sysupdate
+  c2:
+    domains:
+    - vendor.example.com
+  applies_to:
+    manufacturers: [samsung]
+    max_sdk: 30
+",
+            )
+            .unwrap();
+        assert!(rules.has_applicability());
+
+        let pixel = rules.for_target(&Target {
+            manufacturer: Some("Google".to_string()),
+            sdk: Some(34),
+        });
+        assert!(pixel.get("com.wifi0").is_some());
+        assert_eq!(pixel.get("com.samsung.sysupdate"), None);
+        assert_eq!(pixel.get_domain("vendor.example.com"), None);
+
+        let samsung = rules.for_target(&Target {
+            manufacturer: Some("SAMSUNG".to_string()),
+            sdk: Some(30),
+        });
+        assert!(samsung.get("com.samsung.sysupdate").is_some());
+        let newer = rules.for_target(&Target {
+            manufacturer: Some("samsung".to_string()),
+            sdk: Some(31),
+        });
+        assert_eq!(newer.get("com.samsung.sysupdate"), None);
+
+        // nothing is skipped if the device couldn't be identified
+        let unknown = rules.for_target(&Target::default());
+        assert!(unknown.get("com.samsung.sysupdate").is_some());
     }
 }
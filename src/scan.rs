@@ -1,124 +1,646 @@
-use crate::accessibility;
 use crate::args;
-use crate::dumpsys;
+use crate::checkpoint::{Checkpoint, Recorded};
+use crate::checks;
+use crate::dashboard::getprop;
 use crate::errors::*;
+use crate::export;
 use crate::ioc::{Suspicion, SuspicionLevel};
-use crate::package;
-use crate::pm;
+use crate::probes::Probe;
+use crate::progress::{Event, JsonProgress};
 use crate::remote_clock;
-use crate::rules::Rules;
-use crate::settings;
-use crate::tui::Message;
-use forensic_adb::Device;
+use crate::rules::{Rules, Target};
+use crate::shell::{RetryShell, Shell, ThrottleShell, TimeoutShell};
+use crate::syslog::Syslog;
+use chrono::NaiveDateTime;
+use forensic_adb::{AndroidStorageInput, DeviceInfo, Host};
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// Something that has been reported while the scan is running
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanEvent {
+    Suspicion(Suspicion),
+    App {
+        name: String,
+        sus: Suspicion,
+    },
+    Warning(String),
+    /// A check has started, the events that follow are reported by it
+    Check(String),
+    /// How many of the items of the current check have been inspected, eg. apps
+    Progress {
+        done: usize,
+        total: Option<usize>,
+    },
+}
+
 pub enum ScanNotifier {
     Null,
-    Channel(mpsc::Sender<Message>),
+    Channel(mpsc::Sender<ScanEvent>),
+    Json(JsonProgress),
+    Syslog(Syslog),
+    /// Keep everything in memory, to print a report once the scan is done
+    Collect(Findings),
+    /// Record everything in a checkpoint before passing it on
+    Checkpoint(Box<ScanNotifier>, Checkpoint),
+    /// Raise the level of rule matches to the minimum of their rule before passing them on
+    Escalate(Box<ScanNotifier>, HashMap<String, SuspicionLevel>),
+    /// Stop the scan once a finding of this level or higher has been passed on
+    FailFast(Box<ScanNotifier>, SuspicionLevel),
 }
 
 impl ScanNotifier {
+    /// Record progress in the checkpoint and replay what has been reported before it was resumed
+    pub async fn with_checkpoint(mut inner: ScanNotifier, checkpoint: Checkpoint) -> Result<Self> {
+        for event in checkpoint.events().to_vec() {
+            match event {
+                Recorded::Suspicion { sus } => inner.sus(sus).await?,
+                Recorded::App { name, sus } => inner.app(name, sus).await?,
+                Recorded::Warning { message } => inner.warning(message).await?,
+            }
+        }
+        Ok(ScanNotifier::Checkpoint(Box::new(inner), checkpoint))
+    }
+
+    /// Remove the wrappers that have been added for the settings of a scan
+    fn unwrap_filters(self) -> ScanNotifier {
+        match self {
+            ScanNotifier::Escalate(inner, _) | ScanNotifier::FailFast(inner, _) => {
+                inner.unwrap_filters()
+            }
+            _ => self,
+        }
+    }
+
+    /// The notifier that is wrapped by a checkpoint, if any
+    pub fn inner_mut(&mut self) -> &mut ScanNotifier {
+        match self {
+            ScanNotifier::Checkpoint(inner, _) => inner.inner_mut(),
+            ScanNotifier::Escalate(inner, _) => inner.inner_mut(),
+            ScanNotifier::FailFast(inner, _) => inner.inner_mut(),
+            _ => self,
+        }
+    }
+
+    fn checkpoint(&self) -> Option<&Checkpoint> {
+        match self {
+            ScanNotifier::Checkpoint(_, checkpoint) => Some(checkpoint),
+            ScanNotifier::Escalate(inner, _) => inner.checkpoint(),
+            ScanNotifier::FailFast(inner, _) => inner.checkpoint(),
+            _ => None,
+        }
+    }
+
+    fn checkpoint_mut(&mut self) -> Option<&mut Checkpoint> {
+        match self {
+            ScanNotifier::Checkpoint(_, checkpoint) => Some(checkpoint),
+            ScanNotifier::Escalate(inner, _) => inner.checkpoint_mut(),
+            ScanNotifier::FailFast(inner, _) => inner.checkpoint_mut(),
+            _ => None,
+        }
+    }
+
+    /// If a resumed scan has already finished this check
+    pub fn is_check_done(&self, id: &str) -> bool {
+        self.checkpoint()
+            .is_some_and(|checkpoint| checkpoint.is_check_done(id))
+    }
+
+    /// If a resumed scan has already inspected this app
+    pub fn is_app_done(&self, id: &str) -> bool {
+        self.checkpoint()
+            .is_some_and(|checkpoint| checkpoint.is_app_done(id))
+    }
+
+    pub async fn check_done(&mut self, id: &str) -> Result<()> {
+        if let Some(checkpoint) = self.checkpoint_mut() {
+            checkpoint.check_done(id).await?;
+        }
+        Ok(())
+    }
+
+    /// The check runs again when resuming, what it has reported so far isn't recorded
+    pub fn check_failed(&mut self) {
+        if let Some(checkpoint) = self.checkpoint_mut() {
+            checkpoint.check_failed();
+        }
+    }
+
+    pub async fn app_done(&mut self, id: &str) -> Result<()> {
+        if let Some(checkpoint) = self.checkpoint_mut() {
+            checkpoint.app_done(id).await?;
+        }
+        Ok(())
+    }
+
     pub async fn sus(&mut self, sus: Suspicion) -> Result<()> {
-        if let ScanNotifier::Channel(tx) = self {
-            tx.send(Message::Suspicion(sus)).await?;
+        match self {
+            ScanNotifier::Null => (),
+            ScanNotifier::Channel(tx) => tx.send(ScanEvent::Suspicion(sus)).await?,
+            ScanNotifier::Json(out) => out.send(&Event::Suspicion { sus: &sus }).await?,
+            ScanNotifier::Syslog(syslog) => syslog.sus(None, &sus)?,
+            ScanNotifier::Collect(findings) => findings.push(ScanEvent::Suspicion(sus)),
+            ScanNotifier::Checkpoint(inner, checkpoint) => {
+                checkpoint.record(Recorded::Suspicion { sus: sus.clone() });
+                Box::pin(inner.sus(sus)).await?
+            }
+            ScanNotifier::Escalate(inner, escalations) => {
+                let sus = sus.escalate(escalations);
+                Box::pin(inner.sus(sus)).await?
+            }
+            ScanNotifier::FailFast(inner, level) => {
+                let stop = fail_fast(*level, &sus);
+                Box::pin(inner.sus(sus)).await?;
+                if let Some(err) = stop {
+                    return Err(err.into());
+                }
+            }
         }
         Ok(())
     }
 
     pub async fn app(&mut self, name: String, sus: Suspicion) -> Result<()> {
-        if let ScanNotifier::Channel(tx) = self {
-            tx.send(Message::App { name, sus }).await?;
+        match self {
+            ScanNotifier::Null => (),
+            ScanNotifier::Channel(tx) => tx.send(ScanEvent::App { name, sus }).await?,
+            ScanNotifier::Json(out) => {
+                out.send(&Event::App {
+                    name: &name,
+                    sus: &sus,
+                })
+                .await?
+            }
+            ScanNotifier::Syslog(syslog) => syslog.sus(Some(&name), &sus)?,
+            ScanNotifier::Collect(findings) => findings.push(ScanEvent::App { name, sus }),
+            ScanNotifier::Checkpoint(inner, checkpoint) => {
+                checkpoint.record(Recorded::App {
+                    name: name.clone(),
+                    sus: sus.clone(),
+                });
+                Box::pin(inner.app(name, sus)).await?
+            }
+            ScanNotifier::Escalate(inner, escalations) => {
+                let sus = sus.escalate(escalations);
+                Box::pin(inner.app(name, sus)).await?
+            }
+            ScanNotifier::FailFast(inner, level) => {
+                let stop = fail_fast(*level, &sus);
+                Box::pin(inner.app(name, sus)).await?;
+                if let Some(err) = stop {
+                    return Err(err.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Report a condition that makes the scan less complete, without being suspicious itself
+    pub async fn warning(&mut self, message: String) -> Result<()> {
+        match self {
+            ScanNotifier::Null => (),
+            ScanNotifier::Channel(tx) => tx.send(ScanEvent::Warning(message)).await?,
+            ScanNotifier::Json(out) => out.send(&Event::Warning { message: &message }).await?,
+            ScanNotifier::Syslog(syslog) => syslog.warning(&message)?,
+            ScanNotifier::Collect(findings) => findings.push(ScanEvent::Warning(message)),
+            ScanNotifier::Checkpoint(inner, checkpoint) => {
+                checkpoint.record(Recorded::Warning {
+                    message: message.clone(),
+                });
+                Box::pin(inner.warning(message)).await?
+            }
+            ScanNotifier::Escalate(inner, _) | ScanNotifier::FailFast(inner, _) => {
+                Box::pin(inner.warning(message)).await?
+            }
+        }
+        Ok(())
+    }
+
+    /// Announce the check that is about to run
+    pub async fn progress(&mut self, check: &str) -> Result<()> {
+        match self.inner_mut() {
+            ScanNotifier::Json(out) => {
+                out.send(&Event::Progress {
+                    check,
+                    done: None,
+                    total: None,
+                })
+                .await?
+            }
+            ScanNotifier::Channel(tx) => tx.send(ScanEvent::Check(check.to_string())).await?,
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Announce how far the running check has come
+    pub async fn count(&mut self, check: &str, done: usize, total: Option<usize>) -> Result<()> {
+        match self.inner_mut() {
+            ScanNotifier::Json(out) => {
+                out.send(&Event::Progress {
+                    check,
+                    done: Some(done),
+                    total,
+                })
+                .await?
+            }
+            ScanNotifier::Channel(tx) => tx.send(ScanEvent::Progress { done, total }).await?,
+            _ => (),
         }
         Ok(())
     }
 }
 
+/// The error that stops the scan, if the finding is severe enough
+fn fail_fast(level: SuspicionLevel, sus: &Suspicion) -> Option<ScanError> {
+    (sus.level >= level).then(|| ScanError::FailFast {
+        level: sus.level,
+        description: sus.description.clone(),
+    })
+}
+
+/// Number of apps that are inspected at the same time by default, slow devices can be relieved with `--throttle`
+pub const DEFAULT_CONCURRENCY: usize = 4;
+/// Seconds a single adb command may take by default, some dumpsys commands are slow on old devices
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+/// The timeout for adb commands, `0` disables it
+pub fn timeout(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[derive(Debug)]
 pub struct Settings {
     pub skip_apps: bool,
+    pub skip_accessibility: bool,
+    /// Directories to search for files, in addition to the default ones
+    pub scan_paths: Vec<String>,
+    /// The device is an extracted snapshot, only checks that support this are run
+    pub offline: bool,
+    /// How many adb commands for app details may be running at the same time
+    pub concurrency: usize,
+    /// Run failed commands a second time before giving up on a check
+    pub retry: bool,
+    /// Wait at least this long between adb commands, for slow devices
+    pub throttle: Option<Duration>,
+    /// Only run checks for network indicators
+    pub network_only: bool,
+    /// Compare the device with the fingerprint recorded in this file during the last scan
+    pub fingerprints: Option<PathBuf>,
+    /// Only audit apps that have been installed or updated after this time of the device clock, rules still apply to all apps
+    pub since: Option<NaiveDateTime>,
+    /// Matches of rules of these categories, eg. `stalkerware`, are reported with at least this level
+    pub escalate: BTreeMap<String, SuspicionLevel>,
+    /// Only run the checks with these ids, eg. to repeat the ones that reported warnings
+    pub checks: Option<BTreeSet<String>>,
+    /// Additional read-only commands whose output is matched against patterns
+    pub probes: Vec<Probe>,
+    /// Stop the scan at the first finding of this level or higher
+    pub fail_fast: Option<SuspicionLevel>,
+    /// Give up on an adb command that takes longer than this
+    pub timeout: Option<Duration>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            skip_apps: false,
+            skip_accessibility: false,
+            scan_paths: Vec::new(),
+            offline: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            retry: false,
+            throttle: None,
+            network_only: false,
+            fingerprints: None,
+            since: None,
+            escalate: BTreeMap::new(),
+            checks: None,
+            probes: Vec::new(),
+            fail_fast: None,
+            timeout: timeout(DEFAULT_TIMEOUT),
+        }
+    }
 }
 
 impl From<&args::Scan> for Settings {
     fn from(args: &args::Scan) -> Settings {
         Settings {
             skip_apps: args.skip_apps,
+            skip_accessibility: args.skip_accessibility,
+            scan_paths: args.scan_paths.clone(),
+            offline: args.offline.is_some(),
+            concurrency: args.concurrency,
+            retry: args.retry,
+            throttle: args.throttle.map(Duration::from_millis),
+            network_only: args.network_only,
+            fingerprints: None,
+            since: args.since,
+            escalate: args.escalate.iter().cloned().collect(),
+            checks: None,
+            probes: Vec::new(),
+            fail_fast: args.fail_fast,
+            timeout: timeout(args.timeout),
         }
     }
 }
 
 pub async fn run(
-    device: &Device,
+    device: &dyn Shell,
     rules: &Rules,
     scan: &Settings,
     report: &mut ScanNotifier,
 ) -> Result<()> {
     debug!("Using device: {:?}", device);
+    let timeout = scan
+        .timeout
+        .map(|timeout| TimeoutShell::new(device, timeout));
+    let device: &dyn Shell = match &timeout {
+        Some(timeout) => timeout,
+        None => device,
+    };
+    let throttle = scan
+        .throttle
+        .map(|interval| ThrottleShell::new(device, interval));
+    let device: &dyn Shell = match &throttle {
+        Some(throttle) => throttle,
+        None => device,
+    };
+    let retry = RetryShell::new(device);
+    let device: &dyn Shell = if scan.retry { &retry } else { device };
 
-    info!("Fetching remote clock");
-    let (local_time, remote_time, drift) = remote_clock::determine(device).await?;
-    info!(
-        "Local time is {}, remote time is {}, drift={:#}",
-        local_time, remote_time, drift
-    );
+    if !scan.offline {
+        info!("Fetching remote clock");
+        let (local_time, remote_time, drift) = remote_clock::determine(device).await?;
+        info!(
+            "Local time is {}, remote time is {}, drift={:#}",
+            local_time, remote_time, drift
+        );
+    }
 
-    info!("Enumerating android settings");
-    for (_namespace, settings) in settings::dump(device).await? {
-        for sus in settings.audit() {
-            warn!("Suspicious {:?}: {}", sus.level, sus.description);
-            report.sus(sus).await?;
+    let filtered;
+    let rules = if rules.has_applicability() {
+        match target(device).await {
+            Ok(target) => {
+                debug!("Filtering rules for device: {target:?}");
+                filtered = rules.for_target(&target);
+                &filtered
+            }
+            Err(err) => {
+                warn!("Failed to identify the device, using all rules: {err:#}");
+                rules
+            }
+        }
+    } else {
+        rules
+    };
+
+    let escalations = rules.escalations(&scan.escalate);
+    if escalations.is_empty() && scan.fail_fast.is_none() {
+        run_checks(device, rules, scan, report).await?;
+    } else {
+        let mut wrapped = std::mem::replace(report, ScanNotifier::Null);
+        if let Some(level) = scan.fail_fast {
+            wrapped = ScanNotifier::FailFast(Box::new(wrapped), level);
+        }
+        // levels are raised before they are compared with the fail-fast level
+        if !escalations.is_empty() {
+            wrapped = ScanNotifier::Escalate(Box::new(wrapped), escalations);
         }
+        let ret = run_checks(device, rules, scan, &mut wrapped).await;
+        *report = wrapped.unwrap_filters();
+        ret?;
     }
 
-    info!("Enumerating service list");
-    let services = dumpsys::list_services(device).await?;
+    info!("Scan finished");
+
+    Ok(())
+}
 
-    if services.contains("accessibility") {
-        info!("Reading accessibility settings");
-        let accessibility = accessibility::dump(device).await?;
-        for sus in accessibility.audit() {
-            warn!("Suspicious {:?}: {}", sus.level, sus.description);
-            report.sus(sus).await?;
+async fn run_checks(
+    device: &dyn Shell,
+    rules: &Rules,
+    scan: &Settings,
+    report: &mut ScanNotifier,
+) -> Result<()> {
+    let mut completed = false;
+    let mut failed = None;
+    for check in checks::registry() {
+        if !check.enabled(scan) {
+            debug!("Skipping check: {:?}", check.id());
+            continue;
+        }
+        if scan.network_only && !check.network() {
+            debug!(
+                "Skipping check that is not about network indicators: {:?}",
+                check.id()
+            );
+            continue;
+        }
+        if scan.offline && !check.offline() {
+            debug!("Skipping check that needs a live device: {:?}", check.id());
+            continue;
+        }
+        if scan
+            .checks
+            .as_ref()
+            .is_some_and(|checks| !checks.contains(check.id()))
+        {
+            debug!(
+                "Skipping check that has not been selected: {:?}",
+                check.id()
+            );
+            continue;
+        }
+        if report.is_check_done(check.id()) {
+            debug!(
+                "Skipping check that has completed before the scan was resumed: {:?}",
+                check.id()
+            );
+            completed = true;
+            continue;
+        }
+        debug!("Running check: {:?}", check.id());
+        report.progress(check.id()).await?;
+        match check.run(device, rules, scan, report).await {
+            Ok(()) => {
+                completed = true;
+                report.check_done(check.id()).await?;
+            }
+            Err(err) => {
+                if matches!(
+                    err.downcast_ref::<ScanError>(),
+                    Some(ScanError::FailFast { .. })
+                ) {
+                    info!("Stopping scan: {err:#}");
+                    return Err(err);
+                }
+                // a single failed check shouldn't void the results of all the others
+                let message = format!("Check {:?} has failed: {err:#}", check.id());
+                error!("{message}");
+                report.warning(message).await?;
+                report.check_failed();
+                failed = Some(ScanError::Check {
+                    id: check.id(),
+                    source: err,
+                });
+            }
         }
     }
+    // without a single completed check the device is likely gone, this must not look like a clean result
+    match failed {
+        Some(err) if !completed => Err(err.into()),
+        _ => Ok(()),
+    }
+}
 
-    if !scan.skip_apps {
-        // TODO: maybe `cmd package list packages -f`
-        info!("Comparing list of installed apps with known stalkerware ids");
+/// The manufacturer and android version that rules can be restricted to
+async fn target(device: &dyn Shell) -> Result<Target> {
+    let manufacturer = getprop(device, "ro.product.manufacturer").await?;
+    let sdk = getprop(device, "ro.build.version.sdk")
+        .await?
+        .and_then(|sdk| sdk.parse().ok());
+    Ok(Target { manufacturer, sdk })
+}
 
-        let installed_apps = pm::list_packages(device).await?;
-        let mut progress = 0;
-        for apps in installed_apps.chunks(100) {
-            info!(
-                "Scanning installed apps ({}/{})",
-                progress,
-                installed_apps.len()
-            );
+#[derive(Debug, Default)]
+/// Everything that has been reported during the scan of a device
+pub struct Findings {
+    pub findings: Vec<Suspicion>,
+    pub apps: IndexMap<String, Vec<Suspicion>>,
+    pub warnings: Vec<String>,
+}
 
-            for pkg in apps {
-                progress += 1;
+impl Findings {
+    pub fn push(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Suspicion(sus) => self.findings.push(sus),
+            ScanEvent::App { name, sus } => self.apps.entry(name).or_default().push(sus),
+            ScanEvent::Warning(warning) => self.warnings.push(warning),
+            ScanEvent::Check(_) | ScanEvent::Progress { .. } => (),
+        }
+    }
 
-                // TODO: maybe fetch apk and inspect eg. cert
+    /// All findings, about the device and its apps
+    pub fn iter(&self) -> impl Iterator<Item = &Suspicion> {
+        self.findings
+            .iter()
+            .chain(self.apps.values().flat_map(|findings| findings.iter()))
+    }
 
-                if let Some(name) = rules.get(&pkg.id) {
-                    let alert = format!(
-                        "Found known stalkerware with rule: {:?} ({:?})",
-                        pkg.id, name
-                    );
-                    warn!("Suspicious {:?}: {}", SuspicionLevel::High, alert);
-                }
+    /// A report of all findings, for exporting or printing
+    pub fn report<'a>(
+        &'a self,
+        serial: &'a str,
+        device: &'a BTreeMap<String, String>,
+    ) -> export::Report<'a> {
+        export::Report {
+            serial: serial.into(),
+            device,
+            min_level: None,
+            notes: None,
+            warnings: self.warnings.iter().map(String::as_str).collect(),
+            findings: self.findings.iter().collect(),
+            apps: self
+                .apps
+                .iter()
+                .map(|(name, findings)| (name.as_str(), findings.iter().collect()))
+                .collect(),
+            reviewed: vec![],
+        }
+    }
+}
 
-                // fetch infos about package
-                let info = package::dump(device, &pkg.id).await?;
-                trace!("package infos {:?}: {:#?}", pkg.id, info);
+/// Scan the device with this serial, or the only one that is connected, and send every
+/// finding to `events` as soon as it's known
+pub async fn stream(
+    adb_host: &Host,
+    serial: Option<&str>,
+    rules: &Rules,
+    settings: &Settings,
+    events: mpsc::Sender<ScanEvent>,
+) -> Result<()> {
+    let device = adb_host
+        .clone()
+        .device_or_default(serial.as_ref(), AndroidStorageInput::Auto)
+        .await
+        .map_err(|source| ScanError::DeviceUnavailable {
+            serial: serial.map(String::from),
+            source,
+        })?;
+    run(&device, rules, settings, &mut ScanNotifier::Channel(events)).await
+}
 
-                for sus in info.audit() {
-                    warn!("Suspicious {:?}: {}", sus.level, sus.description);
-                    report.app(pkg.id.clone(), sus).await?;
-                }
+/// Scan the device with this serial, or the only one that is connected, and return all findings
+pub async fn collect(
+    adb_host: &Host,
+    serial: Option<&str>,
+    rules: &Rules,
+    settings: &Settings,
+) -> Result<Findings> {
+    let (tx, mut rx) = mpsc::channel(5);
+    let scan = stream(adb_host, serial, rules, settings, tx);
+    let collect = async {
+        let mut findings = Findings::default();
+        while let Some(event) = rx.recv().await {
+            findings.push(event);
+        }
+        findings
+    };
+    let (ret, findings) = tokio::join!(scan, collect);
+    ret?;
+    Ok(findings)
+}
+
+/// Scan a connected device and collect the findings, a failed scan is recorded as warning
+pub async fn scan_device(
+    adb_host: &Host,
+    device: &DeviceInfo,
+    rules: &Rules,
+    settings: &Settings,
+) -> Findings {
+    match collect(adb_host, Some(&device.serial), rules, settings).await {
+        Ok(findings) => findings,
+        Err(err) => {
+            error!("Scan of {:?} has failed: {err:#}", device.serial);
+            Findings {
+                warnings: vec![format!("Scan has failed: {err:#}")],
+                ..Default::default()
             }
         }
     }
+}
 
-    info!("Scan finished");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::MockShell;
 
-    Ok(())
+    #[tokio::test]
+    async fn test_all_checks_failed() {
+        let settings = Settings {
+            offline: true,
+            checks: Some(BTreeSet::from(["apps".to_string()])),
+            ..Default::default()
+        };
+        let mut report = ScanNotifier::Collect(Findings::default());
+        let err = run(
+            &MockShell::default(),
+            &Rules::default(),
+            &settings,
+            &mut report,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ScanError>(),
+            Some(ScanError::Check { id: "apps", .. })
+        ));
+        let ScanNotifier::Collect(findings) = report else {
+            panic!("Findings have not been collected");
+        };
+        assert_eq!(findings.warnings.len(), 1);
+    }
 }
@@ -0,0 +1,194 @@
+use crate::errors::*;
+use crate::ioc::{Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, ScanEvent, ScanNotifier};
+use crate::shell::MockShell;
+use tokio::sync::mpsc;
+
+const RULES: &[u8] = b"
+- name: SpyLive360
+  type: stalkerware
+  packages:
+  - com.wifi0
+";
+
+/// Findings the scan of the mock device is required to report, as (app, level, description)
+const EXPECTED: &[(Option<&str>, SuspicionLevel, &str)] = &[
+    (None, SuspicionLevel::High, "Google Play Protect is turned off"),
+    (None, SuspicionLevel::High, "An accessibility service is bound"),
+    (
+        None,
+        SuspicionLevel::High,
+        "Found file related to known stalkerware \"SpyLive360\": \"/sdcard/Download/com.wifi0.apk\"",
+    ),
+    (
+        Some("com.wifi0"),
+        SuspicionLevel::Info,
+        "Package \"com.wifi0\" has an enabled accessibility service: \"com.wifi0/com.wifi0.AccessibilityReceiver4\"",
+    ),
+    (
+        Some("com.wifi0"),
+        SuspicionLevel::High,
+        "Package \"com.wifi0\" is the device owner",
+    ),
+    (
+        Some("com.wifi0"),
+        SuspicionLevel::Info,
+        "Package \"com.wifi0\" has no installer, it has likely been sideloaded",
+    ),
+    (
+        Some("com.wifi0"),
+        SuspicionLevel::High,
+        "Package \"com.wifi0\" was manually installed",
+    ),
+    (
+        Some("com.wifi0"),
+        SuspicionLevel::High,
+        "Found known stalkerware with rule: \"com.wifi0\" (\"SpyLive360\")",
+    ),
+];
+
+/// A device with stalkerware installed, built from the recordings in test_data/
+pub fn mock_device() -> MockShell {
+    MockShell::default()
+        .with("date -u '+%Y-%m-%d %T %N'", "2024-07-03 10:00:00 123456789\n")
+        .with("settings get global package_verifier_enable", "0\n")
+        .with("settings get global package_verifier_user_consent", "1\n")
+        .with("settings get global upload_apk_enable", "1\n")
+        .with("settings get global verifier_verify_adb_installs", "null\n")
+        .with(
+            "dumpsys -l",
+            "Currently running services:\n  accessibility\n  device_policy\n  package\n",
+        )
+        .with(
+            "dumpsys accessibility",
+            &include_bytes!("../test_data/dumpsys/accessibility/spylive360.txt")[..],
+        )
+        .with(
+            "settings get secure enabled_accessibility_services",
+            "com.wifi0/com.wifi0.AccessibilityReceiver4\n",
+        )
+        .with(
+            "cat /data/misc/adb/adb_keys 2>&1",
+            "cat: /data/misc/adb/adb_keys: Permission denied\n",
+        )
+        .with(
+            "dumpsys device_policy",
+            &include_bytes!("../test_data/dumpsys/device_policy/spyware.txt")[..],
+        )
+        .with(
+            "pm list packages -f -i",
+            "package:/data/app/~~yOGa2vl0xQ==/com.wifi0-Vn9Pp8g==/base.apk=com.wifi0  installer=null\n",
+        )
+        .with(
+            "dumpsys package com.wifi0",
+            &include_bytes!("../test_data/dumpsys/package/spylive360.txt")[..],
+        )
+        .with(
+            "find /sdcard/Download -type f 2>&1",
+            "/sdcard/Download/com.wifi0.apk\n",
+        )
+}
+
+/// Scan the mock device and collect everything that has been reported
+pub async fn scan_mock_device() -> Result<Vec<(Option<String>, Suspicion)>> {
+    let device = mock_device();
+    let mut rules = Rules::default();
+    rules.load_yaml("self-test", RULES)?;
+
+    let (tx, mut rx) = mpsc::channel(5);
+    let scan = async move {
+        let mut report = ScanNotifier::Channel(tx);
+        scan::run(&device, &rules, &scan::Settings::default(), &mut report).await
+    };
+    let collect = async {
+        let mut findings = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                ScanEvent::Suspicion(sus) => findings.push((None, sus)),
+                ScanEvent::App { name, sus } => findings.push((Some(name), sus)),
+                ScanEvent::Warning(_) | ScanEvent::Check(_) | ScanEvent::Progress { .. } => (),
+            }
+        }
+        findings
+    };
+
+    let (ret, findings) = tokio::join!(scan, collect);
+    ret.context("Scan of mock device has failed")?;
+    Ok(findings)
+}
+
+/// Run a full scan against the mock device and verify the known findings are reported
+pub async fn run() -> Result<()> {
+    let findings = scan_mock_device().await?;
+    info!("Scan of mock device reported {} findings", findings.len());
+
+    let mut missing = 0;
+    for (app, level, description) in EXPECTED {
+        let found = findings.iter().any(|(name, sus)| {
+            name.as_deref() == *app && sus.level == *level && sus.description == *description
+        });
+        if found {
+            info!("Found expected finding: {description:?}");
+        } else {
+            error!("Missing expected finding (app={app:?}, level={level:?}): {description:?}");
+            missing += 1;
+        }
+    }
+
+    if missing > 0 {
+        bail!(
+            "Self-test failed, {missing} of {} expected findings are missing",
+            EXPECTED.len()
+        );
+    }
+    info!("Self-test passed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_self_test() {
+        run().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast() {
+        let mut rules = Rules::default();
+        rules.load_yaml("self-test", RULES).unwrap();
+        let settings = scan::Settings {
+            fail_fast: Some(SuspicionLevel::High),
+            ..Default::default()
+        };
+        let err = scan::run(&mock_device(), &rules, &settings, &mut ScanNotifier::Null)
+            .await
+            .unwrap_err();
+        match err.downcast_ref::<ScanError>() {
+            Some(ScanError::FailFast { level, description }) => {
+                assert_eq!(*level, SuspicionLevel::High);
+                assert_eq!(description, "Google Play Protect is turned off");
+            }
+            _ => panic!("Scan was not stopped by the first finding: {err:#}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_report() {
+        let mut rules = Rules::default();
+        rules.load_yaml("self-test", RULES).unwrap();
+        let mut report = ScanNotifier::Collect(scan::Findings::default());
+        scan::run(&mock_device(), &rules, &Default::default(), &mut report)
+            .await
+            .unwrap();
+        let ScanNotifier::Collect(findings) = report else {
+            panic!("Notifier has been replaced");
+        };
+        let device = Default::default();
+        let text = crate::export::render_text(&findings.report("ABC123", &device));
+        assert!(text.starts_with("spytrap-adb report for \"ABC123\"\n"));
+        assert!(text.contains("  [high] Google Play Protect is turned off (advisory)\n"));
+    }
+}
@@ -0,0 +1,134 @@
+//! The triage state of a report, saved to a file so another analyst can continue where it was left
+use crate::errors::*;
+use crate::export;
+use crate::ioc::{Suspicion, SuspicionLevel};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// A finding the analyst has marked as reviewed
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Reviewed {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// when the session has been exported
+    pub time: i64,
+    pub serial: String,
+    #[serde(default)]
+    pub device: BTreeMap<String, String>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+    #[serde(default)]
+    pub findings: Vec<Suspicion>,
+    #[serde(default)]
+    pub apps: IndexMap<String, Vec<Suspicion>>,
+    #[serde(default)]
+    pub reviewed: Vec<Reviewed>,
+    /// apps whose findings are shown in the report view
+    #[serde(default)]
+    pub expanded: BTreeSet<String>,
+    /// files collected from the device, eg. bugreports
+    #[serde(default)]
+    pub artifacts: Vec<PathBuf>,
+    /// checks that reported warnings and can be run again, with their warnings
+    #[serde(default)]
+    pub incomplete: BTreeMap<String, Vec<String>>,
+    /// the scan did not run to completion
+    #[serde(default)]
+    pub cancelled: bool,
+    /// how long the scan took, in seconds
+    pub duration: Option<u64>,
+    /// hide findings below this level in the report view
+    pub filter: Option<SuspicionLevel>,
+    /// the selected row of the report view
+    #[serde(default)]
+    pub cursor: usize,
+    /// the findings are grouped by severity and kind
+    #[serde(default)]
+    pub tree: bool,
+}
+
+/// The default filename for a session, based on the device serial and the time of export
+pub fn filename(serial: &str, timestamp: i64) -> String {
+    let serial = export::safe_serial(serial);
+    format!("spytrap-session-{serial}-{timestamp}.json")
+}
+
+pub async fn save(path: &Path, session: &Session) -> Result<()> {
+    let mut buf = serde_json::to_vec_pretty(session)?;
+    buf.push(b'\n');
+    debug!("Writing session to {path:?}...");
+    fs::write(path, &buf)
+        .await
+        .with_context(|| anyhow!("Failed to write session to {path:?}"))?;
+    Ok(())
+}
+
+pub async fn load(path: &Path) -> Result<Session> {
+    let buf = fs::read(path)
+        .await
+        .with_context(|| anyhow!("Failed to read session from {path:?}"))?;
+    serde_json::from_slice(&buf).with_context(|| anyhow!("Failed to parse session from {path:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::Kind;
+
+    #[tokio::test]
+    async fn test_save_load() {
+        let path =
+            std::env::temp_dir().join(format!("spytrap-session-{}.json", std::process::id()));
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Package \"com.wifi0\" is the device owner",
+        );
+        let session = Session {
+            time: 1719963254,
+            serial: "ABC123".to_string(),
+            device: BTreeMap::from([("model".to_string(), "Pixel_7".to_string())]),
+            notes: "Owner reports location leaks".to_string(),
+            warnings: vec!["Failed to list files".to_string()],
+            findings: Vec::new(),
+            apps: IndexMap::from([("com.wifi0".to_string(), vec![sus.clone()])]),
+            reviewed: vec![Reviewed {
+                app: Some("com.wifi0".to_string()),
+                description: sus.description.clone(),
+            }],
+            expanded: BTreeSet::from(["com.wifi0".to_string()]),
+            artifacts: Vec::new(),
+            incomplete: BTreeMap::from([(
+                "files".to_string(),
+                vec!["Failed to list files".to_string()],
+            )]),
+            cancelled: false,
+            duration: Some(95),
+            filter: Some(SuspicionLevel::Low),
+            cursor: 1,
+            tree: false,
+        };
+        save(&path, &session).await.unwrap();
+        let loaded = load(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, session);
+
+        // sessions written by other tools may leave out the triage state
+        let minimal = serde_json::from_str::<Session>(
+            r#"{"time":1719963254,"serial":"ABC123","duration":null,"filter":null}"#,
+        )
+        .unwrap();
+        assert!(minimal.reviewed.is_empty());
+        assert_eq!(minimal.cursor, 0);
+    }
+}
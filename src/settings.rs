@@ -1,40 +1,55 @@
+use crate::checks::Check;
 use crate::errors::*;
-use crate::ioc::{Suspicion, SuspicionLevel};
-use forensic_adb::Device;
+use crate::ioc::{Kind, Suspicion, SuspicionLevel};
+use crate::rules::Rules;
+use crate::scan::{self, ScanNotifier};
+use crate::shell::Shell;
+use async_trait::async_trait;
 use std::collections::HashMap;
 
+/// How to turn Play Protect back on, it's a common first step before installing stalkerware
+const PLAY_PROTECT_REMEDIATION: &str =
+    "Open the Play Store, tap the profile icon, then Play Protect, and turn on \"Scan apps with Play Protect\"";
+
 const SETTINGS: &[(&str, &[&str])] = &[(
     "global",
     &[
         "package_verifier_enable",
         "package_verifier_user_consent",
         "upload_apk_enable",
+        "verifier_verify_adb_installs",
     ],
 )];
 
-pub async fn dump(device: &Device) -> Result<HashMap<String, Settings>> {
+pub async fn get(device: &dyn Shell, namespace: &str, key: &str) -> Result<Option<String>> {
+    let cmd = format!("settings get {namespace} {key}");
+    debug!("Executing {:?}", cmd);
+    let output = device
+        .exec(&cmd)
+        .await
+        .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
+    let mut output = String::from_utf8_lossy(&output).into_owned();
+    if output.ends_with('\n') {
+        output.pop();
+    }
+    debug!("Received setting for namespace={namespace:?} key={key:?} from device: {output:?}");
+
+    if output != "null" {
+        Ok(Some(output))
+    } else {
+        Ok(None)
+    }
+}
+
+pub async fn dump(device: &dyn Shell) -> Result<HashMap<String, Settings>> {
     let mut out = HashMap::<_, Settings>::new();
 
     for (namespace, keys) in SETTINGS {
         let settings = out.entry(namespace.to_string()).or_default();
 
         for key in *keys {
-            let cmd = format!("settings get {namespace} {key}");
-            debug!("Executing {:?}", cmd);
-            let output = device
-                .execute_host_exec_out_command(&cmd)
-                .await
-                .with_context(|| anyhow!("Failed to run: {:?}", cmd))?;
-            let mut output = String::from_utf8_lossy(&output).into_owned();
-            if output.ends_with('\n') {
-                output.pop();
-            }
-            debug!(
-                "Received setting for namespace={namespace:?} key={key:?} from device: {output:?}"
-            );
-
-            if output != "null" {
-                settings.insert(key.to_string(), output);
+            if let Some(value) = get(device, namespace, key).await? {
+                settings.insert(key.to_string(), value);
             }
         }
     }
@@ -42,6 +57,36 @@ pub async fn dump(device: &Device) -> Result<HashMap<String, Settings>> {
     Ok(out)
 }
 
+pub struct SettingsCheck;
+
+#[async_trait]
+impl Check for SettingsCheck {
+    fn id(&self) -> &'static str {
+        "settings"
+    }
+
+    fn description(&self) -> &'static str {
+        "Look for system settings that disable Google Play Protect or the verification of installed apps"
+    }
+
+    async fn run(
+        &self,
+        device: &dyn Shell,
+        _rules: &Rules,
+        _settings: &scan::Settings,
+        report: &mut ScanNotifier,
+    ) -> Result<()> {
+        info!("Enumerating android settings");
+        for (_namespace, settings) in dump(device).await? {
+            for sus in settings.audit() {
+                warn!("Suspicious {:?}: {}", sus.level, sus.description);
+                report.sus(sus).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Default)]
 pub struct Settings {
     pub values: HashMap<String, String>,
@@ -56,46 +101,91 @@ impl Settings {
         let mut sus = Vec::new();
         for (key, value) in &self.values {
             match key.as_str() {
-                "package_verifier_enable" => {
-                    if value != "1" {
-                        warn!("Google Play Protect is turned off");
-                        sus.push(Suspicion {
-                            level: SuspicionLevel::High,
-                            description: "Google Play Protect is turned off".to_string(),
-                        });
-                    }
+                "package_verifier_enable" if value != "1" => {
+                    warn!("Google Play Protect is turned off");
+                    sus.push(
+                        Suspicion::new(
+                            SuspicionLevel::High,
+                            Kind::Setting,
+                            "Google Play Protect is turned off",
+                        )
+                        .with_remediation(Some(PLAY_PROTECT_REMEDIATION.to_string())),
+                    );
                 }
                 "package_verifier_user_consent" => {
                     if value == "1" {
                         info!("Scanning apps with Google Play Protect is enabled");
-                        sus.push(Suspicion {
-                            level: SuspicionLevel::Good,
-                            description: "Scanning apps with Google Play Protect is enabled"
-                                .to_string(),
-                        });
+                        sus.push(Suspicion::new(
+                            SuspicionLevel::Good,
+                            Kind::Setting,
+                            "Scanning apps with Google Play Protect is enabled",
+                        ));
                     } else {
                         warn!("Scanning apps with Google Play Protect is disabled");
-                        sus.push(Suspicion {
-                            level: SuspicionLevel::High,
-                            description: "Scanning apps with Google Play Protect is disabled"
-                                .to_string(),
-                        });
-                    }
-                }
-                "upload_apk_enable" => {
-                    if value != "1" {
-                        warn!(
-                            "Automatic upload of suspicious apps to Google Play has been disabled"
+                        sus.push(
+                            Suspicion::new(
+                                SuspicionLevel::High,
+                                Kind::Setting,
+                                "Scanning apps with Google Play Protect is disabled",
+                            )
+                            .with_remediation(Some(PLAY_PROTECT_REMEDIATION.to_string())),
                         );
-                        sus.push(Suspicion {
-                            level: SuspicionLevel::High,
-                            description: "Automatic upload of suspicious apps to Google Play has been disabled".to_string(),
-                        });
                     }
                 }
+                "upload_apk_enable" if value != "1" => {
+                    warn!("Automatic upload of suspicious apps to Google Play has been disabled");
+                    sus.push(Suspicion::new(
+                        SuspicionLevel::High,
+                        Kind::Setting,
+                        "Automatic upload of suspicious apps to Google Play has been disabled",
+                    ));
+                }
+                "verifier_verify_adb_installs" if value == "0" => {
+                    warn!("Apps installed over USB are not verified by Google Play Protect");
+                    sus.push(Suspicion::new(
+                        SuspicionLevel::Medium,
+                        Kind::Setting,
+                        "Apps installed over USB are not verified by Google Play Protect",
+                    ));
+                }
                 _ => (),
             }
         }
         sus
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_play_protect() {
+        let mut settings = Settings::default();
+        settings.insert("package_verifier_enable".to_string(), "0".to_string());
+        settings.insert("verifier_verify_adb_installs".to_string(), "0".to_string());
+        let mut sus = settings.audit();
+        sus.sort_by(|a, b| a.description.cmp(&b.description));
+        assert_eq!(
+            sus,
+            &[
+                Suspicion::new(
+                    SuspicionLevel::Medium,
+                    Kind::Setting,
+                    "Apps installed over USB are not verified by Google Play Protect"
+                ),
+                Suspicion::new(
+                    SuspicionLevel::High,
+                    Kind::Setting,
+                    "Google Play Protect is turned off"
+                )
+                .with_remediation(Some(PLAY_PROTECT_REMEDIATION.to_string())),
+            ]
+        );
+
+        let mut settings = Settings::default();
+        settings.insert("package_verifier_enable".to_string(), "1".to_string());
+        settings.insert("verifier_verify_adb_installs".to_string(), "1".to_string());
+        assert_eq!(settings.audit(), &[]);
+    }
+}
@@ -0,0 +1,202 @@
+use crate::errors::*;
+use async_trait::async_trait;
+use forensic_adb::Device;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{self, Instant};
+
+/// Run commands on a device, implemented by adb devices and the mock used for testing
+#[async_trait]
+pub trait Shell: fmt::Debug + Send + Sync {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>>;
+}
+
+#[async_trait]
+impl Shell for Device {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        let output = self.execute_host_exec_out_command(cmd).await?;
+        Ok(output)
+    }
+}
+
+/// A fake device that answers with canned output for known commands
+#[derive(Debug, Default)]
+pub struct MockShell {
+    responses: HashMap<String, Vec<u8>>,
+}
+
+impl MockShell {
+    pub fn with(mut self, cmd: &str, output: impl Into<Vec<u8>>) -> Self {
+        self.responses.insert(cmd.to_string(), output.into());
+        self
+    }
+}
+
+#[async_trait]
+impl Shell for MockShell {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        let output = self
+            .responses
+            .get(cmd)
+            .with_context(|| anyhow!("Mock device has no output for command: {cmd:?}"))?;
+        Ok(output.clone())
+    }
+}
+
+/// Runs every command a second time if it failed, to get past transient adb errors
+#[derive(Debug)]
+pub struct RetryShell<'a> {
+    inner: &'a dyn Shell,
+}
+
+impl<'a> RetryShell<'a> {
+    pub fn new(inner: &'a dyn Shell) -> Self {
+        RetryShell { inner }
+    }
+}
+
+#[async_trait]
+impl Shell for RetryShell<'_> {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        match self.inner.exec(cmd).await {
+            Ok(output) => Ok(output),
+            Err(err) => {
+                warn!("Command {cmd:?} has failed, retrying: {err:#}");
+                self.inner.exec(cmd).await
+            }
+        }
+    }
+}
+
+/// Waits between commands, so slow devices are not overwhelmed by a fast series of commands
+#[derive(Debug)]
+pub struct ThrottleShell<'a> {
+    inner: &'a dyn Shell,
+    interval: Duration,
+    /// the earliest time the next command may be started
+    next: Mutex<Option<Instant>>,
+}
+
+impl<'a> ThrottleShell<'a> {
+    pub fn new(inner: &'a dyn Shell, interval: Duration) -> Self {
+        ThrottleShell {
+            inner,
+            interval,
+            next: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl Shell for ThrottleShell<'_> {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        {
+            // commands of concurrent tasks are paced too, they wait for their turn here
+            let mut next = self.next.lock().await;
+            if let Some(next) = *next {
+                time::sleep_until(next).await;
+            }
+            *next = Some(Instant::now() + self.interval);
+        }
+        self.inner.exec(cmd).await
+    }
+}
+
+/// Gives up on commands that take too long, eg. because the device has stopped responding
+#[derive(Debug)]
+pub struct TimeoutShell<'a> {
+    inner: &'a dyn Shell,
+    timeout: Duration,
+}
+
+impl<'a> TimeoutShell<'a> {
+    pub fn new(inner: &'a dyn Shell, timeout: Duration) -> Self {
+        TimeoutShell { inner, timeout }
+    }
+}
+
+#[async_trait]
+impl Shell for TimeoutShell<'_> {
+    async fn exec(&self, cmd: &str) -> Result<Vec<u8>> {
+        time::timeout(self.timeout, self.inner.exec(cmd))
+            .await
+            .map_err(|_| {
+                anyhow!(
+                    "Command {cmd:?} did not finish within {}s, the device may have stopped responding",
+                    self.timeout.as_secs_f32()
+                )
+            })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails only the first command it is asked to run
+    #[derive(Debug, Default)]
+    struct FlakyShell {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Shell for FlakyShell {
+        async fn exec(&self, _cmd: &str) -> Result<Vec<u8>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                bail!("device offline");
+            }
+            Ok(b"ok".to_vec())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_shell() {
+        let flaky = FlakyShell::default();
+        assert!(flaky.exec("id").await.is_err());
+
+        let flaky = FlakyShell::default();
+        let shell = RetryShell::new(&flaky);
+        assert_eq!(shell.exec("id").await.unwrap(), b"ok");
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Never answers, like a device that hangs
+    #[derive(Debug)]
+    struct HangingShell;
+
+    #[async_trait]
+    impl Shell for HangingShell {
+        async fn exec(&self, _cmd: &str) -> Result<Vec<u8>> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_shell() {
+        let shell = TimeoutShell::new(&HangingShell, Duration::from_millis(10));
+        let err = shell.exec("dumpsys package").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Command \"dumpsys package\" did not finish within 0.01s, the device may have stopped responding"
+        );
+
+        let device = MockShell::default().with("id", "uid=2000(shell)\n");
+        let shell = TimeoutShell::new(&device, Duration::from_millis(10));
+        assert_eq!(shell.exec("id").await.unwrap(), b"uid=2000(shell)\n");
+    }
+
+    #[tokio::test]
+    async fn test_throttle_shell() {
+        let device = MockShell::default().with("id", "uid=2000(shell)\n");
+        let shell = ThrottleShell::new(&device, Duration::from_millis(20));
+        let started = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(shell.exec("id").await.unwrap(), b"uid=2000(shell)\n");
+        }
+        // the first command is sent right away
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
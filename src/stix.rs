@@ -0,0 +1,149 @@
+//! Export app findings as a STIX 2.1 bundle for threat intelligence platforms
+use crate::errors::*;
+use crate::export::Report;
+use crate::ioc::SuspicionLevel;
+use crate::utils;
+use chrono::{offset::Utc, SecondsFormat, TimeZone};
+use serde_json::{json, Value};
+
+/// Derive a stable STIX identifier from the given seed
+fn id(kind: &str, seed: &str) -> String {
+    let hash = utils::sha256(format!("{kind}:{seed}").as_bytes());
+    // format as a version 5 uuid, with the variant bits set
+    let variant = ["8", "9", "a", "b"][usize::from_str_radix(&hash[16..17], 16).unwrap_or(0) % 4];
+    format!(
+        "{kind}--{}-{}-5{}-{variant}{}-{}",
+        &hash[..8],
+        &hash[8..12],
+        &hash[13..16],
+        &hash[17..20],
+        &hash[20..32]
+    )
+}
+
+fn timestamp(timestamp: i64) -> String {
+    let utc = Utc.timestamp_opt(timestamp, 0).unwrap();
+    utc.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Every app with findings is an observed `software` object, apps with findings of high
+/// severity are also turned into an indicator. Device-wide findings have no observable to
+/// attach to and are left out.
+pub fn render(report: &Report<'_>, now: i64) -> Result<String> {
+    let now = timestamp(now);
+    let mut objects = Vec::new();
+
+    for (name, findings) in &report.apps {
+        let software = id("software", name);
+        objects.push(json!({
+            "type": "software",
+            "spec_version": "2.1",
+            "id": software,
+            "name": name,
+        }));
+        objects.push(json!({
+            "type": "observed-data",
+            "spec_version": "2.1",
+            "id": id("observed-data", &format!("{}:{name}", report.serial)),
+            "created": now,
+            "modified": now,
+            "first_observed": now,
+            "last_observed": now,
+            "number_observed": 1,
+            "object_refs": [software],
+            "x_spytrap_device": report.serial,
+            "x_spytrap_findings": findings,
+        }));
+
+        if let Some(sus) = findings
+            .iter()
+            .find(|sus| sus.level == SuspicionLevel::High)
+        {
+            let escaped = name.replace('\\', "\\\\").replace('\'', "\\'");
+            objects.push(json!({
+                "type": "indicator",
+                "spec_version": "2.1",
+                "id": id("indicator", name),
+                "created": now,
+                "modified": now,
+                "name": format!("Android app {name:?}"),
+                "description": sus.description,
+                "indicator_types": ["malicious-activity"],
+                "pattern": format!("[software:name = '{escaped}']"),
+                "pattern_type": "stix",
+                "valid_from": now,
+            }));
+        }
+    }
+
+    let bundle = json!({
+        "type": "bundle",
+        "id": id("bundle", &format!("{}:{now}", report.serial)),
+        "objects": Value::Array(objects),
+    });
+    let out = serde_json::to_string_pretty(&bundle)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::{Kind, Suspicion};
+    use indexmap::IndexMap;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_id() {
+        assert_eq!(
+            id("software", "com.wifi0"),
+            "software--6cd17322-9461-5c29-96b3-839bd8a9b2bf"
+        );
+    }
+
+    #[test]
+    fn test_render_bundle() {
+        let device = BTreeMap::new();
+        let high = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Other,
+            "Found known stalkerware with rule",
+        );
+        let low = Suspicion::new(
+            SuspicionLevel::Low,
+            Kind::Other,
+            "Package has requested permission",
+        );
+        let report = Report {
+            serial: "ABC123".into(),
+            device: &device,
+            min_level: None,
+            notes: None,
+            warnings: vec![],
+            findings: vec![&high],
+            apps: IndexMap::from([("com.wifi0", vec![&high]), ("org.example", vec![&low])]),
+            reviewed: vec![],
+        };
+        let bundle = render(&report, 1719963254).unwrap();
+        let bundle = serde_json::from_str::<Value>(&bundle).unwrap();
+        assert_eq!(bundle["type"], "bundle");
+        let types = bundle["objects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|obj| obj["type"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            &[
+                "software",
+                "observed-data",
+                "indicator",
+                "software",
+                "observed-data"
+            ]
+        );
+        let indicator = &bundle["objects"][2];
+        assert_eq!(indicator["pattern"], "[software:name = 'com.wifi0']");
+        assert_eq!(indicator["valid_from"], "2024-07-02T23:34:14.000Z");
+    }
+}
@@ -0,0 +1,75 @@
+use crate::errors::*;
+use crate::ioc::{Suspicion, SuspicionLevel};
+use ::syslog::{Facility, Formatter3164, Logger, LoggerBackend};
+
+/// Sends findings to the local syslog daemon
+pub struct Syslog {
+    logger: Logger<LoggerBackend, Formatter3164>,
+    serial: String,
+}
+
+impl Syslog {
+    pub fn connect(serial: &str) -> Result<Self> {
+        let formatter = Formatter3164 {
+            facility: Facility::LOG_USER,
+            hostname: None,
+            process: env!("CARGO_PKG_NAME").to_string(),
+            pid: std::process::id(),
+        };
+        let logger = ::syslog::unix(formatter)
+            .map_err(|err| anyhow!("Failed to connect to syslog: {err}"))?;
+        Ok(Syslog {
+            logger,
+            serial: serial.to_string(),
+        })
+    }
+
+    pub fn sus(&mut self, app: Option<&str>, sus: &Suspicion) -> Result<()> {
+        let msg = message(&self.serial, app, sus);
+        match sus.level {
+            SuspicionLevel::High => self.logger.err(msg),
+            SuspicionLevel::Medium => self.logger.warning(msg),
+            SuspicionLevel::Low => self.logger.notice(msg),
+            SuspicionLevel::Info | SuspicionLevel::Good => self.logger.info(msg),
+        }
+        .map_err(|err| anyhow!("Failed to write to syslog: {err}"))
+    }
+
+    pub fn warning(&mut self, warning: &str) -> Result<()> {
+        self.logger
+            .warning(format!("serial={:?} warning: {warning}", self.serial))
+            .map_err(|err| anyhow!("Failed to write to syslog: {err}"))
+    }
+}
+
+fn message(serial: &str, app: Option<&str>, sus: &Suspicion) -> String {
+    let mut msg = format!("serial={serial:?}");
+    if let Some(app) = app {
+        msg.push_str(&format!(" app={app:?}"));
+    }
+    msg.push_str(&format!(" {}: {}", sus.level.as_str(), sus.description));
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ioc::Kind;
+
+    #[test]
+    fn test_message() {
+        let sus = Suspicion::new(
+            SuspicionLevel::High,
+            Kind::Package,
+            "Found known stalkerware",
+        );
+        assert_eq!(
+            message("ABC123", Some("com.wifi0"), &sus),
+            "serial=\"ABC123\" app=\"com.wifi0\" high: Found known stalkerware"
+        );
+        assert_eq!(
+            message("ABC123", None, &sus),
+            "serial=\"ABC123\" high: Found known stalkerware"
+        );
+    }
+}
@@ -1,9 +1,22 @@
+use crate::args::Args;
+use crate::bugreport;
+use crate::bundle;
+use crate::clipboard;
+use crate::config::Config;
+use crate::dashboard::{self, Dashboard};
 use crate::errors::*;
-use crate::ioc::{Repository, RepositoryContent, Suspicion, SuspicionLevel};
+use crate::export;
+use crate::history;
+use crate::i18n::{Lang, Msg};
+use crate::ioc::{Kind, Repository, RepositoryContent, RuleFile, Suspicion, SuspicionLevel};
+use crate::remediate::{self, Remediation};
+use crate::risk::RiskScore;
+use crate::rules::{RuleSource, Rules};
 use crate::scan;
+use crate::session;
 use crate::utils;
 use crossterm::event::EventStream;
-use crossterm::event::{KeyEvent, KeyModifiers};
+use crossterm::event::{KeyEvent, KeyEventKind, KeyModifiers};
 use crossterm::{
     event::{Event, KeyCode},
     execute,
@@ -13,20 +26,26 @@ use forensic_adb::{AndroidStorageInput, DeviceInfo, Host};
 use indexmap::IndexMap;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Frame, Terminal,
 };
+use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::convert::Infallible;
+use std::env;
 use std::io;
 use std::io::Stdout;
-use std::iter::Chain;
-use std::slice::Iter;
-use std::time::Duration;
+use std::io::Write;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicU64};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::process;
 use tokio::sync::mpsc;
 use tokio::task::JoinSet;
 use tokio::time;
@@ -37,25 +56,149 @@ const DARK_GREY: Color = Color::Rgb(0x3b, 0x3b, 0x3b);
 const PAGE_MODIFIER: usize = 10;
 /// The number of lines used by the spytrap-adb UI around the scroll view
 const SCROLL_CHROME_HEIGHT: usize = 6;
+/// The number of lines of the legend below the report
+const LEGEND_HEIGHT: usize = 2;
+/// The number of lines of the box above the report that shows why the scan has failed
+const ERROR_HEIGHT: usize = 3;
 
 const DEVICE_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
 const ACTIVITY_TICK_INTERVAL: Duration = Duration::from_millis(100);
+/// Redraw this often while a scan or download is running, so animations don't stutter
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 const ACTIVITY: &[&str] = &[".", "o", "O", "°", " ", " ", "°", "O", "o", ".", " ", " "];
 
 /// Check for updates if this many seconds elapsed since last successful update check
 const DATABASE_UPDATE_CHECK_INTERVAL: i64 = 60 * 60 * 3;
+const DEFAULT_EDITOR: &str = "vi";
+const LOCAL_RULES_TEMPLATE: &str = "\
+# Custom rules, in the same format as the stalkerware-indicators repository:
+#
+# - name: Example
+#   type: stalkerware
+#   packages:
+#   - com.example.stalkerware
+#   c2:
+#     domains:
+#     - example.com
+#   remediation: Shown in exported reports as what to do about a match
+#   # the severity of a match, high if it's not set
+#   level: medium
+#   # how likely a match is a true positive: low, medium or high
+#   confidence: medium
+#   # only use the rule for some devices
+#   applies_to:
+#     manufacturers: [samsung]
+#     min_sdk: 29
+[]
+";
 
 #[derive(Debug)]
 pub enum Message {
-    Suspicion(Suspicion),
-    App { name: String, sus: Suspicion },
+    /// Something has happened during the scan with this id
+    Scan {
+        id: u64,
+        event: ScanMessage,
+    },
     StartDownload,
-    ScanTick,
-    ScanEnded,
     DownloadTick,
     DownloadEnded(Option<Repository>),
+    RulesUpdated {
+        url: String,
+        ret: Result<String, String>,
+    },
     DeviceRefreshTick,
     DatabaseUpdateAvailable(bool),
+    DashboardLoaded {
+        serial: String,
+        dashboard: Result<Dashboard, String>,
+    },
+    BugreportEnded {
+        serial: String,
+        ret: Result<PathBuf, String>,
+    },
+    RemediationEnded {
+        action: Remediation,
+        serial: String,
+        package: String,
+        user: Option<u32>,
+        ret: Result<(), String>,
+    },
+    DevicesLoaded(std::result::Result<Vec<DeviceInfo>, forensic_adb::DeviceError>),
+}
+
+#[derive(Debug)]
+pub enum ScanMessage {
+    Suspicion(Suspicion),
+    App {
+        name: String,
+        sus: Suspicion,
+    },
+    Tick,
+    Ended {
+        cancelled: bool,
+    },
+    /// The scan has stopped early, sent before it has ended
+    Error(String),
+    Warning(String),
+    CheckStarted(String),
+    Progress {
+        done: usize,
+        total: Option<usize>,
+    },
+}
+
+impl From<scan::ScanEvent> for ScanMessage {
+    fn from(event: scan::ScanEvent) -> Self {
+        match event {
+            scan::ScanEvent::Suspicion(sus) => ScanMessage::Suspicion(sus),
+            scan::ScanEvent::App { name, sus } => ScanMessage::App { name, sus },
+            scan::ScanEvent::Warning(warning) => ScanMessage::Warning(warning),
+            scan::ScanEvent::Check(id) => ScanMessage::CheckStarted(id),
+            scan::ScanEvent::Progress { done, total } => ScanMessage::Progress { done, total },
+        }
+    }
+}
+
+/// Keyboard input that is captured by a dialog in the status line
+#[derive(Debug, PartialEq)]
+pub enum Prompt {
+    ExportFormat {
+        filtered: bool,
+        selected: usize,
+    },
+    ExportPath {
+        filtered: bool,
+        format: export::Format,
+        path: String,
+    },
+    Bugreport {
+        serial: String,
+    },
+    Confirm {
+        action: Remediation,
+        package: String,
+        /// the secondary user the app is installed for, `None` for the primary user
+        user: Option<u32>,
+        /// the device that is going to be modified
+        serial: String,
+    },
+    Notes(String),
+}
+
+/// Apply a key press to a single-line text input, returns false if the key wasn't handled
+fn edit_text(text: &mut String, key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Backspace => {
+            text.pop();
+            true
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            text.push(c);
+            true
+        }
+        _ => false,
+    }
 }
 
 #[derive(Debug)]
@@ -78,7 +221,57 @@ pub struct SavedCursor {
     interval: Option<Duration>,
 }
 
+#[derive(Debug, Default)]
+pub struct Options {
+    pub severity_symbols: bool,
+    pub kind_symbols: bool,
+    /// the terminal can display unicode symbols
+    pub unicode: bool,
+    pub arrival_order: bool,
+    /// show findings of level info and good without changing the filter
+    pub show_advisories: bool,
+    /// ring the terminal bell for findings of this severity or higher
+    pub bell: Option<SuspicionLevel>,
+    /// never modify the device, this disables uninstalling and disabling apps
+    pub read_only: bool,
+    /// remove the serial, device details and notes from exported reports
+    pub anonymize: bool,
+    /// return to the device list after this long without input and no running scan
+    pub idle_timeout: Option<Duration>,
+    /// disconnect network devices from adb when the idle timeout clears the session
+    pub idle_disconnect: bool,
+    /// record the findings and the fingerprint of every scan, so they can be searched and compared later
+    pub keep_history: bool,
+    /// hide serials and account names on screen, exports are not affected
+    pub mask: bool,
+    /// ask again for authorization to scan, after the idle timeout has cleared the session
+    pub require_consent: bool,
+    pub lang: Lang,
+}
+
+impl From<&Args> for Options {
+    fn from(args: &Args) -> Options {
+        Options {
+            severity_symbols: args.severity_symbols,
+            kind_symbols: args.kind_symbols,
+            unicode: utils::is_unicode_locale(),
+            arrival_order: args.arrival_order,
+            show_advisories: args.show_advisories,
+            bell: args.bell,
+            read_only: args.read_only || !args.allow_remediation,
+            anonymize: args.anonymize,
+            idle_timeout: args.idle_timeout.map(Duration::from_secs),
+            idle_disconnect: args.idle_disconnect,
+            keep_history: args.keep_history,
+            mask: args.mask,
+            require_consent: args.require_consent,
+            lang: args.lang.unwrap_or_else(Lang::from_env),
+        }
+    }
+}
+
 pub struct App {
+    options: Options,
     adb_host: Host,
     repository: Repository,
     events_tx: mpsc::Sender<Message>,
@@ -87,19 +280,75 @@ pub struct App {
     timer_rx: Option<mpsc::Receiver<TimerCmd>>,
     current_timer: Option<Duration>,
     devices: Vec<DeviceInfo>,
+    /// false until adb has listed the connected devices for the first time
+    devices_loaded: bool,
     offset: usize,
     cursor: usize,
     /// the previous cursor positions before switching into a different scroll-view
     cursor_backtrace: Vec<SavedCursor>,
     scan: Option<Scan>,
+    /// the report that has been put aside with tab, with its cursor position
+    background_scan: Option<(Scan, SavedCursor)>,
+    /// the overview of a device that is shown before scanning it
+    dashboard: Option<DashboardView>,
+    /// statistics about the loaded rules, shown before scanning
+    rule_stats: Option<Result<Vec<RuleSource>, String>>,
+    /// the rule files to pick from at startup, if there's more than the downloaded ones
+    rule_selector: Option<Vec<(RuleFile, bool)>>,
+    /// the names of the rule files to load, `None` loads the downloaded ones
+    selected_rules: Option<Vec<String>>,
     download: Option<Download>,
+    /// the rules are being downloaded from the configured url
+    updating_rules: bool,
+    /// hide findings below this level in the report view
+    filter: Option<SuspicionLevel>,
+    config: Config,
+    /// leave out the apps in the next scan, toggled from the device list
+    skip_apps: bool,
+    /// show the debug representation of findings, for developers
+    raw_findings: bool,
+    /// move the cursor to every new finding as it arrives
+    follow: bool,
+    /// explain the colors and symbols of findings below the report
+    legend: bool,
+    /// a short message shown in the status line
+    status: Option<String>,
+    /// an open dialog that receives all key presses
+    prompt: Option<Prompt>,
+    /// a line for every scan that has been closed, printed on exit, with the serial of the device
+    scan_summaries: Vec<(String, String)>,
+    /// the files that have been written during this session
+    saved_files: Vec<PathBuf>,
+    /// when the operator has pressed a key the last time
+    last_input: Instant,
 }
 
 impl App {
-    pub fn new(adb_host: Host, repository: Repository) -> Self {
+    pub fn new(
+        adb_host: Host,
+        repository: Repository,
+        mut options: Options,
+        config: Config,
+    ) -> Self {
         let (events_tx, events_rx) = mpsc::channel(5);
         let (timer_tx, timer_rx) = mpsc::channel(5);
+        options.severity_symbols |= config.severity_symbols;
+        options.kind_symbols |= config.kind_symbols;
+        options.arrival_order |= config.arrival_order;
+        options.bell = options.bell.or(config.bell);
+        options.idle_timeout = options
+            .idle_timeout
+            .or(config.idle_timeout.map(Duration::from_secs));
+        options.idle_disconnect |= config.idle_disconnect;
+        options.keep_history |= config.keep_history;
+        options.require_consent |= config.require_consent;
+        options.show_advisories |= config.show_advisories;
+        // advisories are low-signal, keep the matched indicators front and center
+        let filter = config
+            .min_level
+            .or((!options.show_advisories).then_some(SuspicionLevel::Low));
         Self {
+            options,
             adb_host,
             repository,
             events_tx,
@@ -108,23 +357,57 @@ impl App {
             timer_rx: Some(timer_rx),
             current_timer: None,
             devices: Vec::new(),
+            devices_loaded: false,
             offset: 0,
             cursor: 0,
             cursor_backtrace: vec![],
             scan: None,
+            background_scan: None,
+            dashboard: None,
+            rule_stats: None,
+            rule_selector: None,
+            selected_rules: None,
             download: None,
+            updating_rules: false,
+            filter,
+            status: None,
+            prompt: None,
+            scan_summaries: Vec::new(),
+            saved_files: Vec::new(),
+            last_input: Instant::now(),
+            skip_apps: config.scan.skip_apps,
+            config,
+            raw_findings: false,
+            follow: false,
+            legend: false,
         }
     }
 
     pub async fn init(&mut self) -> Result<()> {
-        let devices = self
-            .adb_host
-            .devices::<Vec<_>>()
-            .await
-            .map_err(|e| anyhow!("Failed to list devices from adb: {}", e))?;
-        self.devices = devices;
+        // list devices in the background, so the interface can be drawn while adb is slow
+        let adb_host = self.adb_host.clone();
+        let events_tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let devices = adb_host.devices::<Vec<_>>().await;
+            events_tx.send(Message::DevicesLoaded(devices)).await.ok();
+        });
         self.start_timer(DEVICE_REFRESH_INTERVAL).await?;
 
+        match self.repository.rule_files().await {
+            Ok(files) if files.iter().any(|file| file.custom) => {
+                let files = files
+                    .into_iter()
+                    .map(|file| {
+                        let selected = !file.custom;
+                        (file, selected)
+                    })
+                    .collect();
+                self.rule_selector = Some(files);
+            }
+            Ok(_) => (),
+            Err(err) => warn!("Failed to discover rule files: {err:#}"),
+        }
+
         if let Some(content) = &self.repository.content {
             if !content.update_available
                 && utils::now() > content.last_update_check + DATABASE_UPDATE_CHECK_INTERVAL
@@ -175,39 +458,1156 @@ impl App {
         Ok(())
     }
 
+    /// Render a finding, with severity and kind symbols if configured
+    fn render_sus<'a>(&self, sus: &'a Suspicion) -> Vec<Span<'a>> {
+        if self.raw_findings {
+            return self.mask_spans(vec![Span::styled(
+                format!("{sus:?}"),
+                sus.level.terminal_color(),
+            )]);
+        }
+        let mut row = vec![
+            Span::styled(sus.marker(self.options.unicode), sus.level.terminal_color()),
+            Span::raw(" "),
+        ];
+        if self.options.severity_symbols {
+            row.push(Span::styled(sus.level.symbol(), sus.level.terminal_color()));
+            row.push(Span::raw(" "));
+        }
+        if self.options.kind_symbols {
+            row.push(Span::raw(sus.kind.symbol(self.options.unicode)));
+            row.push(Span::raw(" "));
+        }
+        row.extend(sus.to_terminal());
+        self.mask_spans(row)
+    }
+
+    /// The serials of all devices the interface knows about
+    fn known_serials(&self) -> Vec<&str> {
+        self.devices
+            .iter()
+            .map(|device| device.serial.as_str())
+            .chain(self.scan.as_ref().map(|scan| scan.device.serial.as_str()))
+            .chain(
+                self.background_scan
+                    .as_ref()
+                    .map(|(scan, _)| scan.device.serial.as_str()),
+            )
+            .chain(
+                self.scan_summaries
+                    .iter()
+                    .map(|(serial, _)| serial.as_str()),
+            )
+            .chain(
+                self.dashboard
+                    .as_ref()
+                    .map(|view| view.device.serial.as_str()),
+            )
+            .collect()
+    }
+
+    /// Hide serials and account names in text that is displayed, if masking is enabled
+    pub fn display<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.options.mask {
+            utils::mask_text(text, &self.known_serials())
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    fn mask_spans<'a>(&self, spans: Vec<Span<'a>>) -> Vec<Span<'a>> {
+        if !self.options.mask {
+            return spans;
+        }
+        let serials = self.known_serials();
+        spans
+            .into_iter()
+            .map(|mut span| {
+                let masked = match utils::mask_text(&span.content, &serials) {
+                    Cow::Owned(masked) => Some(masked),
+                    Cow::Borrowed(_) => None,
+                };
+                if let Some(masked) = masked {
+                    span.content = Cow::Owned(masked);
+                }
+                span
+            })
+            .collect()
+    }
+
+    /// Hide or show serials and account names on screen, exports always have the full values
+    pub fn toggle_mask(&mut self) {
+        self.options.mask = !self.options.mask;
+        let msg = if self.options.mask {
+            Msg::MaskingSerials
+        } else {
+            Msg::ShowingSerials
+        };
+        self.status = Some(self.options.lang.get(msg).to_string());
+    }
+
+    /// Switch the report between the list by app and the tree by severity and kind
+    pub fn toggle_tree(&mut self) {
+        let Some(scan) = &mut self.scan else {
+            return;
+        };
+        scan.tree = !scan.tree;
+        self.cursor = 0;
+        self.offset = 0;
+        let msg = if scan.tree {
+            Msg::GroupingFindings
+        } else {
+            Msg::ListingFindings
+        };
+        self.status = Some(self.options.lang.get(msg).to_string());
+    }
+
+    /// Whether the list of connected devices is the active view
+    pub fn is_device_list(&self) -> bool {
+        self.scan.is_none()
+            && self.dashboard.is_none()
+            && self.rule_stats.is_none()
+            && self.rule_selector.is_none()
+    }
+
     /// The number of visible lines in the current active view
     pub fn view_length(&self) -> usize {
         if let Some(scan) = &self.scan {
-            scan.findings.len()
-                + scan.apps.len()
-                + scan
-                    .apps
-                    .iter()
-                    .map(|(name, values)| {
-                        if scan.expanded.contains(name) {
-                            values.len()
-                        } else {
-                            0
-                        }
-                    })
-                    .sum::<usize>()
+            scan.rows(self.filter).len()
+        } else if let Some(files) = &self.rule_selector {
+            files.len()
+        } else if self.dashboard.is_some() || self.rule_stats.is_some() {
+            0
         } else {
             self.devices.len()
         }
     }
 
-    pub async fn save_cursor(&mut self) -> Result<()> {
-        self.cursor_backtrace.push(SavedCursor {
+    /// Write the current report to a file, either in full or only what the filter shows
+    pub async fn export_report(
+        &self,
+        filtered: bool,
+        format: export::Format,
+        path: PathBuf,
+    ) -> Result<Option<PathBuf>> {
+        let Some(scan) = &self.scan else {
+            return Ok(None);
+        };
+        let filter = if filtered { self.filter } else { None };
+        let mut report = scan.report(filter);
+        if self.options.anonymize {
+            report = report.anonymize();
+        }
+        export::write(&path, format, &report).await?;
+        Ok(Some(path))
+    }
+
+    /// Export the report and show the outcome in the status line
+    pub async fn export(&mut self, filtered: bool, format: export::Format, path: PathBuf) {
+        let lang = self.options.lang;
+        self.status = match self.export_report(filtered, format, path).await {
+            Ok(Some(path)) => {
+                let status = lang.format(Msg::ReportWritten, &[("path", &format!("{path:?}"))]);
+                self.saved_files.push(path);
+                Some(status)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                error!("Failed to export report: {err:#}");
+                Some(lang.format(Msg::ExportFailed, &[("err", &format!("{err:#}"))]))
+            }
+        };
+    }
+
+    /// Write the full report as json to a timestamped file in the export directory
+    pub async fn save_report(&self) -> Result<PathBuf> {
+        let scan = self.scan.as_ref().context("There's no report to save")?;
+        let mut report = scan.report(None);
+        if report.findings.is_empty() && report.apps.is_empty() {
+            bail!("The report has no findings to save");
+        }
+        if self.options.anonymize {
+            report = report.anonymize();
+        }
+        let format = export::Format::Json;
+        let path = self
+            .export_dir()
+            .join(export::filename(&report.serial, utils::now(), format));
+        export::write(&path, format, &report).await?;
+        Ok(path)
+    }
+
+    /// Save the report without asking for a format or path, the outcome is shown in the status line
+    pub async fn quick_save(&mut self) {
+        let lang = self.options.lang;
+        self.status = Some(match self.save_report().await {
+            Ok(path) => {
+                let status = lang.format(Msg::ReportWritten, &[("path", &format!("{path:?}"))]);
+                self.saved_files.push(path);
+                status
+            }
+            Err(err) => {
+                error!("Failed to save report: {err:#}");
+                lang.format(Msg::SaveFailed, &[("err", &format!("{err:#}"))])
+            }
+        });
+    }
+
+    /// Copy the report, as the filter shows it, to the clipboard
+    pub fn copy_report(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let mut report = scan.report(self.filter);
+        if self.options.anonymize {
+            report = report.anonymize();
+        }
+        let count = report.findings.len() + report.apps.values().map(Vec::len).sum::<usize>();
+        let text = export::render_text(&report);
+        let lang = self.options.lang;
+        self.status = Some(match clipboard::copy(&text) {
+            Ok(()) => lang.format(Msg::ReportCopied, &[("count", &count.to_string())]),
+            Err(err) => {
+                error!("Failed to copy report: {err:#}");
+                lang.format(Msg::CopyFailed, &[("err", &format!("{err:#}"))])
+            }
+        });
+    }
+
+    /// Process a key press while a prompt is open
+    pub async fn prompt_key(&mut self, prompt: Prompt, key: KeyEvent) {
+        self.prompt = match (prompt, key.code) {
+            (_, KeyCode::Esc) => None,
+            (Prompt::ExportFormat { filtered, selected }, code) => match code {
+                KeyCode::Left | KeyCode::Up | KeyCode::BackTab => Some(Prompt::ExportFormat {
+                    filtered,
+                    selected: selected.saturating_sub(1),
+                }),
+                KeyCode::Right | KeyCode::Down | KeyCode::Tab => Some(Prompt::ExportFormat {
+                    filtered,
+                    selected: (selected + 1).min(export::Format::ALL.len() - 1),
+                }),
+                KeyCode::Enter => {
+                    let format = export::Format::ALL[selected];
+                    let mut serial = self
+                        .scan
+                        .as_ref()
+                        .map(|s| s.device.serial.clone())
+                        .unwrap_or_default();
+                    if self.options.anonymize {
+                        serial = export::anonymize_serial(&serial);
+                    }
+                    let mut path = export::filename(&serial, utils::now(), format);
+                    if let Some(dir) = &self.config.export_dir {
+                        path = dir.join(path).to_string_lossy().into_owned();
+                    }
+                    Some(Prompt::ExportPath {
+                        filtered,
+                        format,
+                        path,
+                    })
+                }
+                _ => Some(Prompt::ExportFormat { filtered, selected }),
+            },
+            (
+                Prompt::ExportPath {
+                    filtered,
+                    format,
+                    mut path,
+                },
+                code,
+            ) => {
+                if code == KeyCode::Enter && !path.is_empty() {
+                    self.export(filtered, format, PathBuf::from(path)).await;
+                    None
+                } else {
+                    edit_text(&mut path, &key);
+                    Some(Prompt::ExportPath {
+                        filtered,
+                        format,
+                        path,
+                    })
+                }
+            }
+            (Prompt::Notes(mut text), code) => {
+                if code == KeyCode::Enter {
+                    if let Some(scan) = &mut self.scan {
+                        scan.notes = text;
+                    }
+                    None
+                } else {
+                    edit_text(&mut text, &key);
+                    Some(Prompt::Notes(text))
+                }
+            }
+            (Prompt::Bugreport { serial }, code) => {
+                if code == KeyCode::Char('y') {
+                    self.capture_bugreport(serial);
+                } else {
+                    self.status = Some(self.options.lang.get(Msg::NoBugreport).to_string());
+                }
+                None
+            }
+            (
+                Prompt::Confirm {
+                    action,
+                    package,
+                    user,
+                    serial,
+                },
+                code,
+            ) => {
+                if code == KeyCode::Char('y') {
+                    self.remediate(action, serial, package, user);
+                } else {
+                    let msg = action_msg(action, Msg::NotUninstalling, Msg::NotDisabling);
+                    self.status = Some(
+                        self.options
+                            .lang
+                            .format(msg, &[("package", &remediate::label(&package, user))]),
+                    );
+                    let outcome = remediate::Outcome::Declined;
+                    self.audit(&serial, action, &package, user, outcome).await;
+                }
+                None
+            }
+        };
+    }
+
+    /// The app under the cursor in the report view
+    pub fn selected_app(&self) -> Option<&str> {
+        let scan = self.scan.as_ref()?;
+        scan.rows(self.filter)
+            .into_iter()
+            .nth(self.cursor)
+            .and_then(|row| match row {
+                Row::App(name, _) | Row::AppFinding(name, _) => Some(name),
+                _ => None,
+            })
+    }
+
+    /// The user of the app under the cursor, if all of its findings are from the same secondary user
+    fn selected_user(&self) -> Option<u32> {
+        let scan = self.scan.as_ref()?;
+        match scan.rows(self.filter).into_iter().nth(self.cursor)? {
+            Row::AppFinding(_, sus) => sus.user,
+            Row::App(_, infos) => {
+                let mut users = infos.iter().map(|sus| sus.user);
+                let user = users.next()??;
+                users.all(|other| other == Some(user)).then_some(user)
+            }
+            _ => None,
+        }
+    }
+
+    /// Ask for confirmation before modifying the device
+    pub fn request_remediation(&mut self, action: Remediation) {
+        if self.options.read_only {
+            let msg = action_msg(action, Msg::ReadOnlyUninstall, Msg::ReadOnlyDisable);
+            self.status = Some(self.options.lang.get(msg).to_string());
+        } else if let (Some(scan), Some(package)) = (&self.scan, self.selected_app()) {
+            self.prompt = Some(Prompt::Confirm {
+                action,
+                package: package.to_string(),
+                user: self.selected_user(),
+                serial: scan.device.serial.clone(),
+            });
+        }
+    }
+
+    /// Run the confirmed action on the scanned device in the background
+    pub fn remediate(
+        &mut self,
+        action: Remediation,
+        serial: String,
+        package: String,
+        user: Option<u32>,
+    ) {
+        let adb_host = self.adb_host.clone();
+        let events_tx = self.events_tx.clone();
+        let read_only = self.options.read_only;
+        self.status = Some(self.options.lang.format(
+            action_msg(action, Msg::Uninstalling, Msg::Disabling),
+            &[("package", &remediate::label(&package, user))],
+        ));
+        tokio::spawn(async move {
+            let ret = async {
+                if read_only {
+                    bail!("Refusing to modify the device in read-only mode");
+                }
+                let device = adb_host
+                    .device_or_default(Some(&serial), AndroidStorageInput::Auto)
+                    .await
+                    .with_context(|| anyhow!("Failed to access device: {serial:?}"))?;
+                remediate::run(&device, action, &package, user).await
+            }
+            .await
+            .map_err(|err| format!("{err:#}"));
+            events_tx
+                .send(Message::RemediationEnded {
+                    action,
+                    serial,
+                    package,
+                    user,
+                    ret,
+                })
+                .await
+                .ok();
+        });
+    }
+
+    pub async fn remediation_ended(
+        &mut self,
+        action: Remediation,
+        serial: &str,
+        package: &str,
+        user: Option<u32>,
+        ret: Result<(), String>,
+    ) {
+        let outcome = match ret {
+            Ok(()) => {
+                self.status = Some(self.options.lang.format(
+                    action_msg(action, Msg::Uninstalled, Msg::Disabled),
+                    &[("package", &remediate::label(package, user))],
+                ));
+                remediate::Outcome::Succeeded
+            }
+            Err(error) => {
+                error!("Remediation has failed: {error}");
+                self.status = Some(error.clone());
+                remediate::Outcome::Failed { error }
+            }
+        };
+        self.audit(serial, action, package, user, outcome).await;
+    }
+
+    /// Record the requested remediation and how it went in the audit log
+    async fn audit(
+        &mut self,
+        serial: &str,
+        action: Remediation,
+        package: &str,
+        user: Option<u32>,
+        outcome: remediate::Outcome,
+    ) {
+        let entry = remediate::AuditEntry::new(serial, action, package, user, outcome);
+        let path = self.repository.audit_log_path();
+        if let Err(err) = remediate::audit(&path, &entry).await {
+            error!("{err:#}");
+            self.status = Some(format!("{err:#}"));
+        }
+    }
+
+    /// Ring the terminal bell if the finding is severe enough, this also works over ssh
+    pub fn alert(&self, level: SuspicionLevel) {
+        if self.options.bell.is_some_and(|min| level >= min) {
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x07").and_then(|_| stdout.flush()).ok();
+        }
+    }
+
+    /// Where reports and bugreports are written to
+    pub fn export_dir(&self) -> PathBuf {
+        self.config
+            .export_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Download a bugreport in the background, the outcome is shown in the status line
+    pub fn capture_bugreport(&mut self, serial: String) {
+        let adb_host = self.adb_host.clone();
+        let events_tx = self.events_tx.clone();
+        let dir = self.export_dir();
+        let read_only = self.options.read_only;
+        tokio::spawn(async move {
+            let ret = async {
+                let device = adb_host
+                    .device_or_default(Some(&serial), AndroidStorageInput::Auto)
+                    .await
+                    .with_context(|| anyhow!("Failed to access device: {serial:?}"))?;
+                bugreport::capture(&device, &dir, read_only).await
+            }
+            .await
+            .map_err(|err| format!("{err:#}"));
+            events_tx
+                .send(Message::BugreportEnded { serial, ret })
+                .await
+                .ok();
+        });
+        self.status = Some(self.options.lang.get(Msg::CapturingBugreport).to_string());
+    }
+
+    /// Add the findings of the scan to the history, if enabled
+    pub async fn record_history(&mut self, id: u64) {
+        if !self.options.keep_history {
+            return;
+        }
+        let Some(scan) = self.scan_by_id(id) else {
+            return;
+        };
+        let mut report = scan.report(None);
+        if self.options.anonymize {
+            report = report.anonymize();
+        }
+        let record = history::Record::from_report(&report, utils::now());
+        if let Err(err) = history::save(&self.repository.history_path(), &record).await {
+            error!("Failed to record scan history: {err:#}");
+            self.status = Some(
+                self.options
+                    .lang
+                    .format(Msg::HistoryFailed, &[("err", &format!("{err:#}"))]),
+            );
+        }
+    }
+
+    /// Archive the full json report and the collected files of the scan into a zip file
+    pub async fn export_bundle(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let mut report = scan.report(None);
+        if self.options.anonymize {
+            report = report.anonymize();
+        }
+        // bugreports contain the serial and account names, they can't be anonymized
+        let artifacts = if self.options.anonymize {
+            &[][..]
+        } else {
+            &scan.artifacts[..]
+        };
+        let ret = bundle::write(&self.export_dir(), &report, artifacts, utils::now()).await;
+        let artifacts = artifacts.len();
+        let lang = self.options.lang;
+        self.status = Some(match ret {
+            Ok(path) => {
+                let status = lang.format(
+                    Msg::BundleWritten,
+                    &[
+                        ("count", &artifacts.to_string()),
+                        ("path", &format!("{path:?}")),
+                    ],
+                );
+                self.saved_files.push(path);
+                status
+            }
+            Err(err) => {
+                error!("Failed to write evidence bundle: {err:#}");
+                lang.format(Msg::BundleFailed, &[("err", &format!("{err:#}"))])
+            }
+        });
+    }
+
+    /// Query a few details of the device in the background and show them
+    pub fn open_dashboard(&mut self, device: DeviceInfo) {
+        let adb_host = self.adb_host.clone();
+        let events_tx = self.events_tx.clone();
+        let serial = device.serial.clone();
+        tokio::spawn(async move {
+            let dashboard = async {
+                let device = adb_host
+                    .device_or_default(Some(&serial), AndroidStorageInput::Auto)
+                    .await
+                    .with_context(|| anyhow!("Failed to access device: {serial:?}"))?;
+                dashboard::collect(&device).await
+            }
+            .await
+            .map_err(|err| format!("{err:#}"));
+            events_tx
+                .send(Message::DashboardLoaded { serial, dashboard })
+                .await
+                .ok();
+        });
+        self.dashboard = Some(DashboardView {
+            device,
+            dashboard: None,
+        });
+    }
+
+    /// Write the overview of the device to a file, without scanning it
+    pub async fn export_profile(&mut self) {
+        let Some(DashboardView {
+            device,
+            dashboard: Some(Ok(dashboard)),
+        }) = &self.dashboard
+        else {
+            return;
+        };
+        let now = utils::now();
+        let mut profile = dashboard::Profile {
+            serial: device.serial.as_str().into(),
+            device: &device.info,
+            time: now,
+            dashboard,
+        };
+        if self.options.anonymize {
+            profile = profile.anonymize();
+        }
+        let path = self
+            .export_dir()
+            .join(dashboard::filename(&profile.serial, now));
+        let lang = self.options.lang;
+        self.status = Some(match dashboard::write(&path, &profile).await {
+            Ok(()) => {
+                let status = lang.format(Msg::ProfileWritten, &[("path", &format!("{path:?}"))]);
+                self.saved_files.push(path);
+                status
+            }
+            Err(err) => {
+                error!("Failed to export device profile: {err:#}");
+                lang.format(Msg::ProfileFailed, &[("err", &format!("{err:#}"))])
+            }
+        });
+    }
+
+    /// Save the report with its notes, review marks and view state, for handing it to another analyst
+    pub async fn save_session(&mut self) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        let session = scan.to_session(self.filter, self.cursor);
+        let path = self
+            .export_dir()
+            .join(session::filename(&session.serial, session.time));
+        let lang = self.options.lang;
+        self.status = Some(match session::save(&path, &session).await {
+            Ok(()) => {
+                let status = lang.format(Msg::SessionWritten, &[("path", &format!("{path:?}"))]);
+                self.saved_files.push(path);
+                status
+            }
+            Err(err) => {
+                error!("Failed to save session: {err:#}");
+                lang.format(Msg::SessionFailed, &[("err", &format!("{err:#}"))])
+            }
+        });
+    }
+
+    /// Show the report of a saved session, as the previous analyst has left it
+    pub async fn open_session(&mut self, session: session::Session) -> Result<()> {
+        // the report is shown right away, later scans use the default selection of rule files
+        self.confirm_rule_selection();
+        if let Some((scan, _)) = self.background_scan.take() {
+            self.push_summary(&scan);
+        }
+        if let Some(scan) = self.scan.take() {
+            self.push_summary(&scan);
+        } else {
+            self.save_cursor().await?;
+        }
+        self.filter = session.filter;
+        let cursor = session.cursor;
+        let scan = Scan::from_session(session);
+        self.cursor = cursor.min(scan.rows(self.filter).len().saturating_sub(1));
+        self.offset = 0;
+        self.status = Some(self.options.lang.format(
+            Msg::SessionOpened,
+            &[(
+                "serial",
+                &format!("{:?}", self.display(&scan.device.serial)),
+            )],
+        ));
+        self.scan = Some(scan);
+        Ok(())
+    }
+
+    /// Continue with the rule files that have been picked in the selector
+    pub fn confirm_rule_selection(&mut self) {
+        let Some(files) = &self.rule_selector else {
+            return;
+        };
+        let selected = files
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(file, _)| file.name.clone())
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            self.status = Some(self.options.lang.get(Msg::SelectRuleFile).to_string());
+            return;
+        }
+        self.selected_rules = Some(selected);
+        self.rule_selector = None;
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    /// Validate the rules after they've been edited and use them for the next scan
+    pub async fn rules_edited(&mut self, path: &Path, ret: Result<()>) {
+        let ret = async {
+            ret?;
+            let buf = fs::read(path)
+                .await
+                .with_context(|| anyhow!("Failed to read rules from file: {path:?}"))?;
+            let name = path.to_string_lossy().into_owned();
+            let mut rules = Rules::default();
+            let sha256 = rules
+                .load_yaml(&name, &buf)
+                .with_context(|| anyhow!("Rules in {path:?} are invalid"))?;
+            Ok::<_, Error>((name, sha256))
+        }
+        .await;
+
+        match ret {
+            Ok((name, sha256)) => {
+                self.select_rule_file(name).await;
+                self.status = Some(self.options.lang.format(
+                    Msg::RulesLoaded,
+                    &[("path", &format!("{path:?}")), ("sha256", &sha256)],
+                ));
+            }
+            Err(err) => self.status = Some(format!("{err:#}")),
+        }
+    }
+
+    /// Use the rule file for the next scan, in addition to the ones already selected
+    async fn select_rule_file(&mut self, name: String) {
+        let selected = self.selected_rules.get_or_insert_with(|| {
+            self.repository
+                .content
+                .iter()
+                .flat_map(|content| content.files.keys().cloned())
+                .collect()
+        });
+        if !selected.contains(&name) {
+            selected.push(name);
+        }
+        if self.rule_stats.is_some() {
+            self.open_rule_stats().await;
+        }
+    }
+
+    /// Download the rules from the configured url in the background
+    pub fn start_rules_update(&mut self) {
+        let Some(url) = self.config.rules_url.clone() else {
+            self.status = Some(self.options.lang.get(Msg::NoRulesUrl).to_string());
+            return;
+        };
+        if self.updating_rules {
+            self.status = Some(self.options.lang.get(Msg::RulesUpdateRunning).to_string());
+            return;
+        }
+        self.updating_rules = true;
+        self.status = Some(
+            self.options
+                .lang
+                .format(Msg::DownloadingRules, &[("url", &format!("{url:?}"))]),
+        );
+        let repo = self.repository.clone();
+        let events_tx = self.events_tx.clone();
+        tokio::spawn(async move {
+            let ret = repo
+                .update_from_url(&url)
+                .await
+                .map_err(|err| format!("{err:#}"));
+            events_tx
+                .send(Message::RulesUpdated { url, ret })
+                .await
+                .ok();
+        });
+    }
+
+    pub async fn rules_updated(&mut self, url: &str, ret: Result<String, String>) {
+        self.updating_rules = false;
+        match ret {
+            Ok(sha256) => {
+                let path = self.repository.remote_rules_path();
+                self.select_rule_file(path.to_string_lossy().into_owned())
+                    .await;
+                self.status = Some(self.options.lang.format(
+                    Msg::RulesUpdated,
+                    &[("url", &format!("{url:?}")), ("sha256", &sha256)],
+                ));
+            }
+            Err(err) => {
+                error!("Failed to update rules: {err}");
+                self.status = Some(
+                    self.options
+                        .lang
+                        .format(Msg::RulesUpdateFailed, &[("err", &err)]),
+                );
+            }
+        }
+    }
+
+    pub async fn open_rule_stats(&mut self) {
+        let stats = self
+            .repository
+            .load_rules(self.selected_rules.as_deref())
+            .await
+            .map(|rules| rules.sources().to_vec())
+            .map_err(|err| format!("{err:#}"));
+        self.rule_stats = Some(stats);
+    }
+
+    pub fn start_scan(&mut self, device: DeviceInfo) {
+        let settings = self.scan_settings();
+        if let Some((scan, _)) = self.background_scan.take() {
+            self.push_summary(&scan);
+        }
+        let (cancel_tx, cancel_rx) = mpsc::channel(1);
+        let scan = Scan::new(device.clone(), cancel_tx);
+        self.spawn_scan(scan.id, cancel_rx, device, settings);
+        self.scan = Some(scan);
+    }
+
+    /// Switch between a full scan and one that leaves out the apps, for the next scan that is started
+    pub fn toggle_skip_apps(&mut self) {
+        if self.active_scan().is_some_and(|scan| scan.cancel.is_some()) {
+            self.status = Some(self.options.lang.get(Msg::ScanRunning).to_string());
+            return;
+        }
+        self.skip_apps = !self.skip_apps;
+        self.status = Some(
+            self.options
+                .lang
+                .get(if self.skip_apps {
+                    Msg::AppsSkipped
+                } else {
+                    Msg::AppsScanned
+                })
+                .to_string(),
+        );
+    }
+
+    /// Run the checks again that reported warnings, new findings are added to the report
+    pub fn rerun_incomplete(&mut self) {
+        let Some(scan) = &mut self.scan else {
+            return;
+        };
+        if scan.cancel.is_some() {
+            return;
+        }
+        if scan.incomplete.is_empty() {
+            self.status = Some(self.options.lang.get(Msg::NoChecksToRerun).to_string());
+            return;
+        }
+        let incomplete = mem::take(&mut scan.incomplete);
+        scan.warnings
+            .retain(|warning| !incomplete.values().flatten().any(|other| other == warning));
+        let checks = incomplete.into_keys().collect::<BTreeSet<_>>();
+        let device = scan.device.clone();
+        let id = scan.id;
+
+        let names = checks.iter().cloned().collect::<Vec<_>>().join(", ");
+        self.status = Some(
+            self.options
+                .lang
+                .format(Msg::RerunningChecks, &[("checks", &names)]),
+        );
+        let mut settings = self.scan_settings();
+        settings.checks = Some(checks);
+        let (cancel_tx, cancel_rx) = mpsc::channel(1);
+        self.spawn_scan(id, cancel_rx, device, settings);
+        if let Some(scan) = &mut self.scan {
+            scan.cancel = Some(cancel_tx);
+            scan.cancelled = false;
+            scan.duration = None;
+            scan.current_check = None;
+            scan.progress = None;
+            scan.rerun = true;
+            scan.error = None;
+            scan.started = Instant::now();
+        }
+    }
+
+    /// The settings of the config, fingerprints are only recorded along with the history
+    fn scan_settings(&self) -> scan::Settings {
+        let mut settings = scan::Settings::from(&self.config.scan);
+        settings.skip_apps = self.skip_apps;
+        if self.options.keep_history && !self.options.anonymize {
+            settings.fingerprints = Some(self.repository.fingerprints_path());
+        }
+        settings
+    }
+
+    fn spawn_scan(
+        &self,
+        id: u64,
+        mut cancel_rx: mpsc::Receiver<Infallible>,
+        device: DeviceInfo,
+        settings: scan::Settings,
+    ) {
+        let adb_host = self.adb_host.clone();
+        let repo = self.repository.clone();
+        let rules = self.selected_rules.clone();
+        let events_tx = self.events_tx.clone();
+
+        tokio::spawn(async move {
+            let send = |event| {
+                let events_tx = events_tx.clone();
+                async move { events_tx.send(Message::Scan { id, event }).await.ok() }
+            };
+            let mut interval = time::interval(ACTIVITY_TICK_INTERVAL);
+            let scan = run_scan(
+                adb_host,
+                repo,
+                rules,
+                settings,
+                device,
+                id,
+                events_tx.clone(),
+            );
+            tokio::pin!(scan);
+
+            loop {
+                tokio::select! {
+                    _ = cancel_rx.recv() => {
+                        debug!("Scan has been canceled");
+                        send(ScanMessage::Ended { cancelled: true }).await;
+                        break;
+                    }
+                    ret = &mut scan => {
+                        debug!("Scan has completed: {:?}", ret);
+                        if let Err(err) = ret {
+                            send(ScanMessage::Error(format!("{err:#}"))).await;
+                        }
+                        send(ScanMessage::Ended { cancelled: false }).await;
+                        break;
+                    }
+                    _ = interval.tick() => {
+                        send(ScanMessage::Tick).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// The report of the scan with this id, if it hasn't been closed yet
+    fn scan_by_id(&self, id: u64) -> Option<&Scan> {
+        self.scan
+            .iter()
+            .chain(self.background_scan.as_ref().map(|(scan, _)| scan))
+            .find(|scan| scan.id == id)
+    }
+
+    fn scan_by_id_mut(&mut self, id: u64) -> Option<&mut Scan> {
+        self.scan
+            .as_mut()
+            .into_iter()
+            .chain(self.background_scan.as_mut().map(|(scan, _)| scan))
+            .find(|scan| scan.id == id)
+    }
+
+    /// Add an event to the report of its scan, events of scans that have been closed are dropped
+    pub async fn scan_event<B: Backend>(
+        &mut self,
+        terminal: &Terminal<B>,
+        id: u64,
+        event: ScanMessage,
+    ) -> Result<()> {
+        let sorted = !self.options.arrival_order;
+        let Some(scan) = self.scan_by_id_mut(id) else {
+            debug!("Ignoring event of scan that has been closed: {event:?}");
+            return Ok(());
+        };
+        match event {
+            ScanMessage::Suspicion(sus) => {
+                if scan.rerun && scan.has_finding(None, &sus) {
+                    return Ok(());
+                }
+                scan.add_finding(sus.clone(), sorted);
+                self.alert(sus.level);
+                self.follow_finding(terminal, None, &sus)?;
+            }
+            ScanMessage::App { name, sus } => {
+                if scan.rerun && scan.has_finding(Some(&name), &sus) {
+                    return Ok(());
+                }
+                scan.add_app_finding(name.clone(), sus.clone(), sorted);
+                self.alert(sus.level);
+                self.follow_finding(terminal, Some(&name), &sus)?;
+            }
+            ScanMessage::Warning(warning) => {
+                if let Some(check) = &scan.current_check {
+                    scan.incomplete
+                        .entry(check.clone())
+                        .or_default()
+                        .push(warning.clone());
+                }
+                scan.warnings.push(warning);
+            }
+            ScanMessage::CheckStarted(check) => {
+                scan.current_check = Some(check);
+                scan.progress = None;
+            }
+            ScanMessage::Progress { done, total } => {
+                scan.progress = Some((done, total));
+            }
+            ScanMessage::Tick => {
+                scan.spinner.activity_tick();
+            }
+            ScanMessage::Error(err) => {
+                error!("Scan has failed: {err}");
+                scan.error = Some(err);
+            }
+            ScanMessage::Ended { cancelled } => {
+                scan.cancel.take();
+                scan.cancelled = cancelled;
+                scan.duration = Some(scan.started.elapsed());
+                self.record_history(id).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// The report that is shown or has been put aside, it still receives the scan results
+    fn active_scan(&self) -> Option<&Scan> {
+        self.scan
+            .as_ref()
+            .or(self.background_scan.as_ref().map(|(scan, _)| scan))
+    }
+
+    fn active_scan_mut(&mut self) -> Option<&mut Scan> {
+        self.scan
+            .as_mut()
+            .or(self.background_scan.as_mut().map(|(scan, _)| scan))
+    }
+
+    /// Switch between the report and the device list, each view keeps its own position
+    pub async fn toggle_report(&mut self) -> Result<()> {
+        if let Some(scan) = self.scan.take() {
+            self.background_scan = Some((scan, self.current_cursor()));
+            self.restore_cursor().await?;
+        } else if self.is_device_list() {
+            let Some((scan, saved)) = self.background_scan.take() else {
+                self.status = Some(self.options.lang.get(Msg::NoReportToSwitchTo).to_string());
+                return Ok(());
+            };
+            self.save_cursor().await?;
+            self.scan = Some(scan);
+            self.apply_cursor(saved).await?;
+        }
+        Ok(())
+    }
+
+    /// Leave the report view, the scan is cancelled if it's still running
+    pub fn close_scan(&mut self) -> bool {
+        if let Some((scan, _)) = self.background_scan.take() {
+            self.push_summary(&scan);
+        }
+        let Some(scan) = self.scan.take() else {
+            return false;
+        };
+        self.push_summary(&scan);
+        true
+    }
+
+    /// Show a short message in the status line
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some(status);
+    }
+
+    /// Nobody has used the interface for a while and there's a report or device overview to clear
+    pub fn is_idle(&self) -> bool {
+        let Some(timeout) = self.options.idle_timeout else {
+            return false;
+        };
+        let open = self.scan.is_some()
+            || self.background_scan.is_some()
+            || self.dashboard.is_some()
+            || self.rule_stats.is_some()
+            || self.prompt.is_some();
+        open && !self.is_busy() && self.last_input.elapsed() >= timeout
+    }
+
+    /// Clear everything that has been shown about the previous device and return to the device list
+    pub async fn reset_session(&mut self) -> Result<()> {
+        info!("No input for a while, clearing the session");
+        self.prompt = None;
+        self.status = None;
+        self.raw_findings = false;
+        self.skip_apps = self.config.scan.skip_apps;
+        let closed = self.close_scan();
+        let closed = self.dashboard.take().is_some() || closed;
+        let closed = self.rule_stats.take().is_some() || closed;
+        if closed {
+            self.cursor_backtrace.truncate(1);
+            self.restore_cursor().await?;
+        }
+        if self.options.idle_disconnect {
+            let status = process::Command::new("adb")
+                .arg("disconnect")
+                .stdout(std::process::Stdio::null())
+                .status()
+                .await;
+            if let Err(err) = status {
+                warn!("Failed to disconnect devices: {err:#}");
+            }
+        }
+        Ok(())
+    }
+
+    /// A scan or download is running in the background
+    pub fn is_busy(&self) -> bool {
+        self.active_scan().is_some_and(|scan| scan.cancel.is_some())
+            || self
+                .download
+                .as_ref()
+                .is_some_and(|download| download.cancel.is_some())
+    }
+
+    /// What has been done in this session, to leave a trace after the terminal is restored
+    pub fn exit_summary(&self) -> Vec<String> {
+        let lang = self.options.lang;
+        let mut lines = self
+            .scan_summaries
+            .iter()
+            .map(|(_, line)| line.clone())
+            .collect::<Vec<_>>();
+        lines.extend(self.scan.as_ref().map(|scan| scan.summary(lang)));
+        lines.extend(
+            self.background_scan
+                .as_ref()
+                .map(|(scan, _)| scan.summary(lang)),
+        );
+        lines.extend(
+            self.saved_files
+                .iter()
+                .map(|path| lang.format(Msg::Saved, &[("path", &format!("{path:?}"))])),
+        );
+        lines
+            .iter()
+            .map(|line| self.display(line).into_owned())
+            .collect()
+    }
+
+    fn push_summary(&mut self, scan: &Scan) {
+        let line = scan.summary(self.options.lang);
+        self.scan_summaries.push((scan.device.serial.clone(), line));
+    }
+
+    fn current_cursor(&self) -> SavedCursor {
+        SavedCursor {
             offset: self.offset,
             cursor: self.cursor,
             interval: self.current_timer,
-        });
+        }
+    }
+
+    pub async fn save_cursor(&mut self) -> Result<()> {
+        self.cursor_backtrace.push(self.current_cursor());
         self.offset = 0;
         self.cursor = 0;
         self.stop_timer().await?;
         Ok(())
     }
 
+    pub async fn restore_cursor(&mut self) -> Result<()> {
+        let saved = self.cursor_backtrace.pop().unwrap_or_default();
+        self.apply_cursor(saved).await
+    }
+
+    async fn apply_cursor(&mut self, saved: SavedCursor) -> Result<()> {
+        self.offset = saved.offset;
+        self.cursor = saved.cursor;
+        if let Some(interval) = saved.interval {
+            self.start_timer(interval).await?;
+        } else {
+            self.stop_timer().await?;
+        }
+        Ok(())
+    }
+
     pub fn key_up(&mut self) {
         self.cursor = self.cursor.saturating_sub(1);
         if self.cursor < self.offset {
@@ -215,83 +1615,606 @@ impl App {
         }
     }
 
-    pub fn key_down<B: Backend>(&mut self, terminal: &Terminal<B>) -> Result<()> {
-        let max = self.view_length().saturating_sub(1);
-
-        if self.cursor < max {
-            self.cursor += 1;
-            self.recalculate_scroll_offset(terminal)?;
+    pub fn key_down<B: Backend>(&mut self, terminal: &Terminal<B>) -> Result<()> {
+        let max = self.view_length().saturating_sub(1);
+
+        if self.cursor < max {
+            self.cursor += 1;
+            self.recalculate_scroll_offset(terminal)?;
+        }
+
+        Ok(())
+    }
+
+    /// In follow mode, move the cursor to the finding that has just arrived
+    pub fn follow_finding<B: Backend>(
+        &mut self,
+        terminal: &Terminal<B>,
+        app: Option<&str>,
+        sus: &Suspicion,
+    ) -> Result<()> {
+        if !self.follow {
+            return Ok(());
+        }
+        let Some(scan) = &self.scan else {
+            return Ok(());
+        };
+        if let Some(idx) = scan.row_of(self.filter, app, sus) {
+            self.cursor = idx;
+            self.offset = self.offset.min(self.cursor);
+            self.recalculate_scroll_offset(terminal)?;
+        }
+        Ok(())
+    }
+
+    /// Move the cursor to the previous or next high severity finding of the report
+    pub fn jump_high<B: Backend>(&mut self, terminal: &Terminal<B>, forward: bool) -> Result<()> {
+        let Some(scan) = &self.scan else {
+            return Ok(());
+        };
+        match scan.next_high(self.filter, self.cursor, forward) {
+            Some(idx) => {
+                self.cursor = idx;
+                self.offset = self.offset.min(self.cursor);
+                self.recalculate_scroll_offset(terminal)?;
+            }
+            None => {
+                let msg = if forward {
+                    Msg::NoHighBelow
+                } else {
+                    Msg::NoHighAbove
+                };
+                self.status = Some(self.options.lang.get(msg).to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn recalculate_scroll_offset<B: Backend>(&mut self, terminal: &Terminal<B>) -> Result<()> {
+        let mut chrome = SCROLL_CHROME_HEIGHT;
+        if self.legend && self.scan.is_some() {
+            chrome += LEGEND_HEIGHT;
+        }
+        if self.scan.as_ref().is_some_and(|scan| scan.error.is_some()) {
+            chrome += ERROR_HEIGHT;
+        }
+        let scroll_height = (terminal.size()?.height as usize).saturating_sub(chrome);
+        if self.cursor - self.offset > scroll_height {
+            self.offset = self.cursor - scroll_height;
+        }
+        Ok(())
+    }
+
+    pub async fn refresh_devices(&mut self) -> Result<()> {
+        let devices = self
+            .adb_host
+            .devices::<Vec<_>>()
+            .await
+            .map_err(ScanError::AdbUnavailable)?;
+        self.set_devices(devices);
+        Ok(())
+    }
+
+    pub fn set_devices(&mut self, devices: Vec<DeviceInfo>) {
+        self.devices = devices;
+        self.devices_loaded = true;
+        // the cursor may belong to a different view
+        if self.is_device_list() && self.devices.get(self.cursor).is_none() {
+            self.cursor = match self.devices.len() {
+                0 => 0,
+                n => n - 1,
+            };
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Spinner {
+    idx: usize,
+}
+
+impl Spinner {
+    pub fn activity_tick(&mut self) {
+        self.idx += 1;
+        self.idx %= ACTIVITY.len();
+    }
+
+    pub fn render(&self) -> Span<'_> {
+        Span::styled(
+            ACTIVITY[self.idx],
+            Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Download {
+    spinner: Spinner,
+    cancel: Option<mpsc::Sender<Infallible>>,
+}
+
+#[derive(Debug)]
+pub struct DashboardView {
+    device: DeviceInfo,
+    /// `None` while the device is still being queried
+    dashboard: Option<Result<Dashboard, String>>,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    /// messages of the scan task are matched to the report with this id
+    id: u64,
+    device: DeviceInfo,
+    /// when the scan, or the last rerun of its checks, has been started
+    started: Instant,
+    findings: Vec<Suspicion>,
+    apps: IndexMap<String, AppInfos>,
+    expanded: BTreeSet<String>,
+    spinner: Spinner,
+    cancel: Option<mpsc::Sender<Infallible>>,
+    /// wall-clock time of the scan, set once it ended
+    duration: Option<Duration>,
+    /// the scan has been stopped before all checks have run
+    cancelled: bool,
+    /// free-form notes of the analyst, included in exports
+    notes: String,
+    /// conditions that made the scan less complete
+    warnings: Vec<String>,
+    /// findings the analyst has already looked at, by app and description
+    reviewed: HashSet<(Option<String>, String)>,
+    /// files collected from the device, eg. bugreports, included in the evidence bundle
+    artifacts: Vec<PathBuf>,
+    /// the check that is currently running
+    current_check: Option<String>,
+    /// how many items the running check has inspected, out of how many if that's known
+    progress: Option<(usize, Option<usize>)>,
+    /// checks that reported warnings and can be run again, with their warnings
+    incomplete: BTreeMap<String, Vec<String>>,
+    /// only some checks are running again, findings that are already known are skipped
+    rerun: bool,
+    /// group the findings by severity and kind instead of listing them by app
+    tree: bool,
+    /// the groups of the tree that are folded, by severity and optionally kind
+    collapsed: BTreeSet<(SuspicionLevel, Option<Kind>)>,
+    /// why the scan has stopped early, eg. the device became unauthorized
+    error: Option<String>,
+}
+
+/// A line in the findings list
+pub enum Row<'a> {
+    Finding(&'a Suspicion),
+    App(&'a str, &'a AppInfos),
+    AppFinding(&'a str, &'a Suspicion),
+    Warning(&'a str),
+    /// The findings of a severity in the tree, with their number
+    Group(SuspicionLevel, usize),
+    /// The findings of a kind within a severity in the tree, with their number
+    KindGroup(SuspicionLevel, Kind, usize),
+}
+
+/// Most severe findings first, alphabetically within the same level
+fn severity_order(a: &Suspicion, b: &Suspicion) -> Ordering {
+    a.level
+        .cmp(&b.level)
+        .reverse()
+        .then(a.description.cmp(&b.description))
+}
+
+fn is_visible(filter: Option<SuspicionLevel>, level: SuspicionLevel) -> bool {
+    filter.map(|min| level >= min).unwrap_or(true)
+}
+
+impl Scan {
+    pub fn new(device: DeviceInfo, cancel: mpsc::Sender<Infallible>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            device,
+            started: Instant::now(),
+            findings: Vec::new(),
+            apps: IndexMap::new(),
+            expanded: BTreeSet::new(),
+            spinner: Spinner::default(),
+            cancel: Some(cancel),
+            duration: None,
+            cancelled: false,
+            notes: String::new(),
+            warnings: Vec::new(),
+            reviewed: HashSet::new(),
+            artifacts: Vec::new(),
+            current_check: None,
+            progress: None,
+            incomplete: BTreeMap::new(),
+            rerun: false,
+            tree: false,
+            collapsed: BTreeSet::new(),
+            error: None,
+        }
+    }
+
+    /// How far the running check has come, eg. `apps 12/192`
+    pub fn progress_text(&self) -> Option<String> {
+        let (done, total) = self.progress?;
+        let count = match total {
+            Some(total) => format!("{done}/{total}"),
+            None => done.to_string(),
+        };
+        Some(match &self.current_check {
+            Some(check) => format!("{check} {count}"),
+            None => count,
+        })
+    }
+
+    /// Whether the report already has this finding
+    pub fn has_finding(&self, app: Option<&str>, sus: &Suspicion) -> bool {
+        match app {
+            Some(app) => self
+                .apps
+                .get(app)
+                .is_some_and(|infos| infos.iter().any(|other| other == sus)),
+            None => self.findings.contains(sus),
+        }
+    }
+
+    /// Add a finding, either at its position by severity or at the end of the list
+    pub fn add_finding(&mut self, sus: Suspicion, sorted: bool) {
+        if sorted {
+            let idx = self
+                .findings
+                .partition_point(|other| severity_order(other, &sus) != Ordering::Greater);
+            self.findings.insert(idx, sus);
+        } else {
+            self.findings.push(sus);
+        }
+    }
+
+    /// Add a finding to an app, moving the app up once it's more suspicious than the ones above it
+    pub fn add_app_finding(&mut self, name: String, sus: Suspicion, sorted: bool) {
+        self.apps.entry(name).or_default().push(sus);
+        if sorted {
+            self.apps
+                .sort_by(|k1, v1, k2, v2| v1.cmp(v2).reverse().then(k1.cmp(k2)));
+        }
+    }
+
+    /// One line about the device and the number of findings by severity
+    pub fn summary(&self, lang: Lang) -> String {
+        let mut counts = BTreeMap::<SuspicionLevel, usize>::new();
+        for sus in self
+            .findings
+            .iter()
+            .chain(self.apps.values().flat_map(|infos| infos.iter()))
+        {
+            *counts.entry(sus.level).or_default() += 1;
+        }
+        let findings = if counts.is_empty() {
+            lang.get(Msg::NoFindings).to_string()
+        } else {
+            counts
+                .iter()
+                .rev()
+                .map(|(level, n)| format!("{n} {}", lang.level(*level)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut line = lang.format(
+            Msg::Scanned,
+            &[("serial", &format!("{:?}", self.device.serial))],
+        );
+        if let Some(model) = self.device.info.get("model") {
+            line.push_str(&format!(" ({model})"));
+        }
+        line.push_str(&format!(": {findings}"));
+        if self.cancelled || self.cancel.is_some() {
+            line.push_str(&format!(" - {}", lang.get(Msg::SummaryPartial)));
+        } else if !self.warnings.is_empty() {
+            let n = self.warnings.len().to_string();
+            line.push_str(&format!(
+                " - {}",
+                lang.format(Msg::SummaryIncomplete, &[("n", &n)])
+            ));
+        }
+        line
+    }
+
+    /// The number of findings that are hidden by the filter
+    pub fn hidden(&self, filter: Option<SuspicionLevel>) -> usize {
+        self.findings
+            .iter()
+            .chain(self.apps.values().flat_map(|infos| infos.iter()))
+            .filter(|sus| !is_visible(filter, sus.level))
+            .count()
+    }
+
+    /// The row that shows a finding, the app itself if its findings are collapsed
+    pub fn row_of(
+        &self,
+        filter: Option<SuspicionLevel>,
+        app: Option<&str>,
+        sus: &Suspicion,
+    ) -> Option<usize> {
+        self.rows(filter).iter().position(|row| match (row, app) {
+            (Row::Finding(other), None) => *other == sus,
+            (Row::App(name, _), Some(app)) => *name == app && !self.expanded.contains(app),
+            (Row::AppFinding(name, other), Some(app)) => *name == app && *other == sus,
+            (Row::Group(level, _), _) => *level == sus.level && self.is_collapsed(*level, None),
+            (Row::KindGroup(level, kind, _), _) => {
+                *level == sus.level && *kind == sus.kind && self.is_collapsed(*level, Some(*kind))
+            }
+            _ => false,
+        })
+    }
+
+    /// The next row after `cursor` with a high severity finding, or the previous one if not `forward`
+    pub fn next_high(
+        &self,
+        filter: Option<SuspicionLevel>,
+        cursor: usize,
+        forward: bool,
+    ) -> Option<usize> {
+        let rows = self.rows(filter);
+        let is_high = |row: &Row<'_>| match row {
+            Row::Finding(sus) | Row::AppFinding(_, sus) => sus.level == SuspicionLevel::High,
+            // the findings of a collapsed app are represented by the app itself
+            Row::App(name, infos) => !self.expanded.contains(*name) && !infos.high.is_empty(),
+            Row::Group(level, _) => {
+                *level == SuspicionLevel::High && self.is_collapsed(*level, None)
+            }
+            Row::KindGroup(level, kind, _) => {
+                *level == SuspicionLevel::High && self.is_collapsed(*level, Some(*kind))
+            }
+            Row::Warning(_) => false,
+        };
+        if forward {
+            (cursor + 1..rows.len()).find(|&i| is_high(&rows[i]))
+        } else {
+            (0..cursor.min(rows.len()))
+                .rev()
+                .find(|&i| is_high(&rows[i]))
+        }
+    }
+
+    /// The lines of the findings list, with findings below `filter` hidden
+    pub fn rows(&self, filter: Option<SuspicionLevel>) -> Vec<Row<'_>> {
+        if self.tree {
+            let mut rows = self.tree_rows(filter);
+            rows.extend(self.warnings.iter().map(|warning| Row::Warning(warning)));
+            return rows;
+        }
+        let mut rows = Vec::new();
+        for sus in &self.findings {
+            if is_visible(filter, sus.level) {
+                rows.push(Row::Finding(sus));
+            }
+        }
+        for (name, infos) in &self.apps {
+            let mut findings = infos
+                .iter()
+                .filter(|sus| is_visible(filter, sus.level))
+                .peekable();
+            if findings.peek().is_none() {
+                continue;
+            }
+            rows.push(Row::App(name, infos));
+            if self.expanded.contains(name) {
+                rows.extend(findings.map(|sus| Row::AppFinding(name, sus)));
+            }
+        }
+        rows.extend(self.warnings.iter().map(|warning| Row::Warning(warning)));
+        rows
+    }
+
+    /// Every finding with the app it belongs to, if any
+    fn all_findings(&self) -> impl Iterator<Item = (Option<&str>, &Suspicion)> {
+        self.findings.iter().map(|sus| (None, sus)).chain(
+            self.apps
+                .iter()
+                .flat_map(|(name, infos)| infos.iter().map(move |sus| (Some(name.as_str()), sus))),
+        )
+    }
+
+    pub fn is_collapsed(&self, level: SuspicionLevel, kind: Option<Kind>) -> bool {
+        self.collapsed.contains(&(level, kind))
+    }
+
+    /// The findings grouped by severity, then by kind, without the groups that are folded
+    fn tree_rows(&self, filter: Option<SuspicionLevel>) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+        for level in [
+            SuspicionLevel::High,
+            SuspicionLevel::Medium,
+            SuspicionLevel::Low,
+            SuspicionLevel::Info,
+            SuspicionLevel::Good,
+        ] {
+            if !is_visible(filter, level) {
+                continue;
+            }
+            let findings = self
+                .all_findings()
+                .filter(|(_, sus)| sus.level == level)
+                .collect::<Vec<_>>();
+            if findings.is_empty() {
+                continue;
+            }
+            rows.push(Row::Group(level, findings.len()));
+            if self.is_collapsed(level, None) {
+                continue;
+            }
+            for kind in Kind::ALL {
+                let findings = findings
+                    .iter()
+                    .filter(|(_, sus)| sus.kind == *kind)
+                    .collect::<Vec<_>>();
+                if findings.is_empty() {
+                    continue;
+                }
+                rows.push(Row::KindGroup(level, *kind, findings.len()));
+                if self.is_collapsed(level, Some(*kind)) {
+                    continue;
+                }
+                rows.extend(findings.into_iter().map(|(app, sus)| match app {
+                    Some(name) => Row::AppFinding(name, sus),
+                    None => Row::Finding(sus),
+                }));
+            }
+        }
+        rows
+    }
+
+    /// Fold or unfold the group at the given row, returns false if it's not a group
+    pub fn toggle_group(&mut self, filter: Option<SuspicionLevel>, idx: usize) -> bool {
+        let key = match self.rows(filter).get(idx) {
+            Some(Row::Group(level, _)) => (*level, None),
+            Some(Row::KindGroup(level, kind, _)) => (*level, Some(*kind)),
+            _ => return false,
+        };
+        if !self.collapsed.remove(&key) {
+            self.collapsed.insert(key);
+        }
+        true
+    }
+
+    pub fn report(&self, filter: Option<SuspicionLevel>) -> export::Report<'_> {
+        export::Report {
+            serial: self.device.serial.as_str().into(),
+            device: &self.device.info,
+            min_level: filter,
+            notes: Some(self.notes.as_str()).filter(|notes| !notes.is_empty()),
+            warnings: self.warnings.iter().map(String::as_str).collect(),
+            findings: self
+                .findings
+                .iter()
+                .filter(|sus| is_visible(filter, sus.level))
+                .collect(),
+            apps: self
+                .apps
+                .iter()
+                .map(|(name, infos)| {
+                    let findings = infos
+                        .iter()
+                        .filter(|sus| is_visible(filter, sus.level))
+                        .collect::<Vec<_>>();
+                    (name.as_str(), findings)
+                })
+                .filter(|(_, findings)| !findings.is_empty())
+                .collect(),
+            // folded apps and groups still count, the view doesn't matter for the report
+            reviewed: self
+                .all_findings()
+                .filter(|(_, sus)| is_visible(filter, sus.level))
+                .filter(|(app, sus)| self.is_reviewed(*app, sus))
+                .map(|(app, sus)| export::FindingId {
+                    app,
+                    description: &sus.description,
+                })
+                .collect(),
         }
-
-        Ok(())
     }
 
-    pub fn recalculate_scroll_offset<B: Backend>(&mut self, terminal: &Terminal<B>) -> Result<()> {
-        let scroll_height = terminal.size()?.height as usize - SCROLL_CHROME_HEIGHT;
-        if self.cursor - self.offset > scroll_height {
-            self.offset = self.cursor - scroll_height;
+    /// The full triage state of the report, to continue it later or elsewhere
+    pub fn to_session(&self, filter: Option<SuspicionLevel>, cursor: usize) -> session::Session {
+        let mut reviewed = self
+            .reviewed
+            .iter()
+            .map(|(app, description)| session::Reviewed {
+                app: app.clone(),
+                description: description.clone(),
+            })
+            .collect::<Vec<_>>();
+        reviewed.sort_by(|a, b| (&a.app, &a.description).cmp(&(&b.app, &b.description)));
+        session::Session {
+            time: utils::now(),
+            serial: self.device.serial.clone(),
+            device: self.device.info.clone(),
+            notes: self.notes.clone(),
+            warnings: self.warnings.clone(),
+            findings: self.findings.clone(),
+            apps: self
+                .apps
+                .iter()
+                .map(|(name, infos)| (name.clone(), infos.iter().cloned().collect()))
+                .collect(),
+            reviewed,
+            expanded: self.expanded.clone(),
+            artifacts: self.artifacts.clone(),
+            incomplete: self.incomplete.clone(),
+            // a scan that is still running is saved with what has been found so far
+            cancelled: self.cancelled || self.cancel.is_some(),
+            duration: self.duration.map(|duration| duration.as_secs()),
+            filter,
+            cursor,
+            tree: self.tree,
         }
-        Ok(())
     }
 
-    pub async fn refresh_devices(&mut self) -> Result<()> {
-        let devices = self
-            .adb_host
-            .devices::<Vec<_>>()
-            .await
-            .map_err(|e| anyhow!("Failed to list devices from adb: {}", e))?;
-        self.devices = devices;
-        if self.devices.get(self.cursor).is_none() {
-            self.cursor = match self.devices.len() {
-                0 => 0,
-                n => n - 1,
-            };
+    /// A finished report with the triage state of a session
+    pub fn from_session(session: session::Session) -> Self {
+        let device = DeviceInfo {
+            serial: session.serial,
+            info: session.device,
+        };
+        let (cancel, _) = mpsc::channel(1);
+        let mut scan = Scan::new(device, cancel);
+        scan.cancel = None;
+        scan.findings = session.findings;
+        for (name, findings) in session.apps {
+            let infos = scan.apps.entry(name).or_default();
+            for sus in findings {
+                infos.push(sus);
+            }
         }
-        Ok(())
+        scan.expanded = session.expanded;
+        scan.duration = session.duration.map(Duration::from_secs);
+        scan.cancelled = session.cancelled;
+        scan.notes = session.notes;
+        scan.warnings = session.warnings;
+        scan.reviewed = session
+            .reviewed
+            .into_iter()
+            .map(|reviewed| (reviewed.app, reviewed.description))
+            .collect();
+        scan.artifacts = session.artifacts;
+        scan.incomplete = session.incomplete;
+        scan.tree = session.tree;
+        scan
     }
-}
 
-#[derive(Debug, Default)]
-pub struct Spinner {
-    idx: usize,
-}
+    pub fn is_reviewed(&self, app: Option<&str>, sus: &Suspicion) -> bool {
+        self.reviewed
+            .contains(&(app.map(String::from), sus.description.clone()))
+    }
 
-impl Spinner {
-    pub fn activity_tick(&mut self) {
-        self.idx += 1;
-        self.idx %= ACTIVITY.len();
+    /// Toggle the reviewed flag of the finding at the given row
+    pub fn toggle_reviewed(&mut self, filter: Option<SuspicionLevel>, idx: usize) {
+        let key = match self.rows(filter).get(idx) {
+            Some(Row::Finding(sus)) => (None, sus.description.clone()),
+            Some(Row::AppFinding(name, sus)) => (Some(name.to_string()), sus.description.clone()),
+            _ => return,
+        };
+        if !self.reviewed.remove(&key) {
+            self.reviewed.insert(key);
+        }
     }
 
-    pub fn render(&self) -> Span {
-        Span::styled(
-            ACTIVITY[self.idx],
-            Style::default()
-                .fg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
+    pub fn risk_score(&self) -> RiskScore {
+        RiskScore::from_findings(
+            self.findings
+                .iter()
+                .chain(self.apps.values().flat_map(|infos| infos.iter())),
         )
     }
 }
 
-#[derive(Debug, Default)]
-pub struct Download {
-    spinner: Spinner,
-    cancel: Option<mpsc::Sender<Infallible>>,
-}
-
-#[derive(Debug, Default)]
-pub struct Scan {
-    findings: Vec<Suspicion>,
-    apps: IndexMap<String, AppInfos>,
-    expanded: BTreeSet<String>,
-    spinner: Spinner,
-    cancel: Option<mpsc::Sender<Infallible>>,
-}
-
 #[derive(Debug, PartialEq, Eq, Default)]
 pub struct AppInfos {
     high: Vec<Suspicion>,
     medium: Vec<Suspicion>,
     low: Vec<Suspicion>,
+    info: Vec<Suspicion>,
 }
 
 impl AppInfos {
@@ -300,25 +2223,28 @@ impl AppInfos {
             SuspicionLevel::High => self.high.push(item),
             SuspicionLevel::Medium => self.medium.push(item),
             SuspicionLevel::Low => self.low.push(item),
+            SuspicionLevel::Info => self.info.push(item),
             SuspicionLevel::Good => (),
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.high.is_empty() && self.medium.is_empty() && self.low.is_empty()
+        self.high.is_empty()
+            && self.medium.is_empty()
+            && self.low.is_empty()
+            && self.info.is_empty()
     }
 
     pub fn len(&self) -> usize {
-        self.high.len() + self.medium.len() + self.low.len()
+        self.high.len() + self.medium.len() + self.low.len() + self.info.len()
     }
 
-    pub fn iter(
-        &self,
-    ) -> Chain<Chain<Iter<'_, Suspicion>, Iter<'_, Suspicion>>, Iter<'_, Suspicion>> {
+    pub fn iter(&self) -> impl Iterator<Item = &Suspicion> {
         self.high
             .iter()
             .chain(self.medium.iter())
             .chain(self.low.iter())
+            .chain(self.info.iter())
     }
 }
 
@@ -328,6 +2254,7 @@ impl Ord for AppInfos {
             .then(self.high.len().cmp(&other.high.len()))
             .then(self.medium.len().cmp(&other.medium.len()))
             .then(self.low.len().cmp(&other.low.len()))
+            .then(self.info.len().cmp(&other.info.len()))
     }
 }
 
@@ -340,30 +2267,81 @@ impl PartialOrd for AppInfos {
 pub enum Action {
     Shutdown,
     Clear,
+    Suspend,
+    EditRules,
+}
+
+/// Fires when the process is continued after having been stopped (eg. with ctrl+z)
+pub struct ResumeSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl ResumeSignal {
+    pub fn new() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let signal = signal(SignalKind::from_raw(libc::SIGCONT))
+                .context("Failed to register SIGCONT handler")?;
+            Ok(Self { signal })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if self.signal.recv().await.is_some() {
+            return;
+        }
+        std::future::pending::<()>().await
+    }
 }
 
 pub async fn run_scan(
     adb_host: Host,
     repo: Repository,
+    rules: Option<Vec<String>>,
+    settings: scan::Settings,
     device: DeviceInfo,
+    id: u64,
     events_tx: mpsc::Sender<Message>,
 ) -> Result<()> {
     let device = adb_host
         .clone()
         .device_or_default(Some(&device.serial), AndroidStorageInput::Auto)
         .await
-        .with_context(|| anyhow!("Failed to access device: {:?}", device.serial))?;
+        .map_err(|source| ScanError::DeviceUnavailable {
+            serial: Some(device.serial.clone()),
+            source,
+        })?;
 
-    let rules = repo.parse_rules().context("Failed to load rules")?;
-    scan::run(
-        &device,
-        &rules,
-        &scan::Settings { skip_apps: false },
-        &mut scan::ScanNotifier::Channel(events_tx),
-    )
-    .await?;
+    let rules = repo
+        .load_rules(rules.as_deref())
+        .await
+        .map_err(ScanError::Rules)?;
 
-    Ok(())
+    let (tx, mut rx) = mpsc::channel(5);
+    let scan = async move {
+        scan::run(
+            &device,
+            &rules,
+            &settings,
+            &mut scan::ScanNotifier::Channel(tx),
+        )
+        .await
+    };
+    let forward = async {
+        while let Some(event) = rx.recv().await {
+            let event = event.into();
+            if events_tx.send(Message::Scan { id, event }).await.is_err() {
+                break;
+            }
+        }
+    };
+    let (ret, _) = tokio::join!(scan, forward);
+    ret
 }
 
 pub async fn run_download(mut repo: Repository) -> Result<Repository> {
@@ -378,6 +2356,18 @@ pub async fn handle_key<B: Backend>(
     app: &mut App,
     event: Event,
 ) -> Result<Option<Action>> {
+    if let Event::Key(key) = event {
+        if let Some(prompt) = app.prompt.take() {
+            // ignore key releases, they would otherwise type every character twice on windows
+            if key.kind == KeyEventKind::Press {
+                app.prompt_key(prompt, key).await;
+            } else {
+                app.prompt = Some(prompt);
+            }
+            return Ok(None);
+        }
+    }
+
     match event {
         Event::Key(KeyEvent {
             code: KeyCode::Esc,
@@ -398,26 +2388,33 @@ pub async fn handle_key<B: Backend>(
                 drop(tx);
             } else if let Some(tx) = app.scan.as_mut().and_then(|s| s.cancel.take()) {
                 drop(tx);
-            } else if app.scan.take().is_none() {
-                println!("Exiting...");
-                return Ok(Some(Action::Shutdown));
+            } else if app.close_scan()
+                || app.dashboard.take().is_some()
+                || app.rule_stats.take().is_some()
+            {
+                app.restore_cursor().await?;
             } else {
-                let saved = app.cursor_backtrace.pop().unwrap_or_default();
-                app.offset = saved.offset;
-                app.cursor = saved.cursor;
-                if let Some(interval) = saved.interval {
-                    app.start_timer(interval).await?;
-                } else {
-                    app.stop_timer().await?;
-                }
+                println!("{}", app.options.lang.get(Msg::Exiting));
+                return Ok(Some(Action::Shutdown));
             }
         }
+        Event::Key(KeyEvent {
+            code: KeyCode::Backspace | KeyCode::Char('h'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() || app.dashboard.is_some() || app.rule_stats.is_some() => {
+            // dropping the scan also cancels it, if it's still running
+            app.close_scan();
+            app.dashboard.take();
+            app.rule_stats.take();
+            app.restore_cursor().await?;
+        }
         Event::Key(KeyEvent {
             code: KeyCode::Char('Q'),
             modifiers: KeyModifiers::SHIFT,
             ..
         }) => {
-            println!("Exiting...");
+            println!("{}", app.options.lang.get(Msg::Exiting));
             return Ok(Some(Action::Shutdown));
         }
         Event::Key(KeyEvent {
@@ -425,67 +2422,41 @@ pub async fn handle_key<B: Backend>(
             modifiers: KeyModifiers::NONE,
             ..
         }) => {
-            if let Some(scan) = &mut app.scan {
-                if let Some(mut idx) = app.cursor.checked_sub(scan.findings.len()) {
-                    let mut offset = 0;
-                    for (i, (name, appinfos)) in scan.apps.iter().enumerate() {
-                        let height = if scan.expanded.contains(name) {
-                            appinfos.len() + 1
-                        } else {
-                            1
-                        };
+            if app.rule_selector.is_some() {
+                app.confirm_rule_selection();
+            } else if let Some(scan) = app.scan.as_mut().filter(|scan| scan.tree) {
+                scan.toggle_group(app.filter, app.cursor);
+                app.cursor = app
+                    .cursor
+                    .min(scan.rows(app.filter).len().saturating_sub(1));
+            } else if let Some(scan) = &mut app.scan {
+                let rows = scan.rows(app.filter);
+                let name = match rows.get(app.cursor) {
+                    Some(Row::App(name, _) | Row::AppFinding(name, _)) => Some(name.to_string()),
+                    _ => None,
+                };
 
-                        if offset + height > idx {
-                            idx = i;
-                            app.cursor = offset + scan.findings.len();
-                            break;
-                        } else {
-                            offset += height;
-                        }
+                // if there is an app under the cursor
+                if let Some(name) = name {
+                    // move the cursor to the app itself, in case it was on one of its findings
+                    if let Some(idx) = rows
+                        .iter()
+                        .position(|row| matches!(row, Row::App(n, _) if *n == name))
+                    {
+                        app.cursor = idx;
                     }
-
-                    // if there is an item under the cursor
-                    if let Some((name, _appinfos)) = scan.apps.get_index(idx) {
-                        // toggle the app on the `expanded` list
-                        if !scan.expanded.remove(name) {
-                            scan.expanded.insert(name.clone());
-                        }
+                    // toggle the app on the `expanded` list
+                    if !scan.expanded.remove(&name) {
+                        scan.expanded.insert(name);
                     }
                 }
+            } else if let Some(view) = app.dashboard.take() {
+                app.start_scan(view.device);
+            } else if app.rule_stats.is_some() {
+                // nothing to select
             } else if let Some(device) = app.devices.get(app.cursor) {
-                let adb_host = app.adb_host.clone();
-                let repo = app.repository.clone();
                 let device = device.clone();
-                let events_tx = app.events_tx.clone();
-
-                let (cancel_tx, mut cancel_rx) = mpsc::channel(1);
-                tokio::spawn(async move {
-                    let mut interval = time::interval(ACTIVITY_TICK_INTERVAL);
-                    let scan = run_scan(adb_host, repo, device, events_tx.clone());
-                    tokio::pin!(scan);
-
-                    loop {
-                        tokio::select! {
-                            _ = cancel_rx.recv() => {
-                                debug!("Scan has been canceled");
-                                events_tx.send(Message::ScanEnded).await.ok();
-                                break;
-                            }
-                            ret = &mut scan => {
-                                debug!("Scan has completed: {:?}", ret); // TODO print errors in UI
-                                events_tx.send(Message::ScanEnded).await.ok();
-                                break;
-                            }
-                            _ = interval.tick() => {
-                                events_tx.send(Message::ScanTick).await.ok();
-                            }
-                        }
-                    }
-                });
-                app.scan = Some(Scan {
-                    cancel: Some(cancel_tx),
-                    ..Default::default()
-                });
+                app.open_dashboard(device);
                 app.save_cursor().await?;
             }
         }
@@ -538,6 +2509,199 @@ pub async fn handle_key<B: Backend>(
             app.cursor = max;
             app.recalculate_scroll_offset(terminal)?;
         }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(c @ ('[' | ']')),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.jump_high(terminal, c == ']')?;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('f'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.filter = match app.filter {
+                None => Some(SuspicionLevel::Low),
+                Some(SuspicionLevel::Low) => Some(SuspicionLevel::Medium),
+                Some(SuspicionLevel::Medium) => Some(SuspicionLevel::High),
+                Some(_) => None,
+            };
+            app.cursor = app.cursor.min(app.view_length().saturating_sub(1));
+            app.offset = app.offset.min(app.cursor);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('e'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.prompt = Some(Prompt::ExportFormat {
+                filtered: false,
+                selected: 0,
+            });
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('e'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.dashboard.is_some() => {
+            app.export_profile().await;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('E'),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        }) if app.scan.is_some() => {
+            app.prompt = Some(Prompt::ExportFormat {
+                filtered: true,
+                selected: 0,
+            });
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Tab,
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => {
+            app.toggle_report().await?;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('m'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => {
+            app.toggle_mask();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('t'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.toggle_tree();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('s'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.save_session().await;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('?'),
+            ..
+        }) if app.scan.is_some() => {
+            app.legend = !app.legend;
+            app.recalculate_scroll_offset(terminal)?;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('w'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.rerun_incomplete();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('y'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.copy_report();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('n'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => {
+            if let Some(scan) = &app.scan {
+                app.prompt = Some(Prompt::Notes(scan.notes.clone()));
+            }
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('R'),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        }) if app.scan.is_some() => {
+            app.raw_findings = !app.raw_findings;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('F'),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        }) if app.scan.is_some() => {
+            app.follow = !app.follow;
+            let msg = if app.follow {
+                Msg::FollowingFindings
+            } else {
+                Msg::StoppedFollowing
+            };
+            app.status = Some(app.options.lang.get(msg).to_string());
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('b'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => {
+            if let Some(scan) = &app.scan {
+                app.prompt = Some(Prompt::Bugreport {
+                    serial: scan.device.serial.clone(),
+                });
+            }
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('B'),
+            modifiers: KeyModifiers::SHIFT,
+            ..
+        }) if app.scan.is_some() => {
+            app.export_bundle().await;
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('u'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.request_remediation(Remediation::Uninstall);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('u'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.is_device_list() => {
+            app.start_rules_update();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('d'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.scan.is_some() => {
+            app.request_remediation(Remediation::Disable);
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(' '),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => {
+            if let Some(files) = &mut app.rule_selector {
+                if let Some((_, selected)) = files.get_mut(app.cursor) {
+                    *selected = !*selected;
+                }
+            } else if let Some(scan) = &mut app.scan {
+                scan.toggle_reviewed(app.filter, app.cursor);
+            }
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('a'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.is_device_list() => {
+            app.toggle_skip_apps();
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('i'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) if app.is_device_list() => {
+            app.open_rule_stats().await;
+            app.save_cursor().await?;
+        }
         Event::Key(KeyEvent {
             code: KeyCode::Char('r'),
             modifiers: KeyModifiers::CONTROL,
@@ -552,6 +2716,27 @@ pub async fn handle_key<B: Backend>(
         }) => {
             return Ok(Some(Action::Clear));
         }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('e'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        }) => {
+            return Ok(Some(Action::EditRules));
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('z'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        }) => {
+            return Ok(Some(Action::Suspend));
+        }
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('s'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        }) if app.scan.is_some() => {
+            app.quick_save().await;
+        }
         Event::Resize(_columns, _rows) => {
             app.recalculate_scroll_offset(terminal)?;
         }
@@ -599,44 +2784,72 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
     let timer_rx = app.timer_rx.take().context("Timer already started")?;
     tasks.spawn(run_timer(timer_rx, app.events_tx.clone()));
 
+    let mut resume = ResumeSignal::new()?;
+
+    let mut redraw = time::interval(REDRAW_INTERVAL);
+    redraw.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+    let mut idle_check = time::interval(IDLE_CHECK_INTERVAL);
+    idle_check.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
     loop {
         terminal.draw(|f| ui(f, app))?;
 
         tokio::select! {
+            _ = redraw.tick(), if app.is_busy() => (),
+            _ = idle_check.tick(), if app.options.idle_timeout.is_some() => {
+                if app.is_idle() {
+                    app.reset_session().await?;
+                    terminal.clear()?;
+                    // the next person needs to be authorized as well
+                    if app.options.require_consent {
+                        if !wait_for_consent(terminal, &mut stream, app.options.lang).await? {
+                            bail!("Authorization to scan has not been confirmed");
+                        }
+                        let time = utils::format_datetime(utils::now());
+                        app.status = Some(
+                            app.options
+                                .lang
+                                .format(Msg::ConsentConfirmed, &[("time", &time)]),
+                        );
+                    }
+                }
+            }
             event = stream.next() => {
                 let Some(event) = event else { break };
                 let event = event.context("Failed to read terminal input")?;
+                app.last_input = Instant::now();
                 match handle_key(terminal, app, event).await? {
                     Some(Action::Shutdown) => break,
                     Some(Action::Clear) => {
                         terminal.clear()?;
                     },
+                    Some(Action::Suspend) => {
+                        suspend()?;
+                        resume_terminal(terminal)?;
+                    }
+                    Some(Action::EditRules) => {
+                        let path = app.repository.local_rules_path();
+                        leave_terminal()?;
+                        let ret = edit_file(&path).await;
+                        resume_terminal(terminal)?;
+                        app.rules_edited(&path, ret).await;
+                    }
                     None => (),
                 }
             }
+            _ = resume.recv() => {
+                debug!("Process has been continued, restoring terminal");
+                resume_terminal(terminal)?;
+            }
             event = app.events_rx.recv() => {
                 let Some(event) = event else { break };
                 debug!("Received message from channel: event={event:?}");
                 match event {
-                    Message::Suspicion(sus) => {
-                        if let Some(scan) = &mut app.scan {
-                            scan.findings.push(sus);
-                            scan.findings.sort_by(|a, b| {
-                                a.level.cmp(&b.level)
-                                    .reverse()
-                                    .then(a.description.cmp(&b.description))
-                            });
-                        }
+                    Message::Scan { id, event } => {
+                        app.scan_event(terminal, id, event).await?;
                     }
-                    Message::App { name, sus } => {
-                        if let Some(scan) = &mut app.scan {
-                            scan.apps.entry(name).or_default().push(sus);
-                            scan.apps.sort_by(|k1, v1, k2, v2| {
-                                v1.cmp(v2)
-                                    .reverse()
-                                    .then(k1.cmp(k2))
-                            });
-                        }
+                    Message::DevicesLoaded(devices) => {
+                        app.set_devices(devices.map_err(ScanError::AdbUnavailable)?);
                     }
                     Message::StartDownload => {
                         let events_tx = app.events_tx.clone();
@@ -680,29 +2893,61 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
                             ..Default::default()
                         });
                     }
-                    Message::ScanTick => {
-                        if let Some(scan) = &mut app.scan {
-                            scan.spinner.activity_tick();
-                        }
-                    }
-                    Message::ScanEnded => {
-                        if let Some(scan) = &mut app.scan {
-                            scan.cancel.take();
-                        }
-                    }
                     Message::DownloadTick => {
                         if let Some(download) = &mut app.download {
                             download.spinner.activity_tick();
                         }
                     }
-                    Message::DownloadEnded(repo) => {
-                        app.download.take();
-                        if let Some(repo) = repo {
-                            app.repository = repo;
-                        }
+                    Message::DownloadEnded(repo) => {
+                        app.download.take();
+                        if let Some(repo) = repo {
+                            app.repository = repo;
+                        }
+                    }
+                    Message::RulesUpdated { url, ret } => {
+                        app.rules_updated(&url, ret).await;
+                    }
+                    Message::DeviceRefreshTick => {
+                        app.refresh_devices().await?;
+                    }
+                    Message::BugreportEnded { serial, ret } => {
+                        let lang = app.options.lang;
+                        app.status = Some(match ret {
+                            Ok(path) => {
+                                let status = lang.format(
+                                    Msg::BugreportWritten,
+                                    &[("path", &format!("{path:?}"))],
+                                );
+                                if let Some(scan) = app.active_scan_mut() {
+                                    if scan.device.serial == serial {
+                                        scan.artifacts.push(path.clone());
+                                    }
+                                }
+                                app.saved_files.push(path);
+                                status
+                            }
+                            Err(err) => {
+                                error!("Failed to capture bugreport: {err}");
+                                lang.format(Msg::BugreportFailed, &[("err", &err)])
+                            }
+                        });
+                    }
+                    Message::RemediationEnded {
+                        action,
+                        serial,
+                        package,
+                        user,
+                        ret,
+                    } => {
+                        app.remediation_ended(action, &serial, &package, user, ret)
+                            .await;
                     }
-                    Message::DeviceRefreshTick => {
-                        app.refresh_devices().await?;
+                    Message::DashboardLoaded { serial, dashboard } => {
+                        if let Some(view) = &mut app.dashboard {
+                            if view.device.serial == serial {
+                                view.dashboard = Some(dashboard);
+                            }
+                        }
                     }
                     Message::DatabaseUpdateAvailable(update_available) => {
                         if let Some(content) = &mut app.repository.content {
@@ -724,23 +2969,147 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Resul
     Ok(())
 }
 
-fn cursor<'a, T: IntoIterator<Item = Span<'a>>>(msg: T, selected: bool) -> (Line<'a>, Style) {
+/// Split a row into lines that fit into `width` columns
+fn wrap(spans: Vec<Span<'_>>, width: usize) -> Vec<Vec<Span<'_>>> {
+    let width = width.max(1);
+    let mut lines = vec![Vec::new()];
+    let mut used = 0;
+    for span in spans {
+        let mut rest = span.content.as_ref();
+        while !rest.is_empty() {
+            if used == width {
+                lines.push(Vec::new());
+                used = 0;
+            }
+            let split = rest
+                .char_indices()
+                .nth(width - used)
+                .map(|(i, _)| i)
+                .unwrap_or(rest.len());
+            let (head, tail) = rest.split_at(split);
+            used += head.chars().count();
+            if let Some(line) = lines.last_mut() {
+                line.push(Span::styled(head.to_string(), span.style));
+            }
+            rest = tail;
+        }
+    }
+    lines
+}
+
+fn cursor<'a, T: IntoIterator<Item = Span<'a>>>(
+    msg: T,
+    selected: bool,
+    width: usize,
+) -> (Text<'a>, Style) {
     let mut style = Style::default();
     if selected {
         style = style.bg(DARK_GREY);
     }
 
-    let mut row = vec![Span::styled(
-        if selected { " > " } else { "   " },
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-    )];
-    row.extend(msg);
+    let prefix = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    let lines = wrap(msg.into_iter().collect(), width.saturating_sub(3))
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut row = vec![Span::styled(
+                if selected && i == 0 { " > " } else { "   " },
+                prefix,
+            )];
+            row.extend(line);
+            Line::from(row)
+        })
+        .collect::<Vec<_>>();
+
+    (Text::from(lines), style)
+}
+
+/// The message for the action, the verbs can't be filled into a sentence in every language
+fn action_msg(action: Remediation, uninstall: Msg, disable: Msg) -> Msg {
+    match action {
+        Remediation::Uninstall => uninstall,
+        Remediation::Disable => disable,
+    }
+}
+
+/// Scroll further if the wrapped rows up to the cursor don't fit into the view
+fn visible_offset(heights: &[usize], offset: usize, cursor: usize, view: usize) -> usize {
+    let cursor = cursor.min(heights.len().saturating_sub(1));
+    let mut offset = offset.min(cursor);
+    while offset < cursor && heights[offset..=cursor].iter().sum::<usize>() > view {
+        offset += 1;
+    }
+    offset
+}
+
+/// The number of rows that fit into the view, starting from the offset, a row that's cut off counts
+fn visible_rows(heights: &[usize], offset: usize, view: usize) -> usize {
+    let mut lines = 0;
+    heights
+        .iter()
+        .skip(offset)
+        .take_while(|height| {
+            let fits = lines < view;
+            lines += *height;
+            fits
+        })
+        .count()
+}
+
+/// What the colors and symbols of findings mean, as they are shown with these options
+fn legend(options: &Options) -> Vec<Line<'static>> {
+    let lang = options.lang;
+    let mut severity = vec![Span::raw(format!(" {}", lang.get(Msg::LegendSeverity)))];
+    for level in [
+        SuspicionLevel::High,
+        SuspicionLevel::Medium,
+        SuspicionLevel::Low,
+        SuspicionLevel::Info,
+        SuspicionLevel::Good,
+    ] {
+        let label = if options.severity_symbols {
+            format!("{} {}", level.symbol().trim_end(), lang.level(level))
+        } else {
+            lang.level(level).to_string()
+        };
+        severity.push(Span::styled(label, level.terminal_color()));
+        severity.push(Span::raw("  "));
+    }
+    severity.pop();
+
+    let mut markers = vec![Span::raw(format!(
+        " {}",
+        lang.format(
+            Msg::LegendMarkers,
+            &[
+                ("indicator", Suspicion::marker_for(true, options.unicode)),
+                ("advisory", Suspicion::marker_for(false, options.unicode)),
+            ],
+        )
+    ))];
+    if options.kind_symbols {
+        for kind in [
+            Kind::Package,
+            Kind::File,
+            Kind::Network,
+            Kind::Setting,
+            Kind::Other,
+        ] {
+            markers.push(Span::raw(format!(
+                "  {} {}",
+                kind.symbol(options.unicode),
+                lang.kind(kind)
+            )));
+        }
+    }
 
-    (Line::from(row), style)
+    vec![Line::from(severity), Line::from(markers)]
 }
 
 pub fn ui(f: &mut Frame<'_>, app: &App) {
     let white = Style::default().fg(Color::White).bg(Color::Black);
+    let legend = app.legend && app.scan.is_some();
+    let error = app.scan.as_ref().and_then(|scan| scan.error.as_deref());
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -748,7 +3117,13 @@ pub fn ui(f: &mut Frame<'_>, app: &App) {
             [
                 Constraint::Length(1),
                 Constraint::Length(1),
+                Constraint::Length(if error.is_some() {
+                    ERROR_HEIGHT as u16
+                } else {
+                    0
+                }),
                 Constraint::Min(1),
+                Constraint::Length(if legend { LEGEND_HEIGHT as u16 } else { 0 }),
                 Constraint::Length(1),
             ]
             .as_ref(),
@@ -757,36 +3132,230 @@ pub fn ui(f: &mut Frame<'_>, app: &App) {
 
     f.render_widget(render_help_widget(app), chunks[0]);
     f.render_widget(Block::default().style(white), chunks[1]);
-    f.render_widget(render_app_widget(app), chunks[2]);
-    f.render_widget(render_statusline_widget(app), chunks[3]);
+    if let Some(error) = error {
+        let red = Style::default().fg(Color::Red);
+        let block = Block::default()
+            .title(Span::styled(
+                format!(" {} ", app.options.lang.get(Msg::ScanFailed)),
+                red.add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(red);
+        f.render_widget(
+            Paragraph::new(app.display(error)).block(block).style(white),
+            chunks[2],
+        );
+    }
+    f.render_widget(render_app_widget(app, chunks[3]), chunks[3]);
+    if legend {
+        f.render_widget(
+            Paragraph::new(Text::from(self::legend(&app.options))).style(white),
+            chunks[4],
+        );
+    }
+    f.render_widget(render_statusline_widget(app), chunks[5]);
 }
 
-fn render_help_widget(app: &App) -> Paragraph {
+fn render_prompt_widget<'a>(app: &App, prompt: &'a Prompt) -> Paragraph<'a> {
     let white = Style::default().fg(Color::White).bg(Color::Black);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let lang = app.options.lang;
     let mut text = Vec::new();
 
-    if let Some(scan) = &app.scan {
+    match prompt {
+        Prompt::ExportFormat { selected, .. } => {
+            text.push(Span::raw(lang.get(Msg::ExportFormat)));
+            for (i, format) in export::Format::ALL.iter().enumerate() {
+                if i == *selected {
+                    text.push(Span::styled(
+                        format!("[{}]", format.name()),
+                        bold.fg(Color::Green),
+                    ));
+                } else {
+                    text.push(Span::raw(format!(" {} ", format.name())));
+                }
+                text.push(Span::raw(" "));
+            }
+            text.extend([
+                Span::raw(format!("- {}", lang.get(Msg::Press))),
+                Span::styled("ENTER", bold),
+                Span::raw(lang.get(Msg::ToSelect)),
+            ]);
+        }
+        Prompt::ExportPath { format, path, .. } => {
+            text.extend([
+                Span::raw(lang.format(Msg::SaveReportTo, &[("format", format.name())])),
+                Span::styled(path.as_str(), bold),
+                Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                Span::raw(format!(" - {}", lang.get(Msg::Press))),
+                Span::styled("ENTER", bold),
+                Span::raw(lang.get(Msg::ToSave)),
+            ]);
+        }
+        Prompt::Notes(notes) => {
+            text.extend([
+                Span::raw(lang.get(Msg::NotesPrompt)),
+                Span::styled(notes.as_str(), bold),
+                Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+                Span::raw(format!(" - {}", lang.get(Msg::Press))),
+                Span::styled("ENTER", bold),
+                Span::raw(lang.get(Msg::ToSave)),
+            ]);
+        }
+        Prompt::Bugreport { serial } => {
+            text.extend([
+                Span::styled(
+                    lang.format(
+                        Msg::ConfirmBugreport,
+                        &[
+                            ("serial", &format!("{serial:?}")),
+                            ("path", &format!("{:?}", app.export_dir())),
+                        ],
+                    ),
+                    bold.fg(Color::Yellow),
+                ),
+                Span::raw(format!(" - {}", lang.get(Msg::Press))),
+                Span::styled("y", bold),
+                Span::raw(lang.get(Msg::ToConfirm)),
+            ]);
+        }
+        Prompt::Confirm {
+            action,
+            package,
+            user,
+            serial,
+        } => {
+            text.extend([
+                Span::styled(
+                    lang.format(
+                        action_msg(*action, Msg::ConfirmUninstall, Msg::ConfirmDisable),
+                        &[
+                            ("package", &remediate::label(package, *user)),
+                            ("serial", &format!("{serial:?}")),
+                        ],
+                    ),
+                    bold.fg(Color::Red),
+                ),
+                Span::raw(format!(" - {}", lang.get(Msg::Press))),
+                Span::styled("y", bold),
+                Span::raw(lang.get(Msg::ToConfirm)),
+            ]);
+        }
+    }
+    text.extend([
+        Span::styled("ESC", bold),
+        Span::raw(lang.get(Msg::ToCancel)),
+    ]);
+
+    Paragraph::new(Text::from(Line::from(app.mask_spans(text)))).style(white)
+}
+
+fn render_help_widget(app: &App) -> Paragraph<'_> {
+    let white = Style::default().fg(Color::White).bg(Color::Black);
+    let lang = app.options.lang;
+    let mut text = Vec::new();
+
+    if let Some(prompt) = &app.prompt {
+        return render_prompt_widget(app, prompt);
+    }
+
+    if let Some(scan) = app.active_scan() {
         if scan.cancel.is_some() {
             text.push(scan.spinner.render());
-            text.push(Span::raw(" scanning - "));
+            text.push(Span::raw(lang.get(Msg::Scanning)));
+            if let Some(progress) = scan.progress_text() {
+                text.push(Span::raw(format!(" {progress}")));
+            }
+            text.push(Span::raw(" - "));
         }
     }
 
     if let Some(download) = &app.download {
         if download.cancel.is_some() {
             text.push(download.spinner.render());
-            text.push(Span::raw(" downloading - "));
+            text.push(Span::raw(lang.get(Msg::Downloading)));
         }
     }
 
     if text.is_empty() {
-        text.push(Span::raw("idle - "));
+        text.push(Span::raw(lang.get(Msg::Idle)));
+    }
+
+    if app.rule_selector.is_some() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        text.extend([
+            Span::raw(lang.get(Msg::Press)),
+            Span::styled("SPACE", bold),
+            Span::raw(lang.get(Msg::ToToggle)),
+            Span::styled("ENTER", bold),
+            Span::raw(lang.get(Msg::ToContinue)),
+        ]);
+    } else if app.scan.is_some() || app.dashboard.is_some() || app.rule_stats.is_some() {
+        if let Some(scan) = app
+            .scan
+            .as_ref()
+            .filter(|scan| scan.cancel.is_none() && !scan.incomplete.is_empty())
+        {
+            text.extend([
+                Span::raw(lang.get(Msg::Press)),
+                Span::styled("w", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(lang.format(
+                    Msg::ToRerunChecks,
+                    &[("n", &scan.incomplete.len().to_string())],
+                )),
+            ]);
+        }
+        if app.scan.is_some() {
+            text.extend([
+                Span::raw(lang.get(Msg::Press)),
+                Span::styled("?", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(lang.get(if app.legend {
+                    Msg::ToHideLegend
+                } else {
+                    Msg::ForLegend
+                })),
+            ]);
+        }
+        text.extend([
+            Span::raw(lang.get(Msg::Press)),
+            Span::styled("BACKSPACE", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(lang.get(Msg::ToGoBack)),
+        ]);
+    } else {
+        if app.background_scan.is_some() {
+            text.extend([
+                Span::raw(lang.get(Msg::Press)),
+                Span::styled("TAB", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(lang.get(Msg::ToReturnToReport)),
+            ]);
+        }
+        text.extend([
+            Span::raw(lang.get(Msg::Press)),
+            Span::styled("i", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(lang.get(Msg::ForRuleStatistics)),
+            Span::styled("a", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(lang.get(if app.skip_apps {
+                Msg::ToScanApps
+            } else {
+                Msg::ToSkipApps
+            })),
+        ]);
+        if app.config.rules_url.is_some() {
+            text.extend([
+                Span::styled("u", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(lang.get(Msg::ToUpdateRules)),
+            ]);
+        }
+        text.extend([
+            Span::styled("ctrl+E", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(lang.get(Msg::ToEditRules)),
+        ]);
     }
 
     text.extend([
-        Span::raw("Press "),
+        Span::raw(lang.get(Msg::Press)),
         Span::styled("ESC", Style::default().add_modifier(Modifier::BOLD)),
-        Span::raw(" to exit - "),
+        Span::raw(lang.get(Msg::ToExit)),
         Span::raw(concat!(
             env!("CARGO_PKG_NAME"),
             " v",
@@ -799,85 +3368,375 @@ fn render_help_widget(app: &App) -> Paragraph {
         .alignment(Alignment::Right)
 }
 
-fn render_app_widget(app: &App) -> List {
+fn render_app_widget(app: &App, area: Rect) -> List<'_> {
     let white = Style::default().fg(Color::White).bg(Color::Black);
+    let lang = app.options.lang;
+    // the size inside of the borders
+    let width = area.width.saturating_sub(2) as usize;
+    let height = area.height.saturating_sub(2) as usize;
 
     if let Some(scan) = &app.scan {
         let mut list = Vec::new();
-        let mut i = 0;
 
-        for sus in &scan.findings {
+        for (i, row) in scan.rows(app.filter).into_iter().enumerate() {
             let selected = i == app.cursor;
-            let row = sus.to_terminal();
-            let (content, style) = cursor(row, selected);
+            let reviewed = match row {
+                Row::Finding(sus) => scan.is_reviewed(None, sus),
+                Row::AppFinding(name, sus) => scan.is_reviewed(Some(name), sus),
+                _ => false,
+            };
+            let row = match row {
+                Row::Finding(sus) if scan.tree => {
+                    let mut row = vec![Span::raw("        ")];
+                    row.extend(app.render_sus(sus));
+                    row
+                }
+                Row::Finding(sus) => app.render_sus(sus),
+                Row::App(name, findings) => {
+                    let is_expanded = scan.expanded.contains(name);
+
+                    let mut row = Vec::new();
+                    row.push(Span::styled(
+                        if is_expanded { "[-]" } else { "[+]" },
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                    let app_name = lang.format(Msg::AppRow, &[("name", &format!("{name:?}"))]);
+                    row.push(Span::raw(format!(" {app_name} (")));
+
+                    let mut details = Vec::new();
+                    for (level, findings) in [
+                        (SuspicionLevel::High, &findings.high),
+                        (SuspicionLevel::Medium, &findings.medium),
+                        (SuspicionLevel::Low, &findings.low),
+                        (SuspicionLevel::Info, &findings.info),
+                    ] {
+                        if !findings.is_empty() && is_visible(app.filter, level) {
+                            details.push(Span::styled(
+                                format!("{} {}", findings.len(), lang.level(level)),
+                                level.terminal_color(),
+                            ));
+                        }
+                    }
+
+                    for (i, value) in details.into_iter().enumerate() {
+                        if i > 0 {
+                            row.push(Span::raw(", "));
+                        }
+                        row.push(value);
+                    }
+
+                    row.push(Span::raw(")"));
+                    row
+                }
+                Row::AppFinding(name, sus) if scan.tree => {
+                    let app_name = lang.format(Msg::AppRow, &[("name", &format!("{name:?}"))]);
+                    let mut row = vec![Span::raw(format!("        {app_name}: "))];
+                    row.extend(app.render_sus(sus));
+                    row
+                }
+                // show app details if expanded
+                Row::AppFinding(_name, sus) => {
+                    let mut row = vec![Span::raw("    ")];
+                    row.extend(app.render_sus(sus));
+                    row
+                }
+                Row::Group(level, count) => vec![
+                    Span::styled(
+                        if scan.is_collapsed(level, None) {
+                            "[+] "
+                        } else {
+                            "[-] "
+                        },
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{count} {}", lang.level(level)),
+                        level.terminal_color(),
+                    ),
+                ],
+                Row::KindGroup(level, kind, count) => vec![
+                    Span::raw("    "),
+                    Span::styled(
+                        if scan.is_collapsed(level, Some(kind)) {
+                            "[+] "
+                        } else {
+                            "[-] "
+                        },
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!("{} {count}", lang.kind(kind))),
+                ],
+                Row::Warning(warning) => vec![
+                    Span::styled(
+                        lang.get(Msg::WarningTag),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(format!(" {}", app.display(warning))),
+                ],
+            };
+            let row = if reviewed {
+                let mut marked = vec![Span::raw("✓ ")];
+                marked.extend(row);
+                marked
+            } else {
+                row
+            };
+            let (content, mut style) = cursor(row, selected, width);
+            if reviewed {
+                style = style.add_modifier(Modifier::DIM);
+            }
             list.push(ListItem::new(content).style(style));
-            i += 1;
         }
 
-        for (name, findings) in &scan.apps {
-            let selected = i == app.cursor;
-            let is_expanded = scan.expanded.contains(name);
+        // scrolling
+        let heights = list.iter().map(ListItem::height).collect::<Vec<_>>();
+        let offset = visible_offset(&heights, app.offset, app.cursor, height);
+        let visible = visible_rows(&heights, offset, height);
+        let total = list.len();
+        let list = list.into_iter().skip(offset);
 
-            let mut row = Vec::new();
-            row.push(Span::styled(
-                if is_expanded { "[-]" } else { "[+]" },
-                Style::default().add_modifier(Modifier::BOLD),
+        let mut title = vec![Span::styled(
+            lang.get(Msg::Findings),
+            white.add_modifier(Modifier::BOLD),
+        )];
+        if visible < total {
+            // the list overflows, show which part of it is on screen
+            let range = lang.format(
+                Msg::VisibleRange,
+                &[
+                    ("first", &(offset + 1).to_string()),
+                    ("last", &(offset + visible).to_string()),
+                    ("total", &total.to_string()),
+                ],
+            );
+            title.push(Span::raw(format!(" {range}")));
+        }
+        if let Some(filter) = app.filter {
+            title.push(Span::raw(format!(" ({}+)", lang.level(filter))));
+            let hidden = scan.hidden(app.filter);
+            if hidden > 0 {
+                let hidden = lang.format(Msg::HiddenByFilter, &[("n", &hidden.to_string())]);
+                title.push(Span::raw(format!(" - {hidden}")));
+            }
+        }
+        if app.follow {
+            title.push(Span::raw(format!(" - {}", lang.get(Msg::FollowingTitle))));
+        }
+        if !scan.notes.is_empty() {
+            let notes = lang.format(Msg::NotesTitle, &[("notes", &app.display(&scan.notes))]);
+            title.push(Span::raw(format!(" - {notes}")));
+        }
+        if !scan.warnings.is_empty() {
+            let warnings = lang.format(
+                Msg::WarningsTitle,
+                &[("n", &scan.warnings.len().to_string())],
+            );
+            title.push(Span::styled(
+                format!(" - {warnings}"),
+                Style::default().fg(Color::Yellow),
             ));
-            row.push(Span::raw(format!(" App {name:?} (")));
-
-            let mut details = Vec::new();
-            if !findings.high.is_empty() {
-                details.push(Span::styled(
-                    format!("{} high", findings.high.len()),
-                    SuspicionLevel::High.terminal_color(),
-                ));
+        }
+        let mut border = Style::default().fg(Color::Green);
+        if scan.cancelled {
+            // a partial scan must not be mistaken for a clean result
+            border = Style::default().fg(Color::Magenta);
+            title.push(Span::styled(
+                format!(" - {}", lang.get(Msg::ScanCancelled)),
+                border.add_modifier(Modifier::BOLD),
+            ));
+        } else if scan.cancel.is_none() {
+            let score = scan.risk_score();
+            title.push(Span::raw(" - "));
+            title.push(Span::styled(
+                score.to_string(),
+                score.rating().level().terminal_color(),
+            ));
+            if let Some(duration) = scan.duration {
+                let took = lang.format(
+                    Msg::Took,
+                    &[("duration", &utils::format_duration(duration))],
+                );
+                title.push(Span::raw(format!(" - {took}")));
             }
+        }
+        let title = Line::from(title);
+        List::new(list).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(white)
+                .border_style(border)
+                .title(title),
+        )
+    } else if let Some(view) = &app.dashboard {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let field = |key: &'static str, value: String| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("   {key:24}")),
+                Span::styled(value, bold),
+            ]))
+        };
 
-            if !findings.medium.is_empty() {
-                details.push(Span::styled(
-                    format!("{} medium", findings.medium.len()),
-                    SuspicionLevel::Medium.terminal_color(),
+        let device = utils::format_device(&view.device);
+        let mut list = vec![field(
+            lang.get(Msg::Device),
+            app.display(&device).into_owned(),
+        )];
+        match &view.dashboard {
+            None => list.push(ListItem::new(format!(
+                "   {}",
+                lang.get(Msg::QueryingDevice)
+            ))),
+            Some(Err(err)) => list.push(ListItem::new(Span::styled(
+                format!(
+                    "   {}",
+                    lang.format(Msg::QueryDeviceFailed, &[("err", err)])
+                ),
+                Style::default().fg(Color::Red),
+            ))),
+            Some(Ok(dashboard)) => {
+                let optional =
+                    |value: &Option<String>| utils::human_option_str(value.as_ref()).to_string();
+                list.push(field(
+                    lang.get(Msg::AndroidVersion),
+                    optional(&dashboard.android_version),
                 ));
-            }
-
-            if !findings.low.is_empty() {
-                details.push(Span::styled(
-                    format!("{} low", findings.low.len()),
-                    SuspicionLevel::Low.terminal_color(),
+                list.push(field(
+                    lang.get(Msg::SecurityPatch),
+                    optional(&dashboard.security_patch),
+                ));
+                list.push(field(
+                    lang.get(Msg::InstalledApps),
+                    lang.format(
+                        Msg::InstalledAppsCount,
+                        &[
+                            ("total", &dashboard.installed_apps.to_string()),
+                            ("system", &dashboard.system_apps.to_string()),
+                            ("user", &dashboard.user_apps.to_string()),
+                        ],
+                    ),
+                ));
+                list.push(field(
+                    lang.get(Msg::DeviceAdmins),
+                    if dashboard.device_admins.is_empty() {
+                        "-".to_string()
+                    } else {
+                        dashboard.device_admins.join(", ")
+                    },
+                ));
+                list.push(field(
+                    lang.get(Msg::WirelessDebugging),
+                    match dashboard.wireless_debugging {
+                        Some(true) => lang.get(Msg::Enabled),
+                        Some(false) => lang.get(Msg::DisabledSetting),
+                        None => "-",
+                    }
+                    .to_string(),
                 ));
             }
+        }
+        list.push(ListItem::new(""));
+        list.push(ListItem::new(Line::from(vec![
+            Span::raw(format!("   {}", lang.get(Msg::Press))),
+            Span::styled("ENTER", bold),
+            Span::raw(lang.get(Msg::ToStartScan)),
+            Span::styled("e", bold),
+            Span::raw(lang.get(Msg::ToExportProfile)),
+        ])));
 
-            for (i, value) in details.into_iter().enumerate() {
-                if i > 0 {
-                    row.push(Span::raw(", "));
-                }
-                row.push(value);
-            }
-
-            row.push(Span::raw(")"));
+        let title = Span::styled(
+            lang.get(Msg::DeviceOverview),
+            white.add_modifier(Modifier::BOLD),
+        );
+        List::new(list).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(white)
+                .border_style(Style::default().fg(Color::Green))
+                .title(title),
+        )
+    } else if let Some(files) = &app.rule_selector {
+        let list = files
+            .iter()
+            .enumerate()
+            .map(|(i, (file, selected))| {
+                let row = [
+                    Span::styled(
+                        if *selected { "[x] " } else { "[ ] " },
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!(
+                        "{} (sha256={}, {})",
+                        file.name,
+                        file.sha256,
+                        utils::format_datetime(file.date)
+                    )),
+                ];
+                let (content, style) = cursor(row, i == app.cursor, width);
+                ListItem::new(content).style(style)
+            })
+            .collect::<Vec<_>>();
 
-            let (content, style) = cursor(row, selected);
-            list.push(ListItem::new(content).style(style));
+        let heights = list.iter().map(ListItem::height).collect::<Vec<_>>();
+        let offset = visible_offset(&heights, app.offset, app.cursor, height);
+        let list = list.into_iter().skip(offset);
 
-            i += 1;
+        let title = Span::styled(
+            lang.get(Msg::SelectRuleFiles),
+            white.add_modifier(Modifier::BOLD),
+        );
+        List::new(list).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(white)
+                .border_style(Style::default().fg(Color::Green))
+                .title(title),
+        )
+    } else if let Some(stats) = &app.rule_stats {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let field = |key: String, value: String| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("   {key:24}")),
+                Span::styled(value, bold),
+            ]))
+        };
 
-            // show app details if expanded
-            if is_expanded {
-                for sus in findings.iter() {
-                    let selected = i == app.cursor;
-                    let mut row = vec![Span::raw("    ")];
-                    row.extend(sus.to_terminal());
-                    let (content, style) = cursor(row, selected);
-                    list.push(ListItem::new(content).style(style));
-                    i += 1;
+        let mut list = Vec::new();
+        match stats {
+            Err(err) => list.push(ListItem::new(Span::styled(
+                format!("   {}", lang.format(Msg::LoadRulesFailed, &[("err", err)])),
+                Style::default().fg(Color::Red),
+            ))),
+            Ok(sources) => {
+                for source in sources {
+                    list.push(ListItem::new(Line::from(vec![
+                        Span::styled(format!(" {}", source.name), bold),
+                        Span::raw(format!(" (sha256={})", source.sha256)),
+                    ])));
+                    list.push(field(
+                        lang.get(Msg::Rules).to_string(),
+                        source.rules().to_string(),
+                    ));
+                    for (kind, count) in &source.types {
+                        list.push(field(format!("  {kind}"), count.to_string()));
+                    }
+                    for (key, count) in [
+                        (Msg::PackageNames, source.packages),
+                        (Msg::Certificates, source.certificates),
+                        (Msg::Websites, source.websites),
+                        (Msg::C2Domains, source.domains),
+                        (Msg::C2Ips, source.ips),
+                    ] {
+                        list.push(field(lang.get(key).to_string(), count.to_string()));
+                    }
+                    list.push(ListItem::new(""));
                 }
             }
         }
 
-        // scrolling
-        let list = list.into_iter().skip(app.offset);
-
-        let title = Span::styled("Findings", white.add_modifier(Modifier::BOLD));
+        let title = Span::styled(
+            lang.get(Msg::RuleStatistics),
+            white.add_modifier(Modifier::BOLD),
+        );
         List::new(list).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -893,20 +3752,26 @@ fn render_app_widget(app: &App) -> List {
             .map(|(i, device)| {
                 let selected = i == app.cursor;
 
-                let msg = format!(
-                    "{:30} device={:?}, model={:?}, product={:?}",
-                    device.serial,
-                    utils::human_option_str(device.info.get("device")),
-                    utils::human_option_str(device.info.get("model")),
-                    utils::human_option_str(device.info.get("product")),
-                );
+                let msg = app.display(&utils::format_device(device)).into_owned();
 
-                let (content, style) = cursor([Span::raw(msg)], selected);
+                let (content, style) = cursor([Span::raw(msg)], selected, width);
                 ListItem::new(content).style(style)
             })
             .collect();
 
-        let title = Span::styled("Connected devices", white.add_modifier(Modifier::BOLD));
+        let devices = if app.devices_loaded {
+            devices
+        } else {
+            vec![ListItem::new(format!(
+                "   {}",
+                lang.get(Msg::EnumeratingDevices)
+            ))]
+        };
+
+        let title = Span::styled(
+            lang.get(Msg::ConnectedDevices),
+            white.add_modifier(Modifier::BOLD),
+        );
         List::new(devices).block(
             Block::default()
                 .borders(Borders::ALL)
@@ -917,16 +3782,29 @@ fn render_app_widget(app: &App) -> List {
     }
 }
 
-fn render_statusline_widget(app: &App) -> Paragraph {
+fn render_statusline_widget(app: &App) -> Paragraph<'_> {
     let white = Style::default().fg(Color::White).bg(Color::Black);
     let green = Style::default().fg(Color::Green).bg(Color::Black);
     let yellow = Style::default().fg(Color::Yellow).bg(Color::Black);
     let mut text = Vec::new();
 
+    if let Some(status) = &app.status {
+        text.push(Span::raw(app.display(status)));
+        text.push(Span::raw(" - "));
+    }
+
+    if app.is_device_list() && app.skip_apps {
+        text.push(Span::styled(app.options.lang.get(Msg::AppsSkipped), yellow));
+        text.push(Span::raw(" - "));
+    }
+
     if let Some(content) = &app.repository.content {
         text.push(Span::raw("ioc-git:"));
         text.push(Span::styled(&content.git_commit, green));
-        text.push(Span::raw(" released:"));
+        text.push(Span::raw(format!(
+            " {}",
+            app.options.lang.get(Msg::Released)
+        )));
         text.push(Span::styled(
             utils::format_datetime(content.released),
             green,
@@ -934,7 +3812,7 @@ fn render_statusline_widget(app: &App) -> Paragraph {
 
         if content.update_available {
             text.push(Span::styled(
-                " (database update available, press ctrl+R)",
+                format!(" {}", app.options.lang.get(Msg::UpdateAvailable)),
                 yellow,
             ));
         }
@@ -946,6 +3824,43 @@ fn render_statusline_widget(app: &App) -> Paragraph {
         .alignment(Alignment::Right)
 }
 
+/// Ask the operator to confirm they are authorized to scan, before adb is used in any way
+pub async fn ask_consent(lang: Lang) -> Result<bool> {
+    let mut terminal = setup()?;
+    let ret = wait_for_consent(&mut terminal, &mut EventStream::new(), lang).await;
+    cleanup(&mut terminal).ok();
+    ret
+}
+
+async fn wait_for_consent<B: Backend>(
+    terminal: &mut Terminal<B>,
+    stream: &mut EventStream,
+    lang: Lang,
+) -> Result<bool> {
+    loop {
+        terminal.draw(|f| {
+            let block = Block::default()
+                .title(format!(" {} ", lang.get(Msg::ConsentTitle)))
+                .borders(Borders::ALL);
+            let text = format!(
+                "{}\n\n{}",
+                lang.get(Msg::Consent),
+                lang.get(Msg::ConsentKey)
+            );
+            let text = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+            f.render_widget(text, f.area());
+        })?;
+        let Some(event) = stream.next().await else {
+            return Ok(false);
+        };
+        if let Event::Key(key) = event.context("Failed to read terminal input")? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(key.code == KeyCode::Char('y'));
+            }
+        }
+    }
+}
+
 pub fn setup() -> Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -955,9 +3870,498 @@ pub fn setup() -> Result<Terminal<CrosstermBackend<Stdout>>> {
     Ok(terminal)
 }
 
+/// Hand the terminal back to the shell and stop the process, like ctrl+z would outside of raw mode
+fn suspend() -> Result<()> {
+    #[cfg(unix)]
+    {
+        leave_terminal()?;
+        // this returns once we receive SIGCONT
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+    }
+    Ok(())
+}
+
+/// Restore the regular terminal, so another program can use it
+fn leave_terminal() -> Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show)?;
+    Ok(())
+}
+
+/// Open a file in the user's editor and wait for it to exit
+async fn edit_file(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| anyhow!("Failed to create directory at {parent:?}"))?;
+    }
+    if fs::metadata(path).await.is_err() {
+        fs::write(path, LOCAL_RULES_TEMPLATE)
+            .await
+            .with_context(|| anyhow!("Failed to create rules file at {path:?}"))?;
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or(DEFAULT_EDITOR);
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .await
+        .with_context(|| anyhow!("Failed to start editor {program:?}"))?;
+    if !status.success() {
+        bail!("Editor {program:?} has exited with {status}");
+    }
+    Ok(())
+}
+
+/// Re-initialize the terminal after the process has been stopped
+fn resume_terminal<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    terminal.clear()?;
+    Ok(())
+}
+
 pub fn cleanup(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen,)?;
     terminal.show_cursor()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use std::collections::BTreeMap;
+
+    fn scan() -> Scan {
+        let device = DeviceInfo {
+            serial: "ABC123".to_string(),
+            info: BTreeMap::new(),
+        };
+        let (cancel, _) = mpsc::channel(1);
+        Scan::new(device, cancel)
+    }
+
+    fn sus(level: SuspicionLevel, description: &str) -> Suspicion {
+        Suspicion::new(level, Kind::Other, description.to_string())
+    }
+
+    #[test]
+    fn test_legend() {
+        let text = |options: &Options| {
+            legend(options)
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            text(&Options::default()),
+            &[
+                " Severity: high  medium  low  info  good",
+                " [ioc] matches a known indicator  [adv] advisory",
+            ]
+        );
+        let options = Options {
+            severity_symbols: true,
+            kind_symbols: true,
+            unicode: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            text(&options),
+            &[
+                " Severity: [!!!] high  [!!] medium  [!] low  [i] info  [ok] good",
+                " ● matches a known indicator  ○ advisory  ▣ app  ▤ file  ⇄ network  ⚙ setting  • other",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let lines = wrap(
+            vec![Span::styled("[+]", bold), Span::raw(" App \"com.wifi0\"")],
+            8,
+        );
+        assert_eq!(
+            lines,
+            vec![
+                vec![Span::styled("[+]", bold), Span::raw(" App ")],
+                vec![Span::raw("\"com.wif")],
+                vec![Span::raw("i0\"")],
+            ]
+        );
+        assert_eq!(
+            wrap(vec![Span::raw("short")], 80),
+            vec![vec![Span::raw("short")]]
+        );
+    }
+
+    #[test]
+    fn test_visible_offset() {
+        // all rows fit, keep the offset
+        assert_eq!(visible_offset(&[1, 1, 1], 0, 2, 10), 0);
+        // the second row wraps into 3 lines, scroll so the cursor stays visible
+        assert_eq!(visible_offset(&[1, 3, 1, 1], 0, 3, 4), 2);
+        // the offset is never past the cursor
+        assert_eq!(visible_offset(&[1, 1, 1], 2, 1, 4), 1);
+        assert_eq!(visible_offset(&[], 3, 5, 4), 0);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        assert_eq!(visible_rows(&[1, 1, 1], 0, 10), 3);
+        assert_eq!(visible_rows(&[1, 1, 1, 1, 1], 1, 3), 3);
+        // the wrapped row is only partially visible
+        assert_eq!(visible_rows(&[1, 3, 1], 0, 2), 2);
+        assert_eq!(visible_rows(&[], 0, 4), 0);
+    }
+
+    #[test]
+    fn test_add_finding_sorted() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::Low, "b"), true);
+        scan.add_finding(sus(SuspicionLevel::High, "z"), true);
+        scan.add_finding(sus(SuspicionLevel::Low, "a"), true);
+        scan.add_finding(sus(SuspicionLevel::Medium, "m"), true);
+        scan.add_finding(sus(SuspicionLevel::High, "c"), true);
+        assert_eq!(
+            scan.findings,
+            &[
+                sus(SuspicionLevel::High, "c"),
+                sus(SuspicionLevel::High, "z"),
+                sus(SuspicionLevel::Medium, "m"),
+                sus(SuspicionLevel::Low, "a"),
+                sus(SuspicionLevel::Low, "b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_add_finding_arrival_order() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::Low, "b"), false);
+        scan.add_finding(sus(SuspicionLevel::High, "z"), false);
+        scan.add_app_finding(
+            "com.example".to_string(),
+            sus(SuspicionLevel::Low, "x"),
+            false,
+        );
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::High, "y"),
+            false,
+        );
+        assert_eq!(
+            scan.findings,
+            &[
+                sus(SuspicionLevel::Low, "b"),
+                sus(SuspicionLevel::High, "z")
+            ]
+        );
+        assert_eq!(
+            scan.apps.keys().collect::<Vec<_>>(),
+            &["com.example", "com.wifi0"]
+        );
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        let mut scan = scan();
+        scan.cancel = None;
+        scan.add_finding(
+            sus(SuspicionLevel::High, "Play Protect is turned off"),
+            true,
+        );
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::Medium, "Has requested permission"),
+            true,
+        );
+        scan.expanded.insert("com.wifi0".to_string());
+        scan.notes = "Checked with the owner".to_string();
+        scan.reviewed.insert((
+            Some("com.wifi0".to_string()),
+            "Has requested permission".to_string(),
+        ));
+
+        let session = scan.to_session(Some(SuspicionLevel::Low), 2);
+        assert_eq!(session.cursor, 2);
+        assert!(!session.cancelled);
+        let restored = Scan::from_session(session);
+        assert!(restored.cancel.is_none());
+        assert_eq!(restored.findings, scan.findings);
+        assert_eq!(restored.apps, scan.apps);
+        assert_eq!(restored.expanded, scan.expanded);
+        assert_eq!(restored.notes, scan.notes);
+        assert_eq!(restored.reviewed, scan.reviewed);
+    }
+
+    #[test]
+    fn test_has_finding() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::Low, "b"), true);
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::High, "y"),
+            true,
+        );
+        assert!(scan.has_finding(None, &sus(SuspicionLevel::Low, "b")));
+        assert!(!scan.has_finding(None, &sus(SuspicionLevel::High, "y")));
+        assert!(scan.has_finding(Some("com.wifi0"), &sus(SuspicionLevel::High, "y")));
+        assert!(!scan.has_finding(Some("com.example"), &sus(SuspicionLevel::High, "y")));
+    }
+
+    #[test]
+    fn test_toggle_reviewed() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::High, "a"), true);
+        scan.add_app_finding("com.wifi0".to_string(), sus(SuspicionLevel::Low, "b"), true);
+        scan.expanded.insert("com.wifi0".to_string());
+
+        // rows: finding "a", app "com.wifi0", its finding "b"
+        scan.toggle_reviewed(None, 1);
+        assert!(scan.reviewed.is_empty());
+        scan.toggle_reviewed(None, 2);
+        assert!(scan.is_reviewed(Some("com.wifi0"), &sus(SuspicionLevel::Low, "b")));
+        assert!(!scan.is_reviewed(None, &sus(SuspicionLevel::High, "a")));
+        assert_eq!(
+            scan.report(None).reviewed,
+            &[export::FindingId {
+                app: Some("com.wifi0"),
+                description: "b",
+            }]
+        );
+
+        scan.toggle_reviewed(None, 2);
+        assert!(scan.report(None).reviewed.is_empty());
+    }
+
+    #[test]
+    fn test_hidden_advisories() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::High, "a"), true);
+        scan.add_finding(sus(SuspicionLevel::Info, "b"), true);
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::Info, "c"),
+            true,
+        );
+        scan.add_app_finding("com.wifi0".to_string(), sus(SuspicionLevel::Low, "d"), true);
+
+        assert_eq!(scan.hidden(None), 0);
+        assert_eq!(scan.hidden(Some(SuspicionLevel::Low)), 2);
+        assert_eq!(scan.hidden(Some(SuspicionLevel::High)), 3);
+        // the app is still listed for its low finding
+        assert_eq!(scan.rows(Some(SuspicionLevel::Low)).len(), 2);
+    }
+
+    #[test]
+    fn test_next_high() {
+        let mut scan = scan();
+        scan.add_finding(sus(SuspicionLevel::High, "a"), true);
+        scan.add_finding(sus(SuspicionLevel::Low, "b"), true);
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::High, "c"),
+            true,
+        );
+        scan.add_app_finding("com.wifi0".to_string(), sus(SuspicionLevel::Low, "d"), true);
+
+        // rows: finding "a", finding "b", collapsed app "com.wifi0"
+        assert_eq!(scan.next_high(None, 0, true), Some(2));
+        assert_eq!(scan.next_high(None, 2, true), None);
+        assert_eq!(scan.next_high(None, 2, false), Some(0));
+        assert_eq!(scan.next_high(None, 0, false), None);
+
+        // once expanded, the high finding of the app is the target instead
+        scan.expanded.insert("com.wifi0".to_string());
+        assert_eq!(scan.next_high(None, 0, true), Some(3));
+        assert_eq!(scan.next_high(None, 5, false), Some(3));
+    }
+
+    #[test]
+    fn test_progress_text() {
+        let mut scan = scan();
+        assert_eq!(scan.progress_text(), None);
+        scan.progress = Some((12, None));
+        assert_eq!(scan.progress_text().as_deref(), Some("12"));
+        scan.current_check = Some("apps".to_string());
+        scan.progress = Some((12, Some(192)));
+        assert_eq!(scan.progress_text().as_deref(), Some("apps 12/192"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut scan = scan();
+        assert_eq!(
+            scan.summary(Lang::En),
+            "Scanned \"ABC123\": no findings - scan did not complete, partial results"
+        );
+
+        scan.cancel.take();
+        scan.add_finding(sus(SuspicionLevel::High, "a"), true);
+        scan.add_finding(sus(SuspicionLevel::Info, "b"), true);
+        scan.add_app_finding(
+            "com.wifi0".to_string(),
+            sus(SuspicionLevel::High, "c"),
+            true,
+        );
+        scan.warnings.push("Hosts file is not readable".to_string());
+        assert_eq!(
+            scan.summary(Lang::En),
+            "Scanned \"ABC123\": 2 high, 1 info - scan may be incomplete, 1 warnings"
+        );
+        assert_eq!(
+            scan.summary(Lang::Fr),
+            "Analysé \"ABC123\": 2 élevée, 1 info - l'analyse peut être incomplète, 1 avertissements"
+        );
+    }
+
+    #[test]
+    fn test_tree_rows() {
+        let mut scan = scan();
+        let setting = Suspicion {
+            kind: Kind::Setting,
+            ..sus(SuspicionLevel::High, "a")
+        };
+        let package = Suspicion {
+            kind: Kind::Package,
+            ..sus(SuspicionLevel::High, "b")
+        };
+        scan.add_finding(setting.clone(), true);
+        scan.add_app_finding("com.wifi0".to_string(), package.clone(), true);
+        scan.add_app_finding("com.wifi0".to_string(), sus(SuspicionLevel::Low, "c"), true);
+        scan.tree = true;
+
+        let shape = |scan: &Scan| {
+            scan.rows(None)
+                .iter()
+                .map(|row| match row {
+                    Row::Group(level, n) => format!("{} {n}", level.as_str()),
+                    Row::KindGroup(_, kind, n) => format!("  {} {n}", kind.as_str()),
+                    Row::Finding(sus) => format!("    {}", sus.description),
+                    Row::AppFinding(name, sus) => format!("    {name}: {}", sus.description),
+                    _ => "?".to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            shape(&scan),
+            &[
+                "high 2",
+                "  package 1",
+                "    com.wifi0: b",
+                "  setting 1",
+                "    a",
+                "low 1",
+                "  other 1",
+                "    com.wifi0: c",
+            ]
+        );
+
+        // folding the high group hides everything below it, its findings are found at the group
+        assert!(scan.toggle_group(None, 0));
+        assert_eq!(
+            shape(&scan),
+            &["high 2", "low 1", "  other 1", "    com.wifi0: c"]
+        );
+        assert_eq!(scan.row_of(None, Some("com.wifi0"), &package), Some(0));
+        assert_eq!(scan.next_high(None, 1, false), Some(0));
+        assert!(!scan.toggle_group(None, 3));
+
+        scan.toggle_reviewed(None, 3);
+        assert!(scan.toggle_group(None, 2));
+        assert_eq!(scan.report(None).reviewed.len(), 1);
+    }
+
+    #[test]
+    fn test_row_of() {
+        let mut scan = scan();
+        let stalkerware = sus(SuspicionLevel::High, "a");
+        let advisory = sus(SuspicionLevel::Info, "b");
+        scan.add_finding(advisory.clone(), true);
+        scan.add_app_finding("com.wifi0".to_string(), sus(SuspicionLevel::Low, "c"), true);
+        scan.add_app_finding("com.wifi0".to_string(), stalkerware.clone(), true);
+
+        // rows: finding "b", collapsed app "com.wifi0"
+        assert_eq!(scan.row_of(None, None, &advisory), Some(0));
+        assert_eq!(scan.row_of(None, Some("com.wifi0"), &stalkerware), Some(1));
+        assert_eq!(
+            scan.row_of(Some(SuspicionLevel::Low), None, &advisory),
+            None
+        );
+
+        // once the app is expanded its finding has a row of its own
+        scan.expanded.insert("com.wifi0".to_string());
+        assert_eq!(scan.row_of(None, Some("com.wifi0"), &stalkerware), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_scans() {
+        let dir = std::env::temp_dir().join(format!("spytrap-tui-{}", std::process::id()));
+        let repo = Repository::init_at(&dir).await.unwrap();
+        let mut app = App::new(Host::default(), repo, Options::default(), Config::default());
+        let terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        app.options.idle_timeout = Some(Duration::ZERO);
+        assert!(!app.is_idle());
+
+        let first = scan();
+        let first_id = first.id;
+        app.scan = Some(first);
+        app.close_scan();
+        let second = scan();
+        let second_id = second.id;
+        app.scan = Some(second);
+
+        // the closed scan is still winding down while the next one runs
+        for (id, event) in [
+            (
+                first_id,
+                ScanMessage::Suspicion(sus(SuspicionLevel::High, "a")),
+            ),
+            (
+                second_id,
+                ScanMessage::Suspicion(sus(SuspicionLevel::Low, "b")),
+            ),
+            (
+                first_id,
+                ScanMessage::Warning("Failed to list files".to_string()),
+            ),
+            (first_id, ScanMessage::Ended { cancelled: true }),
+        ] {
+            app.scan_event(&terminal, id, event).await.unwrap();
+        }
+        let scan = app.scan.as_ref().unwrap();
+        assert_eq!(scan.findings, &[sus(SuspicionLevel::Low, "b")]);
+        assert!(scan.warnings.is_empty());
+        assert!(scan.cancel.is_some());
+        assert!(!scan.cancelled);
+
+        // a report that has been put aside still receives the results of its scan
+        let cursor = app.current_cursor();
+        app.background_scan = app.scan.take().map(|scan| (scan, cursor));
+        app.scan_event(
+            &terminal,
+            second_id,
+            ScanMessage::Ended { cancelled: false },
+        )
+        .await
+        .unwrap();
+        let (scan, _) = app.background_scan.as_ref().unwrap();
+        assert!(scan.cancel.is_none());
+        assert!(scan.duration.is_some());
+        // and is cleared when the station is left alone
+        assert!(app.is_idle());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
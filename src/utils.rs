@@ -1,5 +1,9 @@
-use chrono::{offset::Utc, TimeZone};
+use crate::errors::*;
+use chrono::{offset::Utc, NaiveDate, NaiveDateTime, TimeZone};
+use forensic_adb::DeviceInfo;
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::time::Duration;
 
 pub fn human_option_str(x: Option<&String>) -> &str {
     if let Some(x) = x {
@@ -9,6 +13,28 @@ pub fn human_option_str(x: Option<&String>) -> &str {
     }
 }
 
+pub fn format_device(device: &DeviceInfo) -> String {
+    let mut msg = format!(
+        "{:30} device={:?}, model={:?}, product={:?}",
+        device.serial,
+        human_option_str(device.info.get("device")),
+        human_option_str(device.info.get("model")),
+        human_option_str(device.info.get("product")),
+    );
+    // the transport id tells apart devices with similar serials
+    if let Some(transport_id) = device.info.get("transport_id") {
+        msg.push_str(&format!(", transport_id={transport_id:?}"));
+    }
+    msg
+}
+
+/// Decode output from the device, returns true if invalid UTF-8 had to be replaced
+pub fn decode_lossy(buf: &[u8]) -> (Cow<'_, str>, bool) {
+    let text = String::from_utf8_lossy(buf);
+    let lossy = matches!(text, Cow::Owned(_));
+    (text, lossy)
+}
+
 pub fn now() -> i64 {
     let now = chrono::offset::Utc::now();
     now.timestamp()
@@ -19,8 +45,168 @@ pub fn format_datetime(timestamp: i64) -> String {
     utc.format("%Y-%m-%d %H:%M UTC").to_string()
 }
 
+/// If the locale of the terminal uses UTF-8, the first of these variables that is set wins
+pub fn is_unicode_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|value| {
+            let value = value.to_ascii_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+}
+
+/// A date with an optional time, in the format that is also used by `dumpsys package`
+pub fn parse_datetime(s: &str) -> Result<NaiveDateTime> {
+    let s = s.trim();
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(datetime);
+        }
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .with_context(|| anyhow!("Invalid date, expected YYYY-MM-DD [HH:MM[:SS]]: {s:?}"))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s}s"),
+        (h, m, s) => format!("{h}h {m}m {s}s"),
+    }
+}
+
+/// If a shell command has failed because the unprivileged adb shell isn't allowed to access a path
+pub fn is_permission_denied(error: &str) -> bool {
+    error.trim_end().ends_with("Permission denied")
+}
+
+/// Hide most of a value, keeping the first and last characters to tell values apart
+pub fn mask(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+    if chars.len() <= 4 {
+        return "*".repeat(chars.len());
+    }
+    let mut out = chars[..2].iter().collect::<String>();
+    out.push_str(&"*".repeat(chars.len() - 4));
+    out.extend(&chars[chars.len() - 2..]);
+    out
+}
+
+fn is_email(word: &str) -> bool {
+    word.split_once('@')
+        .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+}
+
+/// Mask the given serials and anything that looks like an account name (an email address)
+pub fn mask_text<'a>(text: &'a str, serials: &[&str]) -> Cow<'a, str> {
+    let mut text = Cow::Borrowed(text);
+    for serial in serials.iter().filter(|serial| !serial.is_empty()) {
+        if text.contains(serial) {
+            text = Cow::Owned(text.replace(serial, &mask(serial)));
+        }
+    }
+    if !text.contains('@') {
+        return text;
+    }
+
+    let is_word = |c: char| c.is_alphanumeric() || "._%+-@".contains(c);
+    let mut out = String::with_capacity(text.len());
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if is_word(c) {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(start) = start.take() {
+            push_word(&mut out, &text[start..i]);
+        }
+        out.push(c);
+    }
+    if let Some(start) = start {
+        push_word(&mut out, &text[start..]);
+    }
+    Cow::Owned(out)
+}
+
+fn push_word(out: &mut String, word: &str) {
+    if is_email(word) {
+        out.push_str(&mask(word));
+    } else {
+        out.push_str(word);
+    }
+}
+
 pub fn sha256(buf: &[u8]) -> String {
     let mut sha256 = Sha256::new();
     sha256.update(buf);
     hex::encode(sha256.finalize())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_decode_lossy() {
+        assert_eq!(
+            decode_lossy(b"com.wifi0"),
+            (Cow::Borrowed("com.wifi0"), false)
+        );
+        assert_eq!(
+            decode_lossy(b"com.wifi\xff"),
+            (Cow::Owned("com.wifi\u{FFFD}".to_string()), true)
+        );
+    }
+
+    #[test]
+    fn test_mask_text() {
+        assert_eq!(mask("ABC123XYZ"), "AB*****YZ");
+        assert_eq!(mask("abc"), "***");
+        assert_eq!(
+            mask_text("Package \"com.wifi0\" on ABC123XYZ", &["ABC123XYZ"]),
+            "Package \"com.wifi0\" on AB*****YZ"
+        );
+        assert_eq!(
+            mask_text("Account (alice@example.com) is synced", &[]),
+            "Account (al*************om) is synced"
+        );
+        assert_eq!(
+            mask_text("Nothing @ here", &["ABC123XYZ"]),
+            Cow::Borrowed("Nothing @ here")
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(30_500)), "30s");
+        assert_eq!(format_duration(Duration::from_secs(8 * 60 + 2)), "8m 2s");
+        assert_eq!(format_duration(Duration::from_secs(3600 + 61)), "1h 1m 1s");
+    }
+
+    #[test]
+    fn test_format_device_transport_id() {
+        let mut device = DeviceInfo {
+            serial: "emulator-5554".to_string(),
+            info: BTreeMap::from([
+                ("device".to_string(), "generic_x86".to_string()),
+                ("model".to_string(), "Android_SDK_built_for_x86".to_string()),
+            ]),
+        };
+        assert_eq!(
+            format_device(&device),
+            "emulator-5554                  device=\"generic_x86\", model=\"Android_SDK_built_for_x86\", product=\"-\""
+        );
+
+        device
+            .info
+            .insert("transport_id".to_string(), "3".to_string());
+        assert_eq!(
+            format_device(&device),
+            "emulator-5554                  device=\"generic_x86\", model=\"Android_SDK_built_for_x86\", product=\"-\", transport_id=\"3\""
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::errors::*;
+use crate::export::{self, Format};
+use crate::history;
+use crate::rules::Rules;
+use crate::scan::{self, Findings};
+use crate::utils;
+use forensic_adb::{DeviceInfo, Host};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::time;
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Return the devices that weren't connected during the last poll
+fn new_devices(seen: &mut HashSet<String>, devices: Vec<DeviceInfo>) -> Vec<DeviceInfo> {
+    // forget disconnected devices, so they are scanned again when plugged back in
+    seen.retain(|serial| devices.iter().any(|device| device.serial == *serial));
+    devices
+        .into_iter()
+        .filter(|device| seen.insert(device.serial.clone()))
+        .collect()
+}
+
+async fn write_report(
+    device: &DeviceInfo,
+    findings: &Findings,
+    report_dir: &Path,
+    history: Option<&Path>,
+    anonymize: bool,
+) -> Result<PathBuf> {
+    let mut report = findings.report(&device.serial, &device.info);
+    if anonymize {
+        report = report.anonymize();
+    }
+    let now = utils::now();
+    if let Some(dir) = history {
+        history::save(dir, &history::Record::from_report(&report, now)).await?;
+    }
+    let path = report_dir.join(export::filename(&report.serial, now, Format::Json));
+    export::write(&path, Format::Json, &report).await?;
+    Ok(path)
+}
+
+/// Wait for devices to be connected and scan each of them once, until interrupted
+pub async fn run(
+    adb_host: Host,
+    rules: &Rules,
+    settings: &scan::Settings,
+    report_dir: &Path,
+    history: Option<&Path>,
+    anonymize: bool,
+) -> Result<()> {
+    info!("Waiting for devices, reports are written to {report_dir:?}");
+    let mut seen = HashSet::new();
+    let mut interval = time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let devices = adb_host
+            .devices::<Vec<_>>()
+            .await
+            .map_err(ScanError::AdbUnavailable)?;
+
+        for device in new_devices(&mut seen, devices) {
+            info!("Scanning new device: {}", utils::format_device(&device));
+            let findings = scan::scan_device(&adb_host, &device, rules, settings).await;
+            match write_report(&device, &findings, report_dir, history, anonymize).await {
+                Ok(path) => info!("Report for {:?} written to {path:?}", device.serial),
+                Err(err) => error!("Failed to write report for {:?}: {err:#}", device.serial),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn device(serial: &str) -> DeviceInfo {
+        DeviceInfo {
+            serial: serial.to_string(),
+            info: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_new_devices() {
+        let mut seen = HashSet::new();
+        let serials = |devices: Vec<DeviceInfo>| {
+            devices
+                .into_iter()
+                .map(|device| device.serial)
+                .collect::<Vec<_>>()
+        };
+
+        let new = new_devices(&mut seen, vec![device("A"), device("B")]);
+        assert_eq!(serials(new), &["A", "B"]);
+        let new = new_devices(&mut seen, vec![device("A"), device("B")]);
+        assert_eq!(serials(new), Vec::<String>::new());
+        // B got disconnected and plugged back in
+        let new = new_devices(&mut seen, vec![device("A")]);
+        assert_eq!(serials(new), Vec::<String>::new());
+        let new = new_devices(&mut seen, vec![device("A"), device("B"), device("C")]);
+        assert_eq!(serials(new), &["B", "C"]);
+    }
+}